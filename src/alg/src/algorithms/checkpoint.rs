@@ -0,0 +1,314 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use bincode::Options;
+use rand_pcg::Pcg64;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    operators::{codec::Codec, solution::Solution},
+    utilities::rng,
+};
+
+pub const CHECKPOINT_FILE: &str = "checkpoint.bin";
+
+/**
+ * Where and how often an algorithm saves its state. A checkpoint holds everything the
+ * algorithm needs to carry on, including the state of the random number generators, so a
+ * resumed run ends with the same result as one that was never interrupted. Checkpoints are
+ * only valid for the same parameters, instance and, for P-NSGA-II, number of cores.
+ **/
+pub struct Checkpoint<'a, C> {
+    codec: &'a C,
+    path: PathBuf,
+    interval: usize,
+    resume: bool,
+}
+
+impl<'a, C> Checkpoint<'a, C> {
+    /// Saves to `path` about every `interval` evaluations, never if it is 0. When resuming,
+    /// the algorithm continues from the state in `path` if there is one.
+    pub fn new(codec: &'a C, path: PathBuf, interval: usize, resume: bool) -> Checkpoint<'a, C> {
+        Checkpoint {
+            codec,
+            path,
+            interval,
+            resume,
+        }
+    }
+
+    pub fn codec(&self) -> &'a C {
+        self.codec
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.interval > 0
+    }
+
+    /// Whether a checkpoint should be saved after `evaluations` since the last one
+    pub fn is_due(&self, evaluations: usize) -> bool {
+        self.is_enabled() && evaluations >= self.interval
+    }
+
+    /// The saved state of `algorithm` when resuming from an existing checkpoint
+    pub fn load<S: DeserializeOwned>(&self, algorithm: &str) -> Result<Option<S>, String> {
+        let path = &self.path;
+        if !self.resume || !path.exists() {
+            return Ok(None);
+        }
+
+        match read_state(path, algorithm) {
+            Ok(state) => {
+                println!("Resuming {} from {:?}", algorithm, path);
+                Ok(Some(state))
+            }
+            Err(err) => Err(format!(
+                "Could not resume from checkpoint {:?}: {}",
+                path, err
+            )),
+        }
+    }
+
+    /// Writes the state, a failed write is reported but does not stop the run
+    pub fn save<S: Serialize>(&self, algorithm: &str, state: &S) {
+        if let Err(err) = write_state(&self.path, algorithm, state) {
+            eprintln!("Could not write checkpoint {:?}: {}", self.path, err);
+        }
+    }
+}
+
+fn read_state<S: DeserializeOwned>(path: &Path, algorithm: &str) -> Result<S, Box<dyn Error>> {
+    let file = File::open(path)?;
+
+    // Nothing in the file can be longer than the file, so a corrupt length fails instead of
+    // being allocated
+    let options = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(file.metadata()?.len());
+    let mut reader = BufReader::new(file);
+
+    let saved_by: String = options.deserialize_from(&mut reader)?;
+    if saved_by != algorithm {
+        return Err(format!("it was saved by {}, not {}", saved_by, algorithm).into());
+    }
+
+    Ok(options.deserialize_from(reader)?)
+}
+
+// Writes to a temporary file first, so being stopped mid-write keeps the last checkpoint
+fn write_state<S: Serialize>(
+    path: &Path,
+    algorithm: &str,
+    state: &S,
+) -> Result<(), Box<dyn Error>> {
+    let tmp_path = path.with_extension("tmp");
+
+    {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        bincode::serialize_into(&mut writer, algorithm)?;
+        bincode::serialize_into(&mut writer, state)?;
+    }
+
+    fs::rename(tmp_path, path)?;
+
+    Ok(())
+}
+
+/// State of the local searches that run one weight vector per task (PPLS/D and SPPLS)
+#[derive(Serialize, Deserialize)]
+pub struct LocalSearchState<E> {
+    pub init_archive: Vec<Solution<E>>,
+    pub seeds: Vec<u64>,
+    pub weights: Vec<Option<WeightState<E>>>,
+}
+
+impl<E> LocalSearchState<E> {
+    /// Checks every saved solution can be decoded by `codec`
+    pub fn check<X, C: Codec<X, Encoded = E>>(&self, codec: &C) -> Result<(), String> {
+        codec.check_all(&self.init_archive)?;
+
+        for weight in self.weights.iter().flatten() {
+            codec.check_all(&weight.archive)?;
+            codec.check_all(&weight.unexplored_archive)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WeightState<E> {
+    pub archive: Vec<Solution<E>>,
+    pub unexplored_archive: Vec<Solution<E>>,
+    pub evaluations: usize,
+    pub rng: Pcg64,
+    pub finished: bool,
+}
+
+/**
+ * Collects the states of the weight vectors of a local search as the parallel tasks report
+ * them and saves all of them together. Each task reports after every `interval / weights`
+ * of its own evaluations, the checkpoint is written once `interval` evaluations have been
+ * reported since the last one.
+ **/
+pub struct WeightCheckpoints<'c, 'a, C, E> {
+    checkpoint: &'c Checkpoint<'a, C>,
+    algorithm: &'static str,
+    task_interval: usize,
+    state: Mutex<(LocalSearchState<E>, usize)>,
+}
+
+impl<'c, 'a, C, E> WeightCheckpoints<'c, 'a, C, E>
+where
+    E: Serialize,
+{
+    /// Starts collecting, saving the initial state straight away
+    pub fn new<X>(
+        checkpoint: &'c Checkpoint<'a, C>,
+        algorithm: &'static str,
+        init_archive: &[Solution<X>],
+        seeds: &[u64],
+        weights: Vec<Option<WeightState<E>>>,
+    ) -> WeightCheckpoints<'c, 'a, C, E>
+    where
+        C: Codec<X, Encoded = E>,
+    {
+        let init_archive = if checkpoint.is_enabled() {
+            checkpoint.codec().encode_all(init_archive)
+        } else {
+            Vec::new()
+        };
+
+        let task_interval = (checkpoint.interval / weights.len().max(1)).max(1);
+
+        let state = LocalSearchState {
+            init_archive,
+            seeds: seeds.to_vec(),
+            weights,
+        };
+
+        if checkpoint.is_enabled() {
+            checkpoint.save(algorithm, &state);
+        }
+
+        WeightCheckpoints {
+            checkpoint,
+            algorithm,
+            task_interval,
+            state: Mutex::new((state, 0)),
+        }
+    }
+
+    /// Whether the task should report after `evaluations` since its last report
+    pub fn is_due(&self, evaluations: usize) -> bool {
+        self.checkpoint.is_enabled() && evaluations >= self.task_interval
+    }
+
+    /**
+     * Records the state of the search on one weight vector, with `new_evaluations` done
+     * since its last report. Must be called from the task, as it saves the task's generator.
+     **/
+    pub fn update<X>(
+        &self,
+        weight: usize,
+        archive: &[Solution<X>],
+        unexplored_archive: &[Solution<X>],
+        evaluations: usize,
+        finished: bool,
+        new_evaluations: usize,
+    ) where
+        C: Codec<X, Encoded = E>,
+    {
+        if !self.checkpoint.is_enabled() {
+            return;
+        }
+
+        let codec = self.checkpoint.codec();
+        let weight_state = WeightState {
+            archive: codec.encode_all(archive),
+            unexplored_archive: codec.encode_all(unexplored_archive),
+            evaluations,
+            rng: rng::state(),
+            finished,
+        };
+
+        let mut guard = self.state.lock().unwrap();
+        let (state, unsaved) = &mut *guard;
+
+        state.weights[weight] = Some(weight_state);
+        *unsaved += new_evaluations;
+
+        let all_finished = state
+            .weights
+            .iter()
+            .all(|weight| weight.as_ref().map_or(false, |weight| weight.finished));
+
+        if self.checkpoint.is_due(*unsaved) || all_finished {
+            self.checkpoint.save(self.algorithm, state);
+            *unsaved = 0;
+        }
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Offset;
+
+    impl Codec<usize> for Offset {
+        type Encoded = u32;
+
+        fn encode(&self, x: &usize) -> u32 {
+            *x as u32 + 1
+        }
+
+        fn decode(&self, encoded: &u32) -> usize {
+            *encoded as usize - 1
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let folder = std::env::temp_dir().join("nfv_mls_conf_test_checkpoint");
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join(CHECKPOINT_FILE);
+
+        let solutions = vec![Solution::new(vec![0, 4]), Solution::new(vec![7])];
+
+        let checkpoint = Checkpoint::new(&Offset, path.clone(), 10, true);
+        assert!(checkpoint
+            .load::<Vec<Solution<u32>>>("test")
+            .unwrap()
+            .is_none());
+        assert!(!checkpoint.is_due(9));
+        assert!(checkpoint.is_due(10));
+
+        checkpoint.save("test", &Offset.encode_all(&solutions));
+        let loaded: Vec<Solution<u32>> = checkpoint.load("test").unwrap().unwrap();
+
+        assert_eq!(loaded[0].point, vec![1, 5]);
+        assert_eq!(Offset.decode_all(&loaded), solutions);
+
+        // Another algorithm's state or a corrupt file is an error, not a panic
+        assert!(checkpoint.load::<Vec<Solution<u32>>>("other").is_err());
+        fs::write(&path, "not a checkpoint").unwrap();
+        assert!(checkpoint.load::<Vec<Solution<u32>>>("test").is_err());
+
+        // Checkpoints are only read when resuming
+        let checkpoint = Checkpoint::new(&Offset, path, 10, false);
+        assert!(checkpoint
+            .load::<Vec<Solution<u32>>>("test")
+            .unwrap()
+            .is_none());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
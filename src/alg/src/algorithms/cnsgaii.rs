@@ -1,15 +1,24 @@
-use rayon::iter::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
 
-use crate::operators::{
-    crossover::Crossover, evaluation::Evaluation, initialisation::InitPop, mapping::Mapping,
-    mutation::Mutation, selection::TournamentSelection, solution::Solution,
+use crate::{
+    operators::{
+        codec::Codec, crossover::Crossover, evaluation::Evaluation, initialisation::InitPop,
+        mapping::Mapping, mutation::Mutation, selection::TournamentSelection, solution::Solution,
+    },
+    utilities::rng,
 };
 use std::cmp::Ordering;
 use std::fmt::Debug;
 
-use super::nsgaii::{
-    crowding_comparison_operator, crowding_distance_assignment, fast_nondominated_sort,
-    NSGAII_Solution,
+use super::{
+    checkpoint::Checkpoint,
+    nsgaii::{
+        check_pop, crowding_comparison_operator, crowding_distance_assignment, decode_pop,
+        encode_pop, fast_nondominated_sort, NSGAIIState, NSGAII_Solution,
+    },
+    report,
 };
 
 pub fn run<
@@ -19,6 +28,7 @@ pub fn run<
     Eval: Evaluation + Sync,
     Mutate: Mutation<X> + Sync,
     Cross: Crossover<X> + Sync,
+    C: Codec<X>,
 >(
     init_pop: &Init,
     mapping: &Map,
@@ -27,28 +37,68 @@ pub fn run<
     crossover: &Cross,
     pop_size: usize,
     max_evaluations: usize,
+    report_interval: usize,
+    checkpoint: &Checkpoint<C>,
     mut iteration_observer: impl FnMut(usize, &Vec<Solution<X>>),
-) where
+) -> Result<(), String>
+where
     X: Clone + Debug + Send,
 {
-    let mut parent_pop: Vec<NSGAII_Solution<X>> = init_pop
-        .apply(pop_size)
-        .into_iter()
-        .map(|solution| NSGAII_Solution::new(solution))
-        .collect();
-
-    parent_pop.par_iter_mut().for_each(|ind| {
-        let routes = mapping.apply(&ind.solution);
-        ind.solution.objectives = evaluate.evaluate_ind(&routes)
-    });
-
-    let mut evaluations = parent_pop.len();
+    let codec = checkpoint.codec();
+
+    let (mut parent_pop, mut child_pop, mut evaluations) =
+        match checkpoint.load::<NSGAIIState<C::Encoded>>("CNSGA-II")? {
+            Some(state) => {
+                check_pop(codec, &state.parent_pop)?;
+                check_pop(codec, &state.child_pop)?;
+                rng::restore(state.rng);
+
+                (
+                    decode_pop(codec, &state.parent_pop),
+                    decode_pop(codec, &state.child_pop),
+                    state.evaluations,
+                )
+            }
+            None => {
+                let mut parent_pop: Vec<NSGAII_Solution<X>> = init_pop
+                    .apply(pop_size)
+                    .into_iter()
+                    .map(|solution| NSGAII_Solution::new(solution))
+                    .collect();
+
+                let seeds = rng::task_seeds(parent_pop.len());
+                parent_pop
+                    .par_iter_mut()
+                    .zip(seeds)
+                    .for_each(|(ind, seed)| {
+                        let _rng = rng::seed_task(seed);
+
+                        let routes = mapping.apply(&ind.solution);
+                        ind.solution.objectives = evaluate.evaluate_ind(&routes)
+                    });
+
+                let evaluations = parent_pop.len();
+
+                // Initial population
+                (parent_pop, Vec::with_capacity(pop_size), evaluations)
+            }
+        };
 
-    // Initial population
-    let mut child_pop = Vec::with_capacity(pop_size);
     let mut combined_pop = Vec::with_capacity(pop_size * 2);
+    let mut last_checkpoint = evaluations;
 
     while evaluations < max_evaluations {
+        if checkpoint.is_due(evaluations - last_checkpoint) {
+            let state = NSGAIIState {
+                evaluations,
+                parent_pop: encode_pop(codec, &parent_pop),
+                child_pop: encode_pop(codec, &child_pop),
+                rng: rng::state(),
+            };
+            checkpoint.save("CNSGA-II", &state);
+            last_checkpoint = evaluations;
+        }
+
         combined_pop.clear();
         combined_pop.append(&mut parent_pop);
         combined_pop.append(&mut child_pop);
@@ -73,9 +123,11 @@ pub fn run<
             crowding_comparison_operator(&parent_pop[x], &parent_pop[y]) == Ordering::Less
         });
 
-        child_pop = (0..(pop_size / 2))
+        child_pop = rng::task_seeds(pop_size / 2)
             .into_par_iter()
-            .flat_map(|_| {
+            .flat_map(|seed| {
+                let _rng = rng::seed_task(seed);
+
                 let parent_one = ts.tournament(2);
                 let parent_two = ts.tournament(2);
 
@@ -99,10 +151,22 @@ pub fn run<
             .collect();
 
         evaluations = evaluations + pop_size;
+
+        // The final population is reported below
+        if evaluations < max_evaluations
+            && report::is_due(report_interval, evaluations - pop_size, evaluations)
+        {
+            iteration_observer(
+                evaluations,
+                &parent_pop.iter().map(|ind| ind.solution.clone()).collect(),
+            );
+        }
     }
 
     iteration_observer(
         evaluations,
         &parent_pop.iter().map(|ind| ind.solution.clone()).collect(),
     );
+
+    Ok(())
 }
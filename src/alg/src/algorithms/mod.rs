@@ -1,5 +1,54 @@
+use serde::Serialize;
+use std::{fmt::Display, str::FromStr};
+
+pub mod checkpoint;
 pub mod cnsgaii;
+pub mod moead;
 pub mod nsgaii;
 pub mod pnsgaii;
 pub mod pplsd;
-pub mod sppls;
\ No newline at end of file
+pub mod report;
+pub mod sppls;
+pub mod weights;
+
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
+pub enum Algorithm {
+    NSGAII,
+    CNSGAII,
+    PNSGAII,
+    PPLS,
+    SPPLS,
+    MOEAD,
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Algorithm::NSGAII => write!(f, "NSGAII"),
+            Algorithm::CNSGAII => write!(f, "CNSGAII"),
+            Algorithm::PNSGAII => write!(f, "PNSGAII"),
+            Algorithm::PPLS => write!(f, "PPLS"),
+            Algorithm::SPPLS => write!(f, "SPPLS"),
+            Algorithm::MOEAD => write!(f, "MOEAD"),
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().replace("-", "").replace("/", "").as_str() {
+            "NSGAII" => Ok(Algorithm::NSGAII),
+            "CNSGAII" => Ok(Algorithm::CNSGAII),
+            "PNSGAII" => Ok(Algorithm::PNSGAII),
+            "PPLS" | "PPLSD" => Ok(Algorithm::PPLS),
+            "SPPLS" => Ok(Algorithm::SPPLS),
+            "MOEAD" => Ok(Algorithm::MOEAD),
+            _ => Err(format!(
+                "Unknown algorithm '{}', expected one of NSGAII, CNSGAII, PNSGAII, PPLS, SPPLS or MOEAD",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,468 @@
+use std::{cmp::Ordering, fmt::Debug, fmt::Display, str::FromStr};
+
+use rand::{seq::SliceRandom, Rng};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    checkpoint::Checkpoint,
+    report,
+    weights::{self, WeightDesign},
+};
+use crate::{
+    operators::{
+        codec::Codec, crossover::Crossover, evaluation::Evaluation, initialisation::InitPop,
+        mapping::Mapping, mutation::Mutation, solution::Constraint, solution::Solution,
+    },
+    utilities::rng::{self, thread_rng},
+};
+
+// Penalty of the distance from the weight vector in PBI when none is given
+const DEFAULT_PBI_PENALTY: f64 = 5.0;
+
+// Smallest weight of an objective in the Tchebycheff function, so a weight of 0 still
+// prefers the better of two solutions that are equal in the other objectives
+const MIN_WEIGHT: f64 = 1e-6;
+
+/**
+ * How MOEA/D turns the objectives of a solution into the value of a subproblem with a weight
+ * vector: `tchebycheff` for the largest weighted distance from the ideal point, `pbi:θ` for
+ * the distance along the weight vector plus θ times the distance from it (θ = 5 if left out)
+ * and `weighted_sum` for the weighted sum, which only finds the convex parts of the front.
+ * The objectives are normalised by the ideal point and the worst objectives in the population.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalarisation {
+    Tchebycheff,
+    PBI(f64),
+    WeightedSum,
+}
+
+impl Scalarisation {
+    /// Value of normalised `objectives` for the subproblem of `weight`, lower is better
+    pub fn apply(&self, objectives: &[f64], weight: &[f64]) -> f64 {
+        match self {
+            Scalarisation::Tchebycheff => objectives
+                .iter()
+                .zip(weight)
+                .map(|(f, w)| w.max(MIN_WEIGHT) * f.abs())
+                .fold(std::f64::MIN, f64::max),
+            Scalarisation::PBI(penalty) => {
+                let norm = weight.iter().map(|w| w * w).sum::<f64>().sqrt();
+                let along = objectives
+                    .iter()
+                    .zip(weight)
+                    .map(|(f, w)| f * w)
+                    .sum::<f64>()
+                    / norm;
+                let away = objectives
+                    .iter()
+                    .zip(weight)
+                    .map(|(f, w)| (f - along * w / norm).powf(2.0))
+                    .sum::<f64>()
+                    .sqrt();
+
+                along + penalty * away
+            }
+            Scalarisation::WeightedSum => objectives.iter().zip(weight).map(|(f, w)| f * w).sum(),
+        }
+    }
+}
+
+impl Default for Scalarisation {
+    fn default() -> Self {
+        Scalarisation::Tchebycheff
+    }
+}
+
+impl Display for Scalarisation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scalarisation::Tchebycheff => write!(f, "tchebycheff"),
+            Scalarisation::PBI(penalty) => write!(f, "pbi:{}", penalty),
+            Scalarisation::WeightedSum => write!(f, "weighted_sum"),
+        }
+    }
+}
+
+impl FromStr for Scalarisation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase().replace("-", "_");
+        let mut parts = s.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim();
+        let penalty = parts.next();
+
+        match (name, penalty) {
+            ("tchebycheff", None) => Ok(Scalarisation::Tchebycheff),
+            ("weighted_sum", None) => Ok(Scalarisation::WeightedSum),
+            ("pbi", None) => Ok(Scalarisation::PBI(DEFAULT_PBI_PENALTY)),
+            ("pbi", Some(penalty)) => match penalty.trim().parse::<f64>() {
+                Ok(penalty) if penalty >= 0.0 => Ok(Scalarisation::PBI(penalty)),
+                _ => Err(format!(
+                    "The PBI penalty must be a non-negative number, got '{}'",
+                    penalty
+                )),
+            },
+            _ => Err(format!(
+                "Unknown scalarisation '{}', expected tchebycheff, pbi, pbi:θ or weighted_sum",
+                s
+            )),
+        }
+    }
+}
+
+serialize_display!(Scalarisation);
+
+/// State of MOEA/D at the start of a generation
+#[derive(Serialize, Deserialize)]
+pub struct MOEADState<E> {
+    pub evaluations: usize,
+    pub pop: Vec<Solution<E>>,
+    pub ideal_point: Vec<f64>,
+    pub rng: Pcg64,
+}
+
+/**
+ * MOEA/D (Zhang & Li, 2007) with the mating and replacement limits of MOEA/D-DE (Li & Zhang,
+ * 2009). There is a subproblem for each weight vector and a solution for each subproblem.
+ * Every generation each subproblem breeds a child from two parents, taken from the
+ * `neighbourhood_size` subproblems with the closest weights with probability `delta` and
+ * from the whole population otherwise. The child replaces at most `max_replacements` of the
+ * solutions in the same pool that it improves on. Feasible solutions beat infeasible ones,
+ * which are compared by their violation.
+ **/
+pub fn run<
+    X,
+    Init: InitPop<X>,
+    Map: Mapping<X>,
+    Eval: Evaluation,
+    Mutate: Mutation<X>,
+    Cross: Crossover<X>,
+    C: Codec<X>,
+>(
+    init_pop: &Init,
+    mapping: &Map,
+    evaluate: &Eval,
+    mutation: &Mutate,
+    crossover: &Cross,
+    pop_size: usize,
+    max_evaluations: usize,
+    num_obj: usize,
+    weight_design: WeightDesign,
+    scalarisation: Scalarisation,
+    neighbourhood_size: usize,
+    delta: f64,
+    max_replacements: usize,
+    report_interval: usize,
+    checkpoint: &Checkpoint<C>,
+    mut iteration_observer: impl FnMut(usize, &Vec<Solution<X>>),
+) -> Result<(), String>
+where
+    X: Clone + Debug,
+{
+    let codec = checkpoint.codec();
+    let weight_vectors = weights::generate(weight_design, pop_size, num_obj);
+    let neighbourhoods = get_neighbourhoods(&weight_vectors, neighbourhood_size);
+
+    let (mut pop, mut ideal_point, mut evaluations) =
+        match checkpoint.load::<MOEADState<C::Encoded>>("MOEA/D")? {
+            Some(state) => {
+                codec.check_all(&state.pop)?;
+                rng::restore(state.rng);
+
+                (
+                    codec.decode_all(&state.pop),
+                    state.ideal_point,
+                    state.evaluations,
+                )
+            }
+            None => {
+                let mut pop = init_pop.apply(weight_vectors.len());
+
+                pop.iter_mut().for_each(|ind| {
+                    let routes = mapping.apply(&ind);
+                    ind.objectives = evaluate.evaluate_ind(&routes)
+                });
+
+                let mut ideal_point = vec![std::f64::MAX; num_obj];
+                pop.iter()
+                    .for_each(|ind| update_ideal_point(&mut ideal_point, ind));
+
+                let evaluations = pop.len();
+
+                (pop, ideal_point, evaluations)
+            }
+        };
+
+    let all_subproblems: Vec<usize> = (0..pop.len()).collect();
+    let mut last_checkpoint = evaluations;
+    let mut rng = thread_rng();
+
+    while evaluations < max_evaluations {
+        if checkpoint.is_due(evaluations - last_checkpoint) {
+            let state = MOEADState {
+                evaluations,
+                pop: codec.encode_all(&pop),
+                ideal_point: ideal_point.clone(),
+                rng: rng::state(),
+            };
+            checkpoint.save("MOEA/D", &state);
+            last_checkpoint = evaluations;
+        }
+
+        let generation_start = evaluations;
+        let nadir_point = get_nadir_point(&pop, num_obj);
+
+        for i in 0..pop.len() {
+            if evaluations >= max_evaluations {
+                break;
+            }
+
+            let pool = if rng.gen::<f64>() < delta {
+                &neighbourhoods[i]
+            } else {
+                &all_subproblems
+            };
+
+            let parents: Vec<usize> = pool.choose_multiple(&mut rng, 2).cloned().collect();
+            let parent_two = *parents.last().unwrap();
+
+            let child = match crossover
+                .apply(&pop[parents[0]], &pop[parent_two])
+                .into_iter()
+                .next()
+            {
+                Some(child) => child,
+                None => continue,
+            };
+            let mut child = mutation.apply(&child);
+
+            let routes = mapping.apply(&child);
+            child.objectives = evaluate.evaluate_ind(&routes);
+            evaluations = evaluations + 1;
+
+            update_ideal_point(&mut ideal_point, &child);
+
+            let mut order = pool.clone();
+            order.shuffle(&mut rng);
+
+            let mut replaced = 0;
+            for j in order {
+                if replaced >= max_replacements {
+                    break;
+                }
+
+                let comparison = compare(
+                    &child,
+                    &pop[j],
+                    &weight_vectors[j],
+                    scalarisation,
+                    &ideal_point,
+                    &nadir_point,
+                );
+
+                if comparison == Ordering::Less {
+                    pop[j] = child.clone();
+                    replaced = replaced + 1;
+                }
+            }
+        }
+
+        // The final population is reported below
+        if evaluations < max_evaluations
+            && report::is_due(report_interval, generation_start, evaluations)
+        {
+            iteration_observer(evaluations, &pop);
+        }
+    }
+
+    iteration_observer(evaluations, &pop);
+
+    Ok(())
+}
+
+/// For each weight vector, the indices of the `size` closest ones, itself first
+pub fn get_neighbourhoods(weight_vectors: &Vec<Vec<f64>>, size: usize) -> Vec<Vec<usize>> {
+    let size = size.max(1).min(weight_vectors.len());
+
+    weight_vectors
+        .iter()
+        .map(|weight| {
+            let mut distances: Vec<(usize, f64)> = weight_vectors
+                .iter()
+                .map(|other| {
+                    weight
+                        .iter()
+                        .zip(other)
+                        .map(|(a, b)| (a - b).powf(2.0))
+                        .sum::<f64>()
+                })
+                .enumerate()
+                .collect();
+
+            // Stable, so of equally distant weights the first ones are kept
+            distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+            distances
+                .into_iter()
+                .take(size)
+                .map(|(index, _)| index)
+                .collect()
+        })
+        .collect()
+}
+
+fn update_ideal_point<X>(ideal_point: &mut Vec<f64>, ind: &Solution<X>) {
+    if let Constraint::Feasible(objectives) = &ind.objectives {
+        for (ideal, objective) in ideal_point.iter_mut().zip(objectives) {
+            *ideal = ideal.min(*objective);
+        }
+    }
+}
+
+fn get_nadir_point<X>(pop: &Vec<Solution<X>>, num_obj: usize) -> Vec<f64> {
+    let mut nadir_point = vec![std::f64::MIN; num_obj];
+
+    for ind in pop {
+        if let Constraint::Feasible(objectives) = &ind.objectives {
+            for (nadir, objective) in nadir_point.iter_mut().zip(objectives) {
+                *nadir = nadir.max(*objective);
+            }
+        }
+    }
+
+    nadir_point
+}
+
+// Objectives relative to the ideal point, scaled by the range of the population in each
+fn normalise(objectives: &[f64], ideal_point: &[f64], nadir_point: &[f64]) -> Vec<f64> {
+    objectives
+        .iter()
+        .zip(ideal_point.iter().zip(nadir_point))
+        .map(|(objective, (ideal, nadir))| {
+            let range = nadir - ideal;
+            let range = if range > 0.0 { range } else { 1.0 };
+
+            (objective - ideal) / range
+        })
+        .collect()
+}
+
+/// Whether `x` is better (Less) or worse (Greater) than `y` on the subproblem of `weight`
+pub fn compare<X>(
+    x: &Solution<X>,
+    y: &Solution<X>,
+    weight: &[f64],
+    scalarisation: Scalarisation,
+    ideal_point: &[f64],
+    nadir_point: &[f64],
+) -> Ordering {
+    match (&x.objectives, &y.objectives) {
+        (Constraint::Feasible(x_obj), Constraint::Feasible(y_obj)) => {
+            let x_value = scalarisation.apply(&normalise(x_obj, ideal_point, nadir_point), weight);
+            let y_value = scalarisation.apply(&normalise(y_obj, ideal_point, nadir_point), weight);
+
+            x_value.partial_cmp(&y_value).unwrap_or(Ordering::Equal)
+        }
+        (Constraint::Feasible(_), _) => Ordering::Less,
+        (_, Constraint::Feasible(_)) => Ordering::Greater,
+        (Constraint::Infeasible(x_cv), Constraint::Infeasible(y_cv)) => x_cv.compare(y_cv),
+        (Constraint::Infeasible(_), Constraint::Undefined) => Ordering::Less,
+        (Constraint::Undefined, Constraint::Infeasible(_)) => Ordering::Greater,
+        (Constraint::Undefined, Constraint::Undefined) => Ordering::Equal,
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::solution::Violation;
+
+    fn with_objectives(objectives: Constraint<Vec<f64>, Violation>) -> Solution<usize> {
+        let mut solution = Solution::new(vec![0]);
+        solution.objectives = objectives;
+        solution
+    }
+
+    #[test]
+    fn test_scalarisation() {
+        let objectives = [0.5, 0.25];
+
+        assert_eq!(
+            Scalarisation::Tchebycheff.apply(&objectives, &[0.5, 0.5]),
+            0.25
+        );
+        assert_eq!(
+            Scalarisation::WeightedSum.apply(&objectives, &[0.5, 0.5]),
+            0.375
+        );
+
+        // On the weight vector PBI is the distance along it
+        let pbi = Scalarisation::PBI(5.0);
+        assert!((pbi.apply(&[0.5, 0.5], &[0.5, 0.5]) - 0.5f64.sqrt()).abs() < 1e-12);
+        assert!((pbi.apply(&[1.0, 0.0], &[0.0, 1.0]) - 5.0).abs() < 1e-12);
+
+        // A zero weight still breaks ties
+        let tchebycheff = Scalarisation::Tchebycheff;
+        assert!(
+            tchebycheff.apply(&[0.0, 0.1], &[1.0, 0.0])
+                < tchebycheff.apply(&[0.0, 0.2], &[1.0, 0.0])
+        );
+    }
+
+    #[test]
+    fn test_parse_scalarisation() {
+        assert_eq!("Tchebycheff".parse(), Ok(Scalarisation::Tchebycheff));
+        assert_eq!("weighted-sum".parse(), Ok(Scalarisation::WeightedSum));
+        assert_eq!("pbi".parse(), Ok(Scalarisation::PBI(5.0)));
+        assert_eq!("pbi:2.5".parse(), Ok(Scalarisation::PBI(2.5)));
+        assert!("pbi:-1".parse::<Scalarisation>().is_err());
+        assert!("chebyshev".parse::<Scalarisation>().is_err());
+        assert_eq!(Scalarisation::PBI(2.5).to_string(), "pbi:2.5");
+    }
+
+    #[test]
+    fn test_get_neighbourhoods() {
+        let weight_vectors = weights::simplex_lattice(2, 4);
+        let neighbourhoods = get_neighbourhoods(&weight_vectors, 3);
+
+        assert_eq!(neighbourhoods[0], vec![0, 1, 2]);
+        assert_eq!(neighbourhoods[2], vec![2, 1, 3]);
+        assert_eq!(neighbourhoods[4], vec![4, 3, 2]);
+
+        assert_eq!(get_neighbourhoods(&weight_vectors, 10)[0].len(), 5);
+    }
+
+    #[test]
+    fn test_compare() {
+        let ideal_point = [0.0, 0.0];
+        let nadir_point = [1.0, 10.0];
+        let compare = |x, y, weight: &[f64]| {
+            compare(
+                &x,
+                &y,
+                weight,
+                Scalarisation::Tchebycheff,
+                &ideal_point,
+                &nadir_point,
+            )
+        };
+
+        let a = with_objectives(Constraint::Feasible(vec![0.2, 8.0]));
+        let b = with_objectives(Constraint::Feasible(vec![0.8, 2.0]));
+        assert_eq!(compare(a.clone(), b.clone(), &[1.0, 0.0]), Ordering::Less);
+        assert_eq!(
+            compare(a.clone(), b.clone(), &[0.0, 1.0]),
+            Ordering::Greater
+        );
+
+        let c = with_objectives(Constraint::Infeasible(Violation::unplaced(1)));
+        let d = with_objectives(Constraint::Infeasible(Violation::unplaced(2)));
+        assert_eq!(compare(b.clone(), c.clone(), &[0.0, 1.0]), Ordering::Less);
+        assert_eq!(compare(d.clone(), b, &[0.0, 1.0]), Ordering::Greater);
+        assert_eq!(compare(c, d, &[0.0, 1.0]), Ordering::Less);
+    }
+}
@@ -1,10 +1,27 @@
-use crate::operators::{
-    crossover::Crossover, evaluation::Evaluation, initialisation::InitPop, mapping::Mapping,
-    mutation::Mutation, selection::TournamentSelection, solution::Constraint, solution::Solution,
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use super::{checkpoint::Checkpoint, report};
+use crate::{
+    operators::{
+        codec::Codec, crossover::Crossover, evaluation::Evaluation, initialisation::InitPop,
+        mapping::Mapping, mutation::Mutation, selection::TournamentSelection, solution::Constraint,
+        solution::Solution, solution::Violation,
+    },
+    utilities::rng,
 };
 use std::cmp::Ordering;
 use std::fmt::Debug;
 
+/// State of NSGA-II and CNSGA-II at the start of a generation
+#[derive(Serialize, Deserialize)]
+pub struct NSGAIIState<E: Clone> {
+    pub evaluations: usize,
+    pub parent_pop: Vec<NSGAII_Solution<E>>,
+    pub child_pop: Vec<NSGAII_Solution<E>>,
+    pub rng: Pcg64,
+}
+
 pub fn run<
     X: Sync,
     Init: InitPop<X>,
@@ -12,6 +29,7 @@ pub fn run<
     Eval: Evaluation + Sync,
     Mutate: Mutation<X> + Sync,
     Cross: Crossover<X> + Sync,
+    C: Codec<X>,
 >(
     init_pop: &Init,
     mapping: &Map,
@@ -20,28 +38,62 @@ pub fn run<
     crossover: &Cross,
     pop_size: usize,
     max_evaluations: usize,
+    report_interval: usize,
+    checkpoint: &Checkpoint<C>,
     mut iteration_observer: impl FnMut(usize, &Vec<Solution<X>>),
-) where
+) -> Result<(), String>
+where
     X: Clone + Debug + Send,
 {
-    let mut parent_pop: Vec<NSGAII_Solution<X>> = init_pop
-        .apply(pop_size)
-        .into_iter()
-        .map(|solution| NSGAII_Solution::new(solution))
-        .collect();
-
-    parent_pop.iter_mut().for_each(|ind| {
-        let routes = mapping.apply(&ind.solution);
-        ind.solution.objectives = evaluate.evaluate_ind(&routes)
-    });
-
-    let mut evaluations = parent_pop.len();
+    let codec = checkpoint.codec();
+
+    let (mut parent_pop, mut child_pop, mut evaluations) =
+        match checkpoint.load::<NSGAIIState<C::Encoded>>("NSGA-II")? {
+            Some(state) => {
+                check_pop(codec, &state.parent_pop)?;
+                check_pop(codec, &state.child_pop)?;
+                rng::restore(state.rng);
+
+                (
+                    decode_pop(codec, &state.parent_pop),
+                    decode_pop(codec, &state.child_pop),
+                    state.evaluations,
+                )
+            }
+            None => {
+                let mut parent_pop: Vec<NSGAII_Solution<X>> = init_pop
+                    .apply(pop_size)
+                    .into_iter()
+                    .map(|solution| NSGAII_Solution::new(solution))
+                    .collect();
+
+                parent_pop.iter_mut().for_each(|ind| {
+                    let routes = mapping.apply(&ind.solution);
+                    ind.solution.objectives = evaluate.evaluate_ind(&routes)
+                });
+
+                let evaluations = parent_pop.len();
+
+                // Initial population
+                (parent_pop, Vec::with_capacity(pop_size), evaluations)
+            }
+        };
 
-    // Initial population
-    let mut child_pop = Vec::with_capacity(pop_size);
     let mut combined_pop = Vec::with_capacity(pop_size * 2);
+    let mut last_checkpoint = evaluations;
 
     while evaluations < max_evaluations {
+        if checkpoint.is_due(evaluations - last_checkpoint) {
+            let state = NSGAIIState {
+                evaluations,
+                parent_pop: encode_pop(codec, &parent_pop),
+                child_pop: encode_pop(codec, &child_pop),
+                rng: rng::state(),
+            };
+            checkpoint.save("NSGA-II", &state);
+            last_checkpoint = evaluations;
+        }
+
         combined_pop.clear();
         combined_pop.append(&mut parent_pop);
         combined_pop.append(&mut child_pop);
@@ -92,12 +144,67 @@ pub fn run<
             .collect();
 
         evaluations = evaluations + pop_size;
+
+        // The final population is reported below
+        if evaluations < max_evaluations
+            && report::is_due(report_interval, evaluations - pop_size, evaluations)
+        {
+            iteration_observer(
+                evaluations,
+                &parent_pop.iter().map(|ind| ind.solution.clone()).collect(),
+            );
+        }
     }
 
     iteration_observer(
         evaluations,
         &parent_pop.iter().map(|ind| ind.solution.clone()).collect(),
     );
+
+    Ok(())
+}
+
+/// Checks every solution of a saved population can be decoded by `codec`
+pub fn check_pop<X, C: Codec<X>>(
+    codec: &C,
+    pop: &[NSGAII_Solution<C::Encoded>],
+) -> Result<(), String> {
+    pop.iter()
+        .flat_map(|ind| &ind.solution.point)
+        .map(|encoded| codec.check(encoded))
+        .collect()
+}
+
+pub fn encode_pop<X: Clone, C: Codec<X>>(
+    codec: &C,
+    pop: &[NSGAII_Solution<X>],
+) -> Vec<NSGAII_Solution<C::Encoded>> {
+    pop.iter()
+        .map(|ind| NSGAII_Solution {
+            solution: Solution {
+                point: ind.solution.point.iter().map(|x| codec.encode(x)).collect(),
+                objectives: ind.solution.objectives.clone(),
+            },
+            crowding_dist: ind.crowding_dist,
+            rank: ind.rank,
+        })
+        .collect()
+}
+
+pub fn decode_pop<X: Clone, C: Codec<X>>(
+    codec: &C,
+    pop: &[NSGAII_Solution<C::Encoded>],
+) -> Vec<NSGAII_Solution<X>> {
+    pop.iter()
+        .map(|ind| NSGAII_Solution {
+            solution: Solution {
+                point: ind.solution.point.iter().map(|x| codec.decode(x)).collect(),
+                objectives: ind.solution.objectives.clone(),
+            },
+            crowding_dist: ind.crowding_dist,
+            rank: ind.rank,
+        })
+        .collect()
 }
 
 pub fn fast_nondominated_sort<X: Clone>(
@@ -226,7 +333,7 @@ pub fn crowding_comparison_operator<X: Clone>(
 }
 
 // Wrapper around Solution struct with extra information for NSGA-II
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[allow(non_camel_case_types)]
 pub struct NSGAII_Solution<X: Clone> {
     pub solution: Solution<X>,
@@ -247,7 +354,7 @@ impl<X: Clone> NSGAII_Solution<X> {
         self.solution.dominates(&other.solution)
     }
 
-    pub fn objectives(&self) -> &Constraint<Vec<f64>, usize> {
+    pub fn objectives(&self) -> &Constraint<Vec<f64>, Violation> {
         &self.solution.objectives
     }
 }
@@ -1,18 +1,38 @@
-use rand::{prelude::SliceRandom, prelude::ThreadRng, thread_rng};
-use rayon::iter::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
-
-use crate::operators::{
-    crossover::Crossover, evaluation::Evaluation, initialisation::InitPop, mapping::Mapping,
-    mutation::Mutation, selection::TournamentSelection, solution::Solution,
+use rand::prelude::SliceRandom;
+use rand_pcg::Pcg64;
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    operators::{
+        codec::Codec, crossover::Crossover, evaluation::Evaluation, initialisation::InitPop,
+        mapping::Mapping, mutation::Mutation, selection::TournamentSelection, solution::Solution,
+    },
+    utilities::rng::{self, thread_rng, ThreadRng},
 };
 use std::cmp::Ordering;
 use std::fmt::Debug;
 
-use super::nsgaii::{
-    crowding_comparison_operator, crowding_distance_assignment, fast_nondominated_sort,
-    NSGAII_Solution,
+use super::{
+    checkpoint::Checkpoint,
+    nsgaii::{
+        check_pop, crowding_comparison_operator, crowding_distance_assignment, decode_pop,
+        encode_pop, fast_nondominated_sort, NSGAII_Solution,
+    },
+    report,
 };
 
+/// State of P-NSGA-II at the start of an epoch
+#[derive(Serialize, Deserialize)]
+struct PNSGAIIState<E: Clone> {
+    epoch: usize,
+    num_cores: usize,
+    global_pop: Vec<NSGAII_Solution<E>>,
+    rng: Pcg64,
+}
+
 // Comparison of Parallel Genetic Algorithm and Particle Swarm Optimization for Real-Time UAV Path Planning
 pub fn run<
     X: Sync,
@@ -21,6 +41,7 @@ pub fn run<
     Eval: Evaluation + Sync,
     Mutate: Mutation<X> + Sync,
     Cross: Crossover<X> + Sync,
+    C: Codec<X>,
 >(
     init_pop: &Init,
     mapping: &Map,
@@ -30,33 +51,83 @@ pub fn run<
     pop_size: usize,
     max_evaluations: usize,
     num_epochs: usize,
+    report_interval: usize,
+    checkpoint: &Checkpoint<C>,
     mut iteration_observer: impl FnMut(usize, &Vec<Solution<X>>),
-) where
+) -> Result<(), String>
+where
     X: Clone + Debug + Send,
 {
-    let mut global_pop: Vec<NSGAII_Solution<X>> = init_pop
-        .apply(pop_size)
-        .into_iter()
-        .map(|solution| NSGAII_Solution::new(solution))
-        .collect();
+    let codec = checkpoint.codec();
+    let num_cores = num_cpus::get();
 
-    global_pop.par_iter_mut().for_each(|ind| {
-        let routes = mapping.apply(&ind.solution);
-        ind.solution.objectives = evaluate.evaluate_ind(&routes)
-    });
+    let (mut global_pop, start_epoch) = match checkpoint
+        .load::<PNSGAIIState<C::Encoded>>("P-NSGA-II")?
+    {
+        Some(state) => {
+            // The sub populations depend on the number of cores
+            if state.num_cores != num_cores {
+                return Err(format!(
+                        "P-NSGA-II must resume on the same number of cores, the checkpoint was saved on {} and there are {}",
+                        state.num_cores, num_cores
+                    ));
+            }
+            check_pop(codec, &state.global_pop)?;
+            rng::restore(state.rng);
 
-    let num_cores = num_cpus::get();
-    let max_sub_evaluations = (max_evaluations - global_pop.len()) / (num_cores * num_epochs);
+            (decode_pop(codec, &state.global_pop), state.epoch)
+        }
+        None => {
+            let mut global_pop: Vec<NSGAII_Solution<X>> = init_pop
+                .apply(pop_size)
+                .into_iter()
+                .map(|solution| NSGAII_Solution::new(solution))
+                .collect();
+
+            let seeds = rng::task_seeds(global_pop.len());
+            global_pop
+                .par_iter_mut()
+                .zip(seeds)
+                .for_each(|(ind, seed)| {
+                    let _rng = rng::seed_task(seed);
+
+                    let routes = mapping.apply(&ind.solution);
+                    ind.solution.objectives = evaluate.evaluate_ind(&routes)
+                });
+
+            (global_pop, 0)
+        }
+    };
+
+    let max_sub_evaluations = (max_evaluations - pop_size) / (num_cores * num_epochs);
     let sub_pop_size = pop_size / num_cores;
+    let epoch_evaluations = max_sub_evaluations * num_cores;
 
     let mut rng = thread_rng();
+    let mut unsaved_evaluations = 0;
+
+    for epoch in start_epoch..num_epochs {
+        if epoch > start_epoch && checkpoint.is_due(unsaved_evaluations) {
+            let state = PNSGAIIState {
+                epoch,
+                num_cores,
+                global_pop: encode_pop(codec, &global_pop),
+                rng: rng::state(),
+            };
+            checkpoint.save("P-NSGA-II", &state);
+            unsaved_evaluations = 0;
+        }
 
-    for _ in 0..num_epochs {
         let pops = scatter_pop(&mut global_pop, num_cores, sub_pop_size, &mut rng);
 
+        let seeds = rng::task_seeds(pops.len());
+
         global_pop = pops
             .into_par_iter()
-            .flat_map(|mut pop| {
+            .zip(seeds)
+            .flat_map(|(mut pop, seed)| {
+                let _rng = rng::seed_task(seed);
+
                 let mut num_sub_evaluations = 0;
 
                 // Initial population
@@ -117,12 +188,31 @@ pub fn run<
                 combined_pop
             })
             .collect();
+
+        unsaved_evaluations += epoch_evaluations;
+
+        // The final population is reported below
+        let evaluations = pop_size + (epoch + 1) * epoch_evaluations;
+        if epoch + 1 < num_epochs
+            && report::is_due(
+                report_interval,
+                evaluations - epoch_evaluations,
+                evaluations,
+            )
+        {
+            iteration_observer(
+                evaluations,
+                &global_pop.iter().map(|ind| ind.solution.clone()).collect(),
+            );
+        }
     }
 
     iteration_observer(
-        max_sub_evaluations * num_cores,
+        pop_size + num_epochs * epoch_evaluations,
         &global_pop.iter().map(|ind| ind.solution.clone()).collect(),
     );
+
+    Ok(())
 }
 
 fn scatter_pop<X: Clone>(
@@ -1,14 +1,23 @@
 use std::fmt::Debug;
 
-use rayon::iter::{IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelIterator,
+};
 
+use super::{
+    checkpoint::{Checkpoint, LocalSearchState, WeightCheckpoints},
+    report::ArchiveReports,
+    weights::{self, WeightDesign},
+};
 use crate::{
     operators::mapping::Mapping,
     operators::neighbour_gen::NeighbourGenerator,
     operators::{
-        evaluation::Evaluation, initialisation::InitPop, solution::Constraint, solution::Solution,
+        codec::Codec, evaluation::Evaluation, initialisation::InitPop, solution::Constraint,
+        solution::Solution,
     },
-    utilities::nds::NonDominatedSet,
+    utilities::{nds::NonDominatedSet, rng},
 };
 
 pub fn run<
@@ -17,6 +26,7 @@ pub fn run<
     Map: Mapping<X> + Sync,
     NeighbourGen: NeighbourGenerator<X> + Sync,
     Eval: Evaluation + Sync + Clone,
+    C: Codec<X> + Sync,
 >(
     init_pop: &Init,
     mapping: &Map,
@@ -26,49 +36,154 @@ pub fn run<
     max_evaluations: usize,
     per_ind_evaluations: usize,
     num_obj: usize,
+    weight_design: WeightDesign,
+    report_interval: usize,
+    checkpoint: &Checkpoint<C>,
     iteration_observer: impl Fn(usize, &Vec<Solution<X>>) + Sync,
-) where
+) -> Result<(), String>
+where
     X: Clone + Debug + Sync + Send,
 {
     // PPLS runs a modified pareto local search on different weight vectors in parallel.
     // Since the different threads do not communicate with each other, we execute *all* evaluations
     // for each weight vector before continuing to the next one. This ensures there isn't too much
     // moving about of memory
-
-    // Evaluate initial pop
-    let weight_vectors = get_weights(pop_size, num_obj);
-    let mut init_archive = init_pop.apply(pop_size);
-
-    init_archive.par_iter_mut().for_each(|ind| {
-        let routes = mapping.apply(&ind);
-        ind.objectives = evaluate.evaluate_ind(&routes)
-    });
+    let codec = checkpoint.codec();
+    let weight_vectors = weights::normalise(weights::generate(weight_design, pop_size, num_obj));
+
+    let (init_archive, seeds, saved_weights) =
+        match checkpoint.load::<LocalSearchState<C::Encoded>>("PPLS/D")? {
+            Some(state) => {
+                state.check(codec)?;
+
+                (
+                    codec.decode_all(&state.init_archive),
+                    state.seeds,
+                    state.weights,
+                )
+            }
+            None => {
+                // Evaluate initial pop
+                let mut init_archive = init_pop.apply(pop_size);
+
+                let seeds = rng::task_seeds(init_archive.len());
+                init_archive
+                    .par_iter_mut()
+                    .zip(seeds)
+                    .for_each(|(ind, seed)| {
+                        let _rng = rng::seed_task(seed);
+
+                        let routes = mapping.apply(&ind);
+                        ind.objectives = evaluate.evaluate_ind(&routes)
+                    });
+
+                let seeds = rng::task_seeds(weight_vectors.len());
+                (init_archive, seeds, vec![None; weight_vectors.len()])
+            }
+        };
 
     let (ref_point, nadir_point) = get_ref_points(&init_archive, num_obj);
 
+    // Shared by the subproblems, of which there can be more or fewer than the population
     let remaining_evaluations = max_evaluations - pop_size;
-    let per_weight_evaluations = remaining_evaluations / pop_size;
+    let per_weight_evaluations = remaining_evaluations / weight_vectors.len();
+
+    let checkpoints = WeightCheckpoints::new(
+        checkpoint,
+        "PPLS/D",
+        &init_archive,
+        &seeds,
+        saved_weights.clone(),
+    );
+
+    // Resumed weights carry on from their saved archives
+    let saved_archives = saved_weights
+        .iter()
+        .map(|saved| {
+            saved
+                .as_ref()
+                .map_or_else(Vec::new, |saved| codec.decode_all(&saved.archive))
+        })
+        .collect();
+    let saved_evaluations: usize = saved_weights
+        .iter()
+        .flatten()
+        .map(|saved| saved.evaluations)
+        .sum();
+
+    let reports = ArchiveReports::new(
+        report_interval,
+        max_evaluations,
+        &iteration_observer,
+        saved_archives,
+        pop_size + saved_evaluations,
+    );
 
     let total_archive: Vec<NonDominatedSet<X>> = weight_vectors
         .par_iter()
-        .map(|wv| {
+        .zip(seeds)
+        .zip(saved_weights.into_par_iter())
+        .enumerate()
+        .map(|(w, ((wv, seed), saved))| {
             let evaluate = evaluate.clone();
 
-            // Pick the best starting individual for the current weight
-            let (best_idx, _, _) = get_best(&init_archive, &wv, &ref_point, &nadir_point);
-            let best_ind = &init_archive[best_idx];
+            let (mut archive, mut unexplored_archive, mut evaluations, _rng) = match saved {
+                // Continue the search where the checkpoint left it
+                Some(saved) => {
+                    let archive =
+                        NonDominatedSet::from_raw(false, codec.decode_all(&saved.archive));
+                    if saved.finished {
+                        return archive;
+                    }
+
+                    (
+                        archive,
+                        codec.decode_all(&saved.unexplored_archive),
+                        saved.evaluations,
+                        rng::restore_task(saved.rng),
+                    )
+                }
+                None => {
+                    let rng = rng::seed_task(seed);
 
-            // Create archives
-            let mut archive = NonDominatedSet::new(false);
-            archive.try_push(best_ind.clone());
+                    // Pick the best starting individual for the current weight
+                    let (best_idx, _, _) = get_best(&init_archive, &wv, &ref_point, &nadir_point);
+                    let best_ind = &init_archive[best_idx];
 
-            let mut unexplored_archive = Vec::new();
-            unexplored_archive.push(best_ind.clone());
+                    // Create archives
+                    let mut archive = NonDominatedSet::new(false);
+                    archive.try_push(best_ind.clone());
 
-            let mut evaluations = 0;
+                    let mut unexplored_archive = Vec::new();
+                    unexplored_archive.push(best_ind.clone());
+                    reports.update(w, archive.get_raw(), 0);
+
+                    (archive, unexplored_archive, 0, rng)
+                }
+            };
+
+            let mut unsaved_evaluations = 0;
+            let mut unreported_evaluations = 0;
             while evaluations < per_weight_evaluations && !unexplored_archive.is_empty() {
+                if checkpoints.is_due(unsaved_evaluations) {
+                    checkpoints.update(
+                        w,
+                        archive.get_raw(),
+                        &unexplored_archive,
+                        evaluations,
+                        false,
+                        unsaved_evaluations,
+                    );
+                    unsaved_evaluations = 0;
+                }
+
+                if reports.is_due(unreported_evaluations) {
+                    reports.update(w, archive.get_raw(), unreported_evaluations);
+                    unreported_evaluations = 0;
+                }
+
                 // Find the unexplored solution with the minimum tchbycheff distance
-                let (idx, best_dist, cnstr_violation) =
+                let (idx, best_dist, _) =
                     get_best(&unexplored_archive, &wv, &ref_point, &nadir_point);
 
                 let best_ind = unexplored_archive.swap_remove(idx);
@@ -113,8 +228,15 @@ pub fn run<
                 if !success {
                     for neighbour in &neighbours {
                         // Accept infeasible solutions only if necessary
-                        if let Constraint::Infeasible(neighbour_violation) = neighbour.objectives {
-                            if neighbour_violation < cnstr_violation {
+                        if let Constraint::Infeasible(neighbour_violation) = &neighbour.objectives {
+                            let improves = match &best_ind.objectives {
+                                Constraint::Infeasible(violation) => {
+                                    neighbour_violation.is_better(violation)
+                                }
+                                _ => false,
+                            };
+
+                            if improves {
                                 let is_added = archive.try_push(neighbour.clone());
                                 if is_added {
                                     unexplored_archive.push(neighbour.clone());
@@ -138,8 +260,20 @@ pub fn run<
                 }
 
                 evaluations = evaluations + per_ind_evaluations;
+                unsaved_evaluations = unsaved_evaluations + per_ind_evaluations;
+                unreported_evaluations = unreported_evaluations + per_ind_evaluations;
             }
 
+            checkpoints.update(
+                w,
+                archive.get_raw(),
+                &unexplored_archive,
+                evaluations,
+                true,
+                unsaved_evaluations,
+            );
+            reports.update(w, archive.get_raw(), unreported_evaluations);
+
             archive
         })
         .collect();
@@ -153,6 +287,8 @@ pub fn run<
     }
 
     iteration_observer(max_evaluations, final_solutions.get_raw());
+
+    Ok(())
 }
 
 fn get_best<'a, X: Clone>(
@@ -160,10 +296,10 @@ fn get_best<'a, X: Clone>(
     wv: &Vec<f64>,
     ref_point: &Vec<f64>,
     nadir_point: &Vec<f64>,
-) -> (usize, f64, usize) {
+) -> (usize, f64, f64) {
     let mut best_ind = 0;
     let mut min_dist = std::f64::INFINITY;
-    let mut min_infeasible = std::usize::MAX;
+    let mut min_infeasible = std::f64::INFINITY;
 
     for (i, ind) in pop.iter().enumerate() {
         match (&ind.objectives, &pop[best_ind].objectives) {
@@ -173,12 +309,12 @@ fn get_best<'a, X: Clone>(
                 if dist < min_dist {
                     min_dist = dist;
                     best_ind = i;
-                    min_infeasible = 0;
+                    min_infeasible = 0.0;
                 }
             }
-            (Constraint::Infeasible(ind_constraint), Constraint::Infeasible(_)) => {
-                if *ind_constraint < min_infeasible {
-                    min_infeasible = *ind_constraint;
+            (Constraint::Infeasible(ind_constraint), Constraint::Infeasible(best)) => {
+                if i == best_ind || ind_constraint.is_better(best) {
+                    min_infeasible = ind_constraint.total;
                     best_ind = i;
                 }
             }
@@ -238,56 +374,6 @@ fn dot_product(vec_a: &Vec<f64>, vec_b: &Vec<f64>) -> f64 {
     vec_a.iter().zip(vec_b).map(|(a, b)| a * b).sum()
 }
 
-fn get_weights(pop_size: usize, num_obj: usize) -> Vec<Vec<f64>> {
-    let pop_size = pop_size as i32;
-
-    let pop_to_h = vec![
-        28, 36, 45, 55, 66, 78, 91, 105, 120, 136, 153, 171, 190, 210, 231, 253, 276, 300, 325,
-        351, 378, 406, 435, 465, 496, 528, 561, 595,
-    ];
-
-    let mut dist = pop_size - pop_to_h[0];
-
-    let mut i = 0;
-
-    loop {
-        let c_dist = (pop_size - pop_to_h[i]).abs();
-        if c_dist < dist {
-            dist = c_dist;
-        }
-
-        if c_dist > dist {
-            break;
-        }
-
-        i = i + 1;
-    }
-
-    let h = i + 5;
-
-    let mut weights = Vec::new();
-    for i in 0..=h {
-        for j in 0..=h {
-            if i + j <= h {
-                let k = h - i - j;
-                let mut weight = Vec::with_capacity(num_obj);
-
-                weight.push(i as f64 / h as f64);
-                weight.push(j as f64 / h as f64);
-                weight.push(k as f64 / h as f64);
-
-                // Normalise weight
-                let mag = weight.iter().map(|w| w.powf(2.0)).sum::<f64>().sqrt();
-                let weight = weight.into_iter().map(|w| w / mag).collect();
-
-                weights.push(weight);
-            }
-        }
-    }
-
-    weights
-}
-
 fn tchebycheff(
     objectives: &Vec<f64>,
     weights: &Vec<f64>,
@@ -336,7 +422,7 @@ pub fn get_ref_points<X>(population: &Vec<Solution<X>>, num_obj: usize) -> (Vec<
 mod tests {
     use super::*;
     use crate::{
-        operators::solution::{Constraint, Solution},
+        operators::solution::{Constraint, Solution, Violation},
         utilities::math::round_to,
     };
 
@@ -357,32 +443,32 @@ mod tests {
         let (af_id, af_dist, af_cv) = get_best(&solutions, &wv, &ref_point, &nadir_point);
 
         // Mixed
-        solutions[0].objectives = Constraint::Infeasible(3);
+        solutions[0].objectives = Constraint::Infeasible(Violation::unplaced(3));
         solutions[1].objectives = Constraint::Feasible(vec![0.45, 0.55]);
-        solutions[2].objectives = Constraint::Infeasible(1);
+        solutions[2].objectives = Constraint::Infeasible(Violation::unplaced(1));
         solutions[3].objectives = Constraint::Feasible(vec![0.75, 0.25]);
 
         let (m_id, m_dist, m_cv) = get_best(&solutions, &wv, &ref_point, &nadir_point);
 
         // All infeasible
-        solutions[0].objectives = Constraint::Infeasible(3);
-        solutions[1].objectives = Constraint::Infeasible(4);
-        solutions[2].objectives = Constraint::Infeasible(5);
-        solutions[3].objectives = Constraint::Infeasible(4);
+        solutions[0].objectives = Constraint::Infeasible(Violation::unplaced(3));
+        solutions[1].objectives = Constraint::Infeasible(Violation::unplaced(4));
+        solutions[2].objectives = Constraint::Infeasible(Violation::unplaced(5));
+        solutions[3].objectives = Constraint::Infeasible(Violation::unplaced(4));
 
         let (if_id, if_dist, if_cv) = get_best(&solutions, &wv, &ref_point, &nadir_point);
 
         assert_eq!(af_id, 2);
         assert_eq!(af_dist, 0.5);
-        assert_eq!(af_cv, 0);
+        assert_eq!(af_cv, 0.0);
 
         assert_eq!(m_id, 1);
         assert_eq!(m_dist, 0.55);
-        assert_eq!(m_cv, 0);
+        assert_eq!(m_cv, 0.0);
 
         assert_eq!(if_id, 0);
         assert_eq!(if_dist, std::f64::INFINITY);
-        assert_eq!(if_cv, 3);
+        assert_eq!(if_cv, 3.0);
     }
 
     #[test]
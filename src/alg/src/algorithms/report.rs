@@ -0,0 +1,128 @@
+use std::sync::Mutex;
+
+use crate::{operators::solution::Solution, utilities::nds::NonDominatedSet};
+
+/**
+ * Whether the population should be reported after the evaluations went from `last` to
+ * `evaluations`. Reports are made each time the evaluations pass a multiple of `interval`,
+ * never if it is 0, so a resumed run reports at the same points as an uninterrupted one.
+ **/
+pub fn is_due(interval: usize, last: usize, evaluations: usize) -> bool {
+    interval > 0 && evaluations / interval > last / interval
+}
+
+/**
+ * Snapshots of a local search that runs one weight vector per task (PPLS/D and SPPLS). The
+ * tasks report their archives as they go and, each time the evaluations of all the tasks
+ * together pass a multiple of `interval`, the non-dominated set of the latest archives is
+ * passed to the observer. The final result is left to the algorithm to report.
+ **/
+pub struct ArchiveReports<'o, X, F> {
+    interval: usize,
+    task_interval: usize,
+    max_evaluations: usize,
+    observer: &'o F,
+    state: Mutex<(Vec<Vec<Solution<X>>>, usize)>,
+}
+
+impl<'o, X, F> ArchiveReports<'o, X, F>
+where
+    X: Clone,
+    F: Fn(usize, &Vec<Solution<X>>),
+{
+    /// Starts from the archives of the weights and the evaluations done so far
+    pub fn new(
+        interval: usize,
+        max_evaluations: usize,
+        observer: &'o F,
+        archives: Vec<Vec<Solution<X>>>,
+        evaluations: usize,
+    ) -> ArchiveReports<'o, X, F> {
+        let task_interval = (interval / archives.len().max(1)).max(1);
+
+        ArchiveReports {
+            interval,
+            task_interval,
+            max_evaluations,
+            observer,
+            state: Mutex::new((archives, evaluations)),
+        }
+    }
+
+    /// Whether the task should report after `evaluations` since its last report
+    pub fn is_due(&self, evaluations: usize) -> bool {
+        self.interval > 0 && evaluations >= self.task_interval
+    }
+
+    /// Records the archive of one weight vector, with `new_evaluations` done since its last report
+    pub fn update(&self, weight: usize, archive: &[Solution<X>], new_evaluations: usize) {
+        if self.interval == 0 {
+            return;
+        }
+
+        let mut guard = self.state.lock().unwrap();
+        let (archives, evaluations) = &mut *guard;
+
+        archives[weight] = archive.to_vec();
+
+        let last = *evaluations;
+        *evaluations += new_evaluations;
+
+        if *evaluations < self.max_evaluations && is_due(self.interval, last, *evaluations) {
+            let mut snapshot = NonDominatedSet::new(false);
+            for solution in archives.iter().flatten() {
+                snapshot.try_push(solution.clone());
+            }
+
+            (self.observer)(*evaluations, snapshot.get_raw());
+        }
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::solution::Constraint;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_is_due() {
+        assert!(!is_due(0, 0, 100));
+        assert!(!is_due(100, 0, 99));
+        assert!(is_due(100, 0, 100));
+        assert!(is_due(100, 90, 110));
+        assert!(!is_due(100, 110, 190));
+        assert!(is_due(100, 110, 350));
+    }
+
+    #[test]
+    fn test_archive_reports() {
+        let reports = RefCell::new(Vec::new());
+        let observer = |evaluations: usize, pop: &Vec<Solution<usize>>| {
+            reports.borrow_mut().push((evaluations, pop.len()))
+        };
+
+        let mut a = Solution::new(vec![0]);
+        a.objectives = Constraint::Feasible(vec![1.0, 2.0]);
+        let mut b = Solution::new(vec![1]);
+        b.objectives = Constraint::Feasible(vec![2.0, 1.0]);
+        let mut c = Solution::new(vec![2]);
+        c.objectives = Constraint::Feasible(vec![2.0, 2.0]);
+
+        let archive_reports = ArchiveReports::new(10, 40, &observer, vec![Vec::new(); 2], 5);
+        assert!(!archive_reports.is_due(4));
+        assert!(archive_reports.is_due(5));
+
+        archive_reports.update(0, &[a.clone()], 4);
+        assert!(reports.borrow().is_empty());
+
+        // The dominated solution is left out of the snapshot
+        archive_reports.update(1, &[b, c], 4);
+        assert_eq!(*reports.borrow(), vec![(13, 2)]);
+
+        // The final evaluations are left to the algorithm
+        archive_reports.update(0, &[a], 27);
+        assert_eq!(*reports.borrow(), vec![(13, 2)]);
+    }
+}
@@ -1,15 +1,24 @@
 use std::fmt::Debug;
 
 use rand::Rng;
-use rayon::iter::{IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelIterator,
+};
 
+use super::{
+    checkpoint::{Checkpoint, LocalSearchState, WeightCheckpoints},
+    report::ArchiveReports,
+    weights::{self, WeightDesign},
+};
 use crate::{
     operators::mapping::Mapping,
     operators::neighbour_gen::NeighbourGenerator,
     operators::{
-        evaluation::Evaluation, initialisation::InitPop, solution::Constraint, solution::Solution,
+        codec::Codec, evaluation::Evaluation, initialisation::InitPop, solution::Constraint,
+        solution::Solution,
     },
-    utilities::nds::NonDominatedSet,
+    utilities::{nds::NonDominatedSet, rng},
 };
 
 pub fn run<
@@ -18,6 +27,7 @@ pub fn run<
     Map: Mapping<X> + Sync,
     NeighbourGen: NeighbourGenerator<X> + Sync,
     Eval: Evaluation + Sync + Clone,
+    C: Codec<X> + Sync,
 >(
     init_pop: &Init,
     mapping: &Map,
@@ -27,43 +37,144 @@ pub fn run<
     max_evaluations: usize,
     per_ind_evaluations: usize,
     num_obj: usize,
+    weight_design: WeightDesign,
+    report_interval: usize,
+    checkpoint: &Checkpoint<C>,
     iteration_observer: impl Fn(usize, &Vec<Solution<X>>) + Sync,
-) where
+) -> Result<(), String>
+where
     X: Clone + Debug + Sync + Send,
 {
-    // Evaluate initial pop
-    let weight_vectors = get_weights(pop_size, num_obj);
-    let mut init_archive = init_pop.apply(pop_size);
-
-    init_archive.par_iter_mut().for_each(|ind| {
-        let routes = mapping.apply(&ind);
-        ind.objectives = evaluate.evaluate_ind(&routes)
-    });
+    let codec = checkpoint.codec();
+    let weight_vectors = weights::normalise(weights::generate(weight_design, pop_size, num_obj));
+
+    let (init_archive, seeds, saved_weights) =
+        match checkpoint.load::<LocalSearchState<C::Encoded>>("SPPLS")? {
+            Some(state) => {
+                state.check(codec)?;
+
+                (
+                    codec.decode_all(&state.init_archive),
+                    state.seeds,
+                    state.weights,
+                )
+            }
+            None => {
+                // Evaluate initial pop
+                let mut init_archive = init_pop.apply(pop_size);
+
+                let seeds = rng::task_seeds(init_archive.len());
+                init_archive
+                    .par_iter_mut()
+                    .zip(seeds)
+                    .for_each(|(ind, seed)| {
+                        let _rng = rng::seed_task(seed);
+
+                        let routes = mapping.apply(&ind);
+                        ind.objectives = evaluate.evaluate_ind(&routes)
+                    });
+
+                let seeds = rng::task_seeds(weight_vectors.len());
+                (init_archive, seeds, vec![None; weight_vectors.len()])
+            }
+        };
 
     let (ref_point, nadir_point) = get_ref_points(&init_archive, num_obj);
 
+    // Shared by the subproblems, of which there can be more or fewer than the population
     let remaining_evaluations = max_evaluations - pop_size;
-    let per_weight_evaluations = remaining_evaluations / pop_size;
+    let per_weight_evaluations = remaining_evaluations / weight_vectors.len();
+
+    let checkpoints = WeightCheckpoints::new(
+        checkpoint,
+        "SPPLS",
+        &init_archive,
+        &seeds,
+        saved_weights.clone(),
+    );
+
+    // Resumed weights carry on from their saved archives
+    let saved_archives = saved_weights
+        .iter()
+        .map(|saved| {
+            saved
+                .as_ref()
+                .map_or_else(Vec::new, |saved| codec.decode_all(&saved.archive))
+        })
+        .collect();
+    let saved_evaluations: usize = saved_weights
+        .iter()
+        .flatten()
+        .map(|saved| saved.evaluations)
+        .sum();
+
+    let reports = ArchiveReports::new(
+        report_interval,
+        max_evaluations,
+        &iteration_observer,
+        saved_archives,
+        pop_size + saved_evaluations,
+    );
 
     let total_archive: Vec<NonDominatedSet<X>> = weight_vectors
         .par_iter()
-        .map(|wv| {
+        .zip(seeds)
+        .zip(saved_weights.into_par_iter())
+        .enumerate()
+        .map(|(w, ((wv, seed), saved))| {
             let evaluate = evaluate.clone();
 
-            // Pick the best starting individual for the current weight
-            let (best_idx, _, _) = get_best(&init_archive, &wv, &ref_point, &nadir_point);
-            let best_ind = &init_archive[best_idx];
+            let (mut archive, mut evaluations, _rng) = match saved {
+                // Continue the search where the checkpoint left it
+                Some(saved) => {
+                    let archive =
+                        NonDominatedSet::from_raw(false, codec.decode_all(&saved.archive));
+                    if saved.finished {
+                        return archive;
+                    }
+
+                    (archive, saved.evaluations, rng::restore_task(saved.rng))
+                }
+                None => {
+                    let rng = rng::seed_task(seed);
+
+                    // Pick the best starting individual for the current weight
+                    let (best_idx, _, _) = get_best(&init_archive, &wv, &ref_point, &nadir_point);
+                    let best_ind = &init_archive[best_idx];
 
-            // Create archives
-            let mut archive = NonDominatedSet::new(false);
-            archive.try_push_with(best_ind.clone(), |x, y| {
-                dominates(x, y, wv, &weight_vectors)
-            });
+                    // Create archives
+                    let mut archive = NonDominatedSet::new(false);
+                    archive.try_push_with(best_ind.clone(), |x, y| {
+                        dominates(x, y, wv, &weight_vectors)
+                    });
+                    reports.update(w, archive.get_raw(), 0);
 
-            let mut evaluations = 0;
+                    (archive, 0, rng)
+                }
+            };
+
+            let mut unsaved_evaluations = 0;
+            let mut unreported_evaluations = 0;
             while evaluations < per_weight_evaluations {
+                if checkpoints.is_due(unsaved_evaluations) {
+                    checkpoints.update(
+                        w,
+                        archive.get_raw(),
+                        &[],
+                        evaluations,
+                        false,
+                        unsaved_evaluations,
+                    );
+                    unsaved_evaluations = 0;
+                }
+
+                if reports.is_due(unreported_evaluations) {
+                    reports.update(w, archive.get_raw(), unreported_evaluations);
+                    unreported_evaluations = 0;
+                }
+
                 // Choose a random solution from the archive
-                let mut rng = rand::thread_rng();
+                let mut rng = rng::thread_rng();
                 let idx = rng.gen_range(0, archive.get_raw().len());
 
                 let best_ind = archive.get_raw()[idx].clone();
@@ -79,8 +190,20 @@ pub fn run<
                 }
 
                 evaluations = evaluations + per_ind_evaluations;
+                unsaved_evaluations = unsaved_evaluations + per_ind_evaluations;
+                unreported_evaluations = unreported_evaluations + per_ind_evaluations;
             }
 
+            checkpoints.update(
+                w,
+                archive.get_raw(),
+                &[],
+                evaluations,
+                true,
+                unsaved_evaluations,
+            );
+            reports.update(w, archive.get_raw(), unreported_evaluations);
+
             archive
         })
         .collect();
@@ -94,6 +217,8 @@ pub fn run<
     }
 
     iteration_observer(max_evaluations, final_solutions.get_raw());
+
+    Ok(())
 }
 
 fn dominates<X>(
@@ -111,8 +236,8 @@ fn dominates_cnstr<X>(x: &Solution<X>, y: &Solution<X>) -> bool {
         // Infeasible solutions are dominated by any feasible one
         (Constraint::Feasible(_), Constraint::Infeasible(_)) => true,
         (Constraint::Infeasible(_), Constraint::Feasible(_)) => false,
-        // Infeasible solutions with a lower rating are better
-        (Constraint::Infeasible(x), Constraint::Infeasible(y)) => x < y,
+        // Infeasible solutions with a lower violation are better
+        (Constraint::Infeasible(x), Constraint::Infeasible(y)) => x.is_better(y),
         _ => panic!("Undefined objectives"),
     }
 }
@@ -145,10 +270,10 @@ fn get_best<'a, X: Clone>(
     wv: &Vec<f64>,
     ref_point: &Vec<f64>,
     nadir_point: &Vec<f64>,
-) -> (usize, f64, usize) {
+) -> (usize, f64, f64) {
     let mut best_ind = 0;
     let mut min_dist = std::f64::INFINITY;
-    let mut min_infeasible = std::usize::MAX;
+    let mut min_infeasible = std::f64::INFINITY;
 
     for (i, ind) in pop.iter().enumerate() {
         match (&ind.objectives, &pop[best_ind].objectives) {
@@ -158,12 +283,12 @@ fn get_best<'a, X: Clone>(
                 if dist < min_dist {
                     min_dist = dist;
                     best_ind = i;
-                    min_infeasible = 0;
+                    min_infeasible = 0.0;
                 }
             }
-            (Constraint::Infeasible(ind_constraint), Constraint::Infeasible(_)) => {
-                if *ind_constraint < min_infeasible {
-                    min_infeasible = *ind_constraint;
+            (Constraint::Infeasible(ind_constraint), Constraint::Infeasible(best)) => {
+                if i == best_ind || ind_constraint.is_better(best) {
+                    min_infeasible = ind_constraint.total;
                     best_ind = i;
                 }
             }
@@ -203,56 +328,6 @@ fn dot_product(vec_a: &Vec<f64>, vec_b: &Vec<f64>) -> f64 {
     vec_a.iter().zip(vec_b).map(|(a, b)| a * b).sum()
 }
 
-fn get_weights(pop_size: usize, num_obj: usize) -> Vec<Vec<f64>> {
-    let pop_size = pop_size as i32;
-
-    let pop_to_h = vec![
-        28, 36, 45, 55, 66, 78, 91, 105, 120, 136, 153, 171, 190, 210, 231, 253, 276, 300, 325,
-        351, 378, 406, 435, 465, 496, 528, 561, 595,
-    ];
-
-    let mut dist = pop_size - pop_to_h[0];
-
-    let mut i = 0;
-
-    loop {
-        let c_dist = (pop_size - pop_to_h[i]).abs();
-        if c_dist < dist {
-            dist = c_dist;
-        }
-
-        if c_dist > dist {
-            break;
-        }
-
-        i = i + 1;
-    }
-
-    let h = i + 5;
-
-    let mut weights = Vec::new();
-    for i in 0..=h {
-        for j in 0..=h {
-            if i + j <= h {
-                let k = h - i - j;
-                let mut weight = Vec::with_capacity(num_obj);
-
-                weight.push(i as f64 / h as f64);
-                weight.push(j as f64 / h as f64);
-                weight.push(k as f64 / h as f64);
-
-                // Normalise weight
-                let mag = weight.iter().map(|w| w.powf(2.0)).sum::<f64>().sqrt();
-                let weight = weight.into_iter().map(|w| w / mag).collect();
-
-                weights.push(weight);
-            }
-        }
-    }
-
-    weights
-}
-
 fn tchebycheff(
     objectives: &Vec<f64>,
     weights: &Vec<f64>,
@@ -301,7 +376,7 @@ pub fn get_ref_points<X>(population: &Vec<Solution<X>>, num_obj: usize) -> (Vec<
 mod tests {
     use super::*;
     use crate::{
-        operators::solution::{Constraint, Solution},
+        operators::solution::{Constraint, Solution, Violation},
         utilities::math::round_to,
     };
 
@@ -370,32 +445,32 @@ mod tests {
         let (af_id, af_dist, af_cv) = get_best(&solutions, &wv, &ref_point, &nadir_point);
 
         // Mixed
-        solutions[0].objectives = Constraint::Infeasible(3);
+        solutions[0].objectives = Constraint::Infeasible(Violation::unplaced(3));
         solutions[1].objectives = Constraint::Feasible(vec![0.45, 0.55]);
-        solutions[2].objectives = Constraint::Infeasible(1);
+        solutions[2].objectives = Constraint::Infeasible(Violation::unplaced(1));
         solutions[3].objectives = Constraint::Feasible(vec![0.75, 0.25]);
 
         let (m_id, m_dist, m_cv) = get_best(&solutions, &wv, &ref_point, &nadir_point);
 
         // All infeasible
-        solutions[0].objectives = Constraint::Infeasible(3);
-        solutions[1].objectives = Constraint::Infeasible(4);
-        solutions[2].objectives = Constraint::Infeasible(5);
-        solutions[3].objectives = Constraint::Infeasible(4);
+        solutions[0].objectives = Constraint::Infeasible(Violation::unplaced(3));
+        solutions[1].objectives = Constraint::Infeasible(Violation::unplaced(4));
+        solutions[2].objectives = Constraint::Infeasible(Violation::unplaced(5));
+        solutions[3].objectives = Constraint::Infeasible(Violation::unplaced(4));
 
         let (if_id, if_dist, if_cv) = get_best(&solutions, &wv, &ref_point, &nadir_point);
 
         assert_eq!(af_id, 2);
         assert_eq!(af_dist, 0.5);
-        assert_eq!(af_cv, 0);
+        assert_eq!(af_cv, 0.0);
 
         assert_eq!(m_id, 1);
         assert_eq!(m_dist, 0.55);
-        assert_eq!(m_cv, 0);
+        assert_eq!(m_cv, 0.0);
 
         assert_eq!(if_id, 0);
         assert_eq!(if_dist, std::f64::INFINITY);
-        assert_eq!(if_cv, 3);
+        assert_eq!(if_cv, 3.0);
     }
 
     #[test]
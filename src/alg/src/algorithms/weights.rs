@@ -0,0 +1,436 @@
+use std::{fmt::Display, str::FromStr};
+
+use rand::Rng;
+
+use crate::utilities::rng::thread_rng;
+
+// Iterations of the Riesz s-energy design and the largest step of a weight in one of them
+const RIESZ_ITERATIONS: usize = 500;
+const RIESZ_STEP: f64 = 0.01;
+
+// Shrinks the inner layer of the two-layer design towards the centre of the simplex
+const INNER_SCALE: f64 = 0.5;
+
+/**
+ * How the weight vectors of the decomposition algorithms are spread over the simplex:
+ * `simplex` for the Das–Dennis simplex-lattice, `two_layer` for a lattice on the boundary
+ * and a shrunk one inside it, which suits many objectives where a single lattice has few
+ * interior weights, `random` for uniformly random weights and `riesz` for weights spread
+ * apart by minimising their Riesz s-energy. The lattices have the size closest to the
+ * number of weights asked for, the other designs have exactly that many.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightDesign {
+    SimplexLattice,
+    TwoLayer,
+    Random,
+    RieszEnergy,
+}
+
+impl Default for WeightDesign {
+    fn default() -> Self {
+        WeightDesign::SimplexLattice
+    }
+}
+
+impl Display for WeightDesign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeightDesign::SimplexLattice => write!(f, "simplex"),
+            WeightDesign::TwoLayer => write!(f, "two_layer"),
+            WeightDesign::Random => write!(f, "random"),
+            WeightDesign::RieszEnergy => write!(f, "riesz"),
+        }
+    }
+}
+
+impl FromStr for WeightDesign {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace("-", "_").as_str() {
+            "simplex" | "das_dennis" => Ok(WeightDesign::SimplexLattice),
+            "two_layer" => Ok(WeightDesign::TwoLayer),
+            "random" => Ok(WeightDesign::Random),
+            "riesz" => Ok(WeightDesign::RieszEnergy),
+            _ => Err(format!(
+                "Unknown weight design '{}', expected simplex, two_layer, random or riesz",
+                s
+            )),
+        }
+    }
+}
+
+serialize_display!(WeightDesign);
+
+/// About `num_weights` weight vectors for `num_obj` objectives, each summing to 1
+pub fn generate(design: WeightDesign, num_weights: usize, num_obj: usize) -> Vec<Vec<f64>> {
+    if num_obj == 1 {
+        return vec![vec![1.0]];
+    }
+
+    match design {
+        WeightDesign::SimplexLattice => {
+            simplex_lattice(num_obj, divisions_for(num_weights, num_obj))
+        }
+        WeightDesign::TwoLayer => {
+            let (outer, inner) = two_layer_divisions_for(num_weights, num_obj);
+            two_layer(num_obj, outer, inner)
+        }
+        WeightDesign::Random => random(num_weights, num_obj),
+        WeightDesign::RieszEnergy => riesz_energy(num_weights, num_obj),
+    }
+}
+
+/// Scales each weight to unit length, as the local searches compare them by angle
+pub fn normalise(weights: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    weights
+        .into_iter()
+        .map(|weight| {
+            let mag = weight.iter().map(|w| w.powf(2.0)).sum::<f64>().sqrt();
+            weight.into_iter().map(|w| w / mag).collect()
+        })
+        .collect()
+}
+
+/// Number of weights in the simplex-lattice with `divisions` for `num_obj` objectives
+pub fn lattice_size(num_obj: usize, divisions: usize) -> usize {
+    // C(divisions + num_obj - 1, num_obj - 1), each step stays an integer
+    let mut size = 1;
+    for i in 1..num_obj {
+        size = size * (divisions + i) / i;
+    }
+
+    size
+}
+
+/**
+ * The divisions of the lattice with the size closest to `num_weights`, the larger on a tie.
+ * With three objectives, fewer weights than the 6 division lattice get 5 divisions, 21
+ * weights, like the design PPLS/D and SPPLS have always used.
+ **/
+pub fn divisions_for(num_weights: usize, num_obj: usize) -> usize {
+    if num_obj == 3 && num_weights < lattice_size(3, 6) {
+        return 5;
+    }
+
+    let mut divisions = 1;
+    while lattice_size(num_obj, divisions) < num_weights {
+        divisions += 1;
+    }
+
+    let below = lattice_size(num_obj, divisions - 1);
+    if divisions > 1 && num_weights - below < lattice_size(num_obj, divisions) - num_weights {
+        divisions - 1
+    } else {
+        divisions
+    }
+}
+
+/**
+ * Every weight whose components are multiples of 1 / `divisions`, the Das–Dennis design.
+ * The weights are in lexicographic order of their components.
+ **/
+pub fn simplex_lattice(num_obj: usize, divisions: usize) -> Vec<Vec<f64>> {
+    let mut weights = Vec::with_capacity(lattice_size(num_obj, divisions));
+    let mut steps = Vec::with_capacity(num_obj);
+
+    add_lattice_points(num_obj, divisions, divisions, &mut steps, &mut weights);
+
+    weights
+}
+
+fn add_lattice_points(
+    num_obj: usize,
+    divisions: usize,
+    remaining: usize,
+    steps: &mut Vec<usize>,
+    weights: &mut Vec<Vec<f64>>,
+) {
+    // The last component takes what is left
+    if steps.len() == num_obj - 1 {
+        let weight = steps
+            .iter()
+            .chain(&[remaining])
+            .map(|&step| step as f64 / divisions as f64)
+            .collect();
+        weights.push(weight);
+
+        return;
+    }
+
+    for step in 0..=remaining {
+        steps.push(step);
+        add_lattice_points(num_obj, divisions, remaining - step, steps, weights);
+        steps.pop();
+    }
+}
+
+/**
+ * A lattice with `outer` divisions and one with `inner` divisions shrunk halfway towards
+ * the centre of the simplex, so it only has interior weights. No inner layer if `inner` is 0.
+ **/
+pub fn two_layer(num_obj: usize, outer: usize, inner: usize) -> Vec<Vec<f64>> {
+    let mut weights = simplex_lattice(num_obj, outer);
+
+    if inner > 0 {
+        let centre = 1.0 / num_obj as f64;
+
+        for weight in simplex_lattice(num_obj, inner) {
+            let shrunk = weight
+                .iter()
+                .map(|w| (1.0 - INNER_SCALE) * centre + INNER_SCALE * w)
+                .collect();
+            weights.push(shrunk);
+        }
+    }
+
+    weights
+}
+
+// The divisions of the two layers with the total closest to `num_weights`, the inner layer
+// never finer than the outer one
+fn two_layer_divisions_for(num_weights: usize, num_obj: usize) -> (usize, usize) {
+    let mut best = (1, 0);
+    let mut best_dist = std::usize::MAX;
+
+    let mut outer = 1;
+    while lattice_size(num_obj, outer) <= num_weights.max(num_obj) {
+        for inner in 0..=outer {
+            let size = lattice_size(num_obj, outer)
+                + if inner > 0 {
+                    lattice_size(num_obj, inner)
+                } else {
+                    0
+                };
+            let dist = (size as isize - num_weights as isize).abs() as usize;
+
+            if dist < best_dist {
+                best = (outer, inner);
+                best_dist = dist;
+            }
+        }
+
+        outer += 1;
+    }
+
+    best
+}
+
+/// Weights drawn uniformly from the simplex, by normalising exponential samples
+pub fn random(num_weights: usize, num_obj: usize) -> Vec<Vec<f64>> {
+    let mut rng = thread_rng();
+
+    (0..num_weights)
+        .map(|_| {
+            let samples: Vec<f64> = (0..num_obj)
+                .map(|_| -(1.0 - rng.gen::<f64>()).ln())
+                .collect();
+            let sum: f64 = samples.iter().sum();
+
+            samples.into_iter().map(|sample| sample / sum).collect()
+        })
+        .collect()
+}
+
+/**
+ * Weights that repel each other with a force falling off with distance, the Riesz s-energy
+ * design of Blank et al. (2021) with s = `num_obj` + 1. Starts from random weights with the
+ * corners of the simplex fixed among them, then moves each of the others down the gradient
+ * of the energy, keeping them on the simplex.
+ **/
+pub fn riesz_energy(num_weights: usize, num_obj: usize) -> Vec<Vec<f64>> {
+    let mut weights = random(num_weights, num_obj);
+
+    let num_fixed = num_obj.min(num_weights);
+    for (i, weight) in weights.iter_mut().take(num_fixed).enumerate() {
+        *weight = (0..num_obj)
+            .map(|j| if i == j { 1.0 } else { 0.0 })
+            .collect();
+    }
+
+    let s = (num_obj + 1) as f64;
+
+    for iteration in 0..RIESZ_ITERATIONS {
+        let mut forces = vec![vec![0.0; num_obj]; num_weights];
+
+        for i in num_fixed..num_weights {
+            for j in 0..num_weights {
+                if i == j {
+                    continue;
+                }
+
+                let diff: Vec<f64> = (0..num_obj)
+                    .map(|d| weights[i][d] - weights[j][d])
+                    .collect();
+                let dist = diff.iter().map(|x| x * x).sum::<f64>().sqrt().max(1e-12);
+                let scale = s / dist.powf(s + 2.0);
+
+                for d in 0..num_obj {
+                    forces[i][d] += scale * diff[d];
+                }
+            }
+
+            // Only the part of the force along the simplex moves the weight
+            let mean = forces[i].iter().sum::<f64>() / num_obj as f64;
+            forces[i].iter_mut().for_each(|force| *force -= mean);
+        }
+
+        let max_force = forces
+            .iter()
+            .flat_map(|force| force.iter())
+            .fold(0.0, |max: f64, force| max.max(force.abs()));
+        if max_force == 0.0 {
+            break;
+        }
+
+        let step = RIESZ_STEP * (1.0 - iteration as f64 / RIESZ_ITERATIONS as f64);
+
+        for i in num_fixed..num_weights {
+            for d in 0..num_obj {
+                weights[i][d] = (weights[i][d] + step * forces[i][d] / max_force).max(0.0);
+            }
+
+            let sum: f64 = weights[i].iter().sum();
+            weights[i].iter_mut().for_each(|w| *w /= sum);
+        }
+    }
+
+    weights
+}
+
+// Sum over the pairs of weights of 1 / distance^s
+#[cfg(test)]
+fn energy(weights: &Vec<Vec<f64>>, s: f64) -> f64 {
+    let mut energy = 0.0;
+    for i in 0..weights.len() {
+        for j in i + 1..weights.len() {
+            let dist = weights[i]
+                .iter()
+                .zip(&weights[j])
+                .map(|(a, b)| (a - b).powf(2.0))
+                .sum::<f64>()
+                .sqrt();
+            energy += 1.0 / dist.powf(s);
+        }
+    }
+
+    energy
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::rng;
+
+    fn on_simplex(weights: &Vec<Vec<f64>>, num_obj: usize) -> bool {
+        weights.iter().all(|weight| {
+            weight.len() == num_obj
+                && weight.iter().all(|&w| w >= 0.0)
+                && (weight.iter().sum::<f64>() - 1.0).abs() < 1e-9
+        })
+    }
+
+    #[test]
+    fn test_simplex_lattice() {
+        let weights = simplex_lattice(3, 2);
+        assert_eq!(
+            weights,
+            vec![
+                vec![0.0, 0.0, 1.0],
+                vec![0.0, 0.5, 0.5],
+                vec![0.0, 1.0, 0.0],
+                vec![0.5, 0.0, 0.5],
+                vec![0.5, 0.5, 0.0],
+                vec![1.0, 0.0, 0.0],
+            ]
+        );
+
+        assert_eq!(lattice_size(3, 6), 28);
+        assert_eq!(lattice_size(5, 4), 70);
+        assert_eq!(simplex_lattice(5, 4).len(), 70);
+        assert!(on_simplex(&simplex_lattice(4, 3), 4));
+        assert_eq!(simplex_lattice(2, 3).len(), 4);
+    }
+
+    #[test]
+    fn test_divisions_for() {
+        assert_eq!(divisions_for(28, 3), 6);
+        assert_eq!(divisions_for(32, 3), 7);
+        assert_eq!(divisions_for(1, 3), 5);
+        assert_eq!(divisions_for(4, 2), 3);
+        assert_eq!(divisions_for(100, 2), 99);
+        assert_eq!(generate(WeightDesign::SimplexLattice, 120, 4).len(), 120);
+
+        // The default ppls_pop_size
+        assert_eq!(generate(WeightDesign::SimplexLattice, 16, 3).len(), 21);
+
+        // The sizes of the lattices from 6 divisions, which PPLS/D and SPPLS picked from
+        let sizes = [
+            28, 36, 45, 55, 66, 78, 91, 105, 120, 136, 153, 171, 190, 210, 231, 253, 276, 300, 325,
+            351, 378, 406, 435, 465, 496, 528, 561, 595,
+        ];
+        for pop_size in 1..=560 {
+            let mut dist = pop_size as isize - sizes[0];
+            let mut i = 0;
+            loop {
+                let c_dist = (pop_size as isize - sizes[i]).abs();
+                if c_dist < dist {
+                    dist = c_dist;
+                }
+                if c_dist > dist {
+                    break;
+                }
+                i += 1;
+            }
+
+            assert_eq!(divisions_for(pop_size, 3), i + 5);
+        }
+    }
+
+    #[test]
+    fn test_two_layer() {
+        let weights = two_layer(8, 3, 2);
+        assert_eq!(weights.len(), lattice_size(8, 3) + lattice_size(8, 2));
+        assert!(on_simplex(&weights, 8));
+
+        // The inner layer has no weight on the boundary
+        assert!(weights[lattice_size(8, 3)..]
+            .iter()
+            .all(|weight| weight.iter().all(|&w| w > 0.0)));
+
+        let weights = generate(WeightDesign::TwoLayer, 156, 8);
+        assert_eq!(weights.len(), 156);
+    }
+
+    #[test]
+    fn test_random() {
+        rng::seed(2);
+        let weights = generate(WeightDesign::Random, 50, 4);
+        assert_eq!(weights.len(), 50);
+        assert!(on_simplex(&weights, 4));
+    }
+
+    #[test]
+    fn test_riesz_energy() {
+        rng::seed(3);
+        let random = random(20, 3);
+
+        rng::seed(3);
+        let weights = riesz_energy(20, 3);
+
+        assert_eq!(weights.len(), 20);
+        assert!(on_simplex(&weights, 3));
+        assert_eq!(weights[0], vec![1.0, 0.0, 0.0]);
+        assert!(energy(&weights, 4.0) < energy(&random, 4.0));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("das-dennis".parse(), Ok(WeightDesign::SimplexLattice));
+        assert_eq!("two_layer".parse(), Ok(WeightDesign::TwoLayer));
+        assert!("grid".parse::<WeightDesign>().is_err());
+        assert_eq!(WeightDesign::RieszEnergy.to_string(), "riesz");
+    }
+}
@@ -0,0 +1,445 @@
+use clap::{App, AppSettings, Arg, SubCommand};
+
+pub fn app() -> App<'static, 'static> {
+    App::new("nfv_mls_conf")
+        .about("Parallel multi-objective algorithms for the VNF placement problem")
+        .setting(AppSettings::VersionlessSubcommands)
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .short("c")
+                .value_name("FILE")
+                .help("Config file to read settings from [default: Config]")
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("Runs the algorithms on generated problem instances (the default command)")
+                .arg(
+                    Arg::with_name("manifest")
+                        .long("manifest")
+                        .short("m")
+                        .value_name("FILE")
+                        .help("TOML or JSON manifest describing the study, used instead of the config file")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("Lists the jobs of the study without running them"),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .long("resume")
+                        .help("Skips finished jobs and continues the others from their checkpoints"),
+                )
+                .args(&experiment_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("gen_topology")
+                .about("Builds a datacentre and its routing tables and writes them to the topology folder")
+                .arg(
+                    Arg::with_name("topology")
+                        .help("One of FatTree, LeafSpine or DCell")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("num_servers")
+                        .help("Target number of servers, the closest topology is generated")
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("topology-folder")
+                        .long("topology-folder")
+                        .value_name("DIR")
+                        .help("Folder to write the topology files to")
+                        .takes_value(true),
+                )
+                .args(&link_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about("Samples a problem instance and saves it, as JSON if the file ends in .json and bincode otherwise")
+                .arg(
+                    Arg::with_name("topology")
+                        .help("One of FatTree, LeafSpine or DCell")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("num_servers")
+                        .help("Target number of servers, the closest topology is used")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .help("File to write the instance to")
+                        .required(true),
+                )
+                .arg(value("utilisations", "U", "Expected proportion of server capacity used").alias("utilisation"))
+                .arg(value("server-capacity", "N", "Capacity of each server, e.g. 32,64,10 for several resource types"))
+                .arg(workload_arg())
+                .arg(value("seed", "SEED", "Seed of the instance")),
+        )
+        .subcommand(
+            SubCommand::with_name("evaluate")
+                .about("Evaluates a stored placement with the queueing, utilisation and heuristic models")
+                .arg(
+                    Arg::with_name("topology")
+                        .help("One of FatTree, LeafSpine or DCell")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("num_servers")
+                        .help("Target number of servers, the closest topology is used")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("placement")
+                        .help("Placement of service ids on each server, or a solutions export, as JSON if the file ends in .json and bincode otherwise")
+                        .required(true),
+                )
+                .arg(value(
+                    "instance",
+                    "FILE",
+                    "Instance the placement is for [default: instance.json beside the placement]",
+                ))
+                .arg(value(
+                    "solution",
+                    "N",
+                    "Solution of a solutions export to evaluate [default: 0]",
+                ))
+                .arg(value(
+                    "diagnostics",
+                    "FILE",
+                    "CSV file to write the state of each switch, server and VNF in the queueing model to",
+                ))
+                .arg(value(
+                    "seed",
+                    "SEED",
+                    "Master seed of the run, to break ties between servers the same way",
+                ))
+                .arg(value(
+                    "topology-folder",
+                    "DIR",
+                    "Folder containing the topology files",
+                ))
+                .args(&model_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("simulate")
+                .about("Simulates a stored placement packet by packet and compares it with the queueing model")
+                .arg(
+                    Arg::with_name("topology")
+                        .help("One of FatTree, LeafSpine or DCell")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("num_servers")
+                        .help("Target number of servers, the closest topology is used")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("placement")
+                        .help("Placement of service ids on each server, or a solutions export, as JSON if the file ends in .json and bincode otherwise")
+                        .required(true),
+                )
+                .arg(value(
+                    "instance",
+                    "FILE",
+                    "Instance the placement is for [default: instance.json beside the placement]",
+                ))
+                .arg(value(
+                    "solution",
+                    "N",
+                    "Solution of a solutions export to simulate [default: 0]",
+                ))
+                .arg(value(
+                    "duration",
+                    "T",
+                    "Simulated time over which packets are measured [default: 1000]",
+                ))
+                .arg(value(
+                    "warm-up",
+                    "T",
+                    "Simulated time before packets are measured [default: 100]",
+                ))
+                .arg(value(
+                    "output",
+                    "FILE",
+                    "CSV file to write the comparison of each service to",
+                ))
+                .arg(value(
+                    "seed",
+                    "SEED",
+                    "Master seed of the run, also seeds the simulation",
+                ))
+                .arg(value(
+                    "topology-folder",
+                    "DIR",
+                    "Folder containing the topology files",
+                ))
+                .args(&model_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("analyse")
+                .about("Computes quality indicators of the fronts in a results folder and writes them to CSV tables")
+                .arg(value("results-folder", "DIR", "Folder of results to analyse"))
+                .arg(value(
+                    "output",
+                    "DIR",
+                    "Folder to write the tables to [default: analysis in the results folder]",
+                ))
+                .arg(value(
+                    "control",
+                    "ALG",
+                    "Algorithm the others are compared with [default: NSGAII, or else the first]",
+                ))
+                .arg(
+                    Arg::with_name("paired")
+                        .long("paired")
+                        .help("Pairs the runs of the algorithms by number and uses the signed-rank test"),
+                )
+                .arg(value("alpha", "A", "Significance level of the comparisons [default: 0.05]")),
+        )
+        .subcommand(
+            SubCommand::with_name("num_cores")
+                .about("Writes the number of available cores to NumCores.txt in the results folder")
+                .arg(results_folder_arg()),
+        )
+}
+
+fn results_folder_arg() -> Arg<'static, 'static> {
+    Arg::with_name("results-folder")
+        .long("results-folder")
+        .value_name("DIR")
+        .help("Folder to write results to")
+        .takes_value(true)
+}
+
+fn value(
+    name: &'static str,
+    value_name: &'static str,
+    help: &'static str,
+) -> Arg<'static, 'static> {
+    Arg::with_name(name)
+        .long(name)
+        .value_name(value_name)
+        .help(help)
+        .takes_value(true)
+}
+
+fn workload_arg() -> Arg<'static, 'static> {
+    value(
+        "workload",
+        "PROFILE",
+        "Workload profile (default, heavy_tailed, bimodal, web_security) or profile file",
+    )
+}
+
+fn experiment_args() -> Vec<Arg<'static, 'static>> {
+    let mut args = vec![
+        results_folder_arg(),
+        value(
+            "topology-folder",
+            "DIR",
+            "Folder containing the topology files",
+        ),
+        value(
+            "topologies",
+            "LIST",
+            "Comma separated topologies: FatTree, LeafSpine, DCell",
+        ),
+        value("scales", "LIST", "Comma separated numbers of servers"),
+        value(
+            "algorithms",
+            "LIST",
+            "Comma separated algorithms: NSGAII, CNSGAII, PNSGAII, PPLS, SPPLS, MOEAD",
+        ),
+        value("max-evaluations", "N", "Evaluation budget of each run"),
+        value(
+            "seed",
+            "SEED",
+            "Master seed, the seeds of the runs are drawn from it",
+        ),
+        value(
+            "seeds",
+            "LIST",
+            "Comma separated seeds, one per problem instance",
+        ),
+        value(
+            "instance",
+            "FILE",
+            "Stored problem instance to run on instead of generating one per run",
+        ),
+        value("instances", "N", "Number of problem instances per scale"),
+        value(
+            "utilisations",
+            "LIST",
+            "Comma separated expected proportions of server capacity used",
+        )
+        .alias("utilisation"),
+        workload_arg(),
+        value(
+            "server-capacity",
+            "N",
+            "Capacity of each server, e.g. 32,64,10 for several resource types",
+        ),
+    ];
+
+    args.extend(model_args());
+    args.extend(vec![
+        value("pop-size", "N", "Population size of the NSGA-II variants and MOEA/D"),
+        value("ppls-pop-size", "N", "Population size of PPLS/D and SPPLS"),
+        value(
+            "ppls-neighbours",
+            "N",
+            "Neighbours evaluated per solution in PPLS/D and SPPLS",
+        ),
+        value(
+            "weight-design",
+            "DESIGN",
+            "Weight vectors of PPLS/D, SPPLS and MOEA/D: simplex, two_layer, random or riesz [default: simplex]",
+        ),
+        value(
+            "scalarisation",
+            "FUNCTION",
+            "Subproblems of MOEA/D: tchebycheff, pbi, pbi:θ or weighted_sum [default: tchebycheff]",
+        ),
+        value(
+            "moead-neighbours",
+            "N",
+            "Closest subproblems MOEA/D mates and replaces within [default: 20]",
+        ),
+        value(
+            "moead-delta",
+            "P",
+            "Probability MOEA/D mates within the neighbours rather than the population [default: 0.9]",
+        ),
+        value(
+            "moead-max-replacements",
+            "N",
+            "Most solutions a child of MOEA/D replaces [default: 2]",
+        ),
+        value("epochs", "N", "Number of epochs in P-NSGA-II"),
+        value("pm", "P", "Mutation probability"),
+        value("pc", "P", "Crossover probability"),
+        value(
+            "checkpoint-interval",
+            "N",
+            "Evaluations between checkpoints of a run, 0 disables them",
+        ),
+        value(
+            "report-interval",
+            "N",
+            "Evaluations between the populations written during a run, 0 only writes the final one",
+        ),
+        value(
+            "export",
+            "LIST",
+            "Comma separated formats to also export the written solutions in: json, bincode",
+        ),
+        value(
+            "objectives",
+            "LIST",
+            "Comma separated objectives: mean_latency, max_latency, latency_pN, mean_packet_loss, max_packet_loss, energy, active_servers, vnf_instances, max_link_utilisation [default: mean_latency,mean_packet_loss,energy]",
+        ),
+    ]);
+
+    args
+}
+
+// Settings of the models a placement is evaluated with
+fn model_args() -> Vec<Arg<'static, 'static>> {
+    let mut args = vec![
+        value(
+            "switch-service-rate",
+            "RATE",
+            "Service rate of a switch per port",
+        ),
+        value(
+            "switch-queue-length",
+            "N",
+            "Queue length of a switch per port",
+        ),
+        value("accuracy", "X", "Queueing model convergence threshold"),
+        value(
+            "converged-iterations",
+            "N",
+            "Iterations below the threshold before the queueing model has converged",
+        ),
+        value(
+            "max-iterations",
+            "N",
+            "Iterations before the queueing model gives up on converging, 0 for no limit",
+        ),
+        value(
+            "relaxation",
+            "X",
+            "Weight in (0, 1] of each new arrival rate in the queueing model, 0 averages them all",
+        ),
+        value(
+            "switch-queue",
+            "QUEUE",
+            "Queue discipline of the switches: mm1k, md1k, mg1k:CV or mmck:SERVERS",
+        ),
+        value(
+            "vnf-queue",
+            "QUEUE",
+            "Queue discipline of the VNFs: mm1k, md1k, mg1k:CV or mmck:SERVERS",
+        ),
+        value(
+            "active-cost",
+            "X",
+            "Energy cost of a fully utilised component",
+        ),
+        value("idle-cost", "X", "Energy cost of an idle component"),
+        value(
+            "server-power",
+            "CURVE",
+            "Power curve of the servers: linear:IDLE:ACTIVE, cubic:IDLE:ACTIVE, polynomial:IDLE:ACTIVE:EXPONENT or ports:CHASSIS:IDLE:ACTIVE [default: linear from the idle to the active cost]",
+        ),
+        value("edge-power", "CURVE", "Power curve of the edge switches"),
+        value(
+            "aggregation-power",
+            "CURVE",
+            "Power curve of the aggregation switches",
+        ),
+        value("core-power", "CURVE", "Power curve of the core switches"),
+        value(
+            "sleep",
+            "POLICY",
+            "Whether unused components sleep: never, unused or unused:POWER [default: unused]",
+        ),
+        value(
+            "link-overload",
+            "MODE",
+            "How overloaded links count against a placement: ignore, constraint or objective [default: ignore]",
+        ),
+        value(
+            "violation-aggregation",
+            "AGGREGATION",
+            "How constraint violations are combined: sum or max, optionally with the weights of unplaced services, SLA excess and link overload, e.g. sum:10:1:1, model failures always rank last [default: sum]",
+        ),
+    ];
+    args.extend(link_args());
+
+    args
+}
+
+// Attributes of the links, stored with a generated topology or replacing the stored ones
+fn link_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        value(
+            "link-bandwidth",
+            "RATE",
+            "Packets per unit of time each link carries [default: unlimited]",
+        ),
+        value(
+            "core-link-bandwidth",
+            "RATE",
+            "Bandwidth of the links to the core switches [default: the link bandwidth]",
+        ),
+        value("link-latency", "X", "Propagation delay of each link"),
+    ]
+}
@@ -0,0 +1,783 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::{self, File},
+    io::{prelude::*, BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use super::{manifest::PARAMETERS_FILE, parameters::Parameters};
+use crate::{
+    algorithms::Algorithm,
+    operators::evaluation::{Objective, DEFAULT_OBJECTIVES},
+    utilities::metrics::{
+        indicators::{self, bounds, nondominated, normalise},
+        mean, percentile, statistics, std_dev,
+    },
+};
+
+/// Folder in the results folder the tables are written to by default
+pub const ANALYSIS_FOLDER: &str = "analysis";
+
+pub const INDICATORS: [&str; 6] = [
+    "hypervolume",
+    "igd",
+    "igd_plus",
+    "epsilon",
+    "spread",
+    "spacing",
+];
+
+/// Reference point of the hypervolume, just beyond the nadir of the normalised fronts
+const REFERENCE: f64 = 1.000001;
+
+/// A population written by a run as `<services>_<evaluations>.objs`
+struct Front {
+    evaluations: usize,
+    points: Vec<Vec<f64>>,
+}
+
+struct Run {
+    problem: String,
+    algorithm: String,
+    run: String,
+    fronts: Vec<Front>,
+    running_time: Option<f64>,
+}
+
+/// How the final results of the algorithms are compared
+pub struct Comparison {
+    /// Algorithm the others are compared with, by default NSGAII or else the first one
+    pub control: Option<String>,
+    /// Pairs the runs of the algorithms by their number, which share an instance, and uses
+    /// the signed-rank test instead of the rank-sum test
+    pub paired: bool,
+    /// Significance level of the Holm corrected p-values
+    pub alpha: f64,
+}
+
+/// Indicators of the final front of a run
+struct Final<'a> {
+    run: &'a str,
+    values: Vec<f64>,
+    running_time: Option<f64>,
+}
+
+/// An algorithm's results on one indicator and how they compare with the control
+struct ComparisonRow {
+    problem: String,
+    indicator: &'static str,
+    algorithm: String,
+    values: Vec<f64>,
+    a12: Option<f64>,
+    p_value: Option<f64>,
+    holm_p_value: Option<f64>,
+    result: &'static str,
+}
+
+/**
+ * Computes the quality indicators of every front in the results folder and writes them to
+ * CSV tables in `output`, replacing the Matlab scripts in `processing`. Each folder holding
+ * .objs files is a run, the folder above it is the algorithm and the path to that is the
+ * problem, e.g. `utilisation_0.6/FatTree/500/NSGAII/3`.
+ *
+ * Like the scripts, the energy of each solution is divided by the number of services and
+ * the fronts are reduced to their non-dominated points. Which objective is the energy is
+ * read from the parameters.json of each run, runs without one have the default objectives.
+ * The fronts of every algorithm on a problem are normalised by the same ideal and nadir
+ * points, found over all of them, so share the reference point of the hypervolume. The
+ * distance based indicators compare each front with the non-dominated set of all of them.
+ * The tables written are:
+ *
+ *  - runs.csv, the indicators of each front of each run, for convergence plots
+ *  - summary.csv, the mean, standard deviation and quartiles of the indicators and running
+ *    times over the final fronts of the runs of each algorithm on each problem
+ *  - comparison.csv, whether each algorithm is significantly better, worse or equal to the
+ *    control on each problem, with comparison.tex holding the same as LaTeX tables
+ **/
+pub fn analyse(
+    results_folder: &Path,
+    output: &Path,
+    comparison: &Comparison,
+) -> Result<(), Box<dyn Error>> {
+    let mut run_folders = Vec::new();
+    find_run_folders(results_folder, &mut run_folders)?;
+
+    let mut problems: BTreeMap<String, Vec<Run>> = BTreeMap::new();
+    for folder in run_folders {
+        if let Some(run) = read_run(results_folder, &folder)? {
+            problems
+                .entry(run.problem.clone())
+                .or_insert_with(Vec::new)
+                .push(run);
+        }
+    }
+
+    if problems.is_empty() {
+        return Err(format!("No .objs files found in {:?}", results_folder).into());
+    }
+
+    let mut algorithms: Vec<String> = problems
+        .values()
+        .flat_map(|runs| runs.iter().map(|run| run.algorithm.clone()))
+        .collect();
+    algorithms.sort();
+    algorithms.dedup();
+
+    let control = match &comparison.control {
+        Some(control) if !algorithms.contains(control) => {
+            return Err(format!(
+                "The control algorithm {} has no results in {:?}",
+                control, results_folder
+            )
+            .into());
+        }
+        Some(control) => control.clone(),
+        None => {
+            let nsgaii = Algorithm::NSGAII.to_string();
+            if algorithms.contains(&nsgaii) {
+                nsgaii
+            } else {
+                algorithms[0].clone()
+            }
+        }
+    };
+
+    fs::create_dir_all(output)?;
+    let mut runs_file = BufWriter::new(File::create(output.join("runs.csv"))?);
+    let mut summary_file = BufWriter::new(File::create(output.join("summary.csv"))?);
+
+    writeln!(
+        runs_file,
+        "problem,algorithm,run,evaluations,solutions,{}",
+        INDICATORS.join(",")
+    )?;
+    writeln!(
+        summary_file,
+        "problem,algorithm,indicator,runs,mean,stdev,min,lq,median,uq,max"
+    )?;
+
+    let mut comparison_rows = Vec::new();
+
+    for (problem, mut runs) in problems {
+        runs.sort_by(|a, b| {
+            (&a.algorithm, natural_key(&a.run)).cmp(&(&b.algorithm, natural_key(&b.run)))
+        });
+
+        // Normalise every front by the bounds of all of them
+        let all_points: Vec<Vec<f64>> = runs
+            .iter()
+            .flat_map(|run| run.fronts.iter())
+            .flat_map(|front| front.points.iter().cloned())
+            .collect();
+        let (ideal, nadir) = bounds(&all_points);
+        let reference_front = nondominated(normalise(&all_points, &ideal, &nadir));
+        let reference = vec![REFERENCE; ideal.len()];
+
+        println!(
+            "{}: {} runs, ideal {:?}, nadir {:?}",
+            problem,
+            runs.len(),
+            ideal,
+            nadir
+        );
+
+        // Indicators of the final front of each run, by algorithm
+        let mut finals: BTreeMap<&str, Vec<Final>> = BTreeMap::new();
+
+        for run in &runs {
+            for front in &run.fronts {
+                let points = nondominated(normalise(&front.points, &ideal, &nadir));
+                let values = evaluate(&points, &reference_front, &reference);
+
+                let values_str: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                writeln!(
+                    runs_file,
+                    "{},{},{},{},{},{}",
+                    problem,
+                    run.algorithm,
+                    run.run,
+                    front.evaluations,
+                    points.len(),
+                    values_str.join(",")
+                )?;
+
+                if front.evaluations == run.fronts[run.fronts.len() - 1].evaluations {
+                    finals
+                        .entry(&run.algorithm)
+                        .or_insert_with(Vec::new)
+                        .push(Final {
+                            run: &run.run,
+                            values,
+                            running_time: run.running_time,
+                        });
+                }
+            }
+        }
+
+        for (algorithm, results) in &finals {
+            for (i, indicator) in summary_indicators().enumerate() {
+                let values = sample(results, i).into_iter().map(|(_, v)| v).collect();
+                write_summary(&mut summary_file, &problem, algorithm, indicator, &values)?;
+            }
+        }
+
+        for (i, indicator) in summary_indicators().enumerate() {
+            comparison_rows.extend(compare(
+                &problem, indicator, i, &finals, &control, comparison,
+            ));
+        }
+    }
+
+    write_comparison(output, &comparison_rows)?;
+    write_latex(output, &comparison_rows, &algorithms, &control, comparison)?;
+
+    println!(
+        "Wrote runs.csv, summary.csv, comparison.csv and comparison.tex to {:?}",
+        output
+    );
+
+    Ok(())
+}
+
+// The indicators in the order of INDICATORS
+fn evaluate(points: &[Vec<f64>], reference_front: &[Vec<f64>], reference: &[f64]) -> Vec<f64> {
+    vec![
+        indicators::hypervolume(points, reference),
+        indicators::igd(points, reference_front),
+        indicators::igd_plus(points, reference_front),
+        indicators::additive_epsilon(points, reference_front),
+        indicators::spread(points, reference_front),
+        indicators::spacing(points),
+    ]
+}
+
+// The indicators and then the running time
+fn summary_indicators() -> impl Iterator<Item = &'static str> {
+    INDICATORS
+        .iter()
+        .cloned()
+        .chain(std::iter::once("running_time"))
+}
+
+// The value of the i-th summary indicator of each run that has it
+fn sample<'a>(results: &[Final<'a>], i: usize) -> Vec<(&'a str, f64)> {
+    results
+        .iter()
+        .filter_map(|result| {
+            let value = if i < INDICATORS.len() {
+                Some(result.values[i])
+            } else {
+                result.running_time
+            };
+
+            value.map(|value| (result.run, value))
+        })
+        .collect()
+}
+
+fn write_summary(
+    file: &mut impl Write,
+    problem: &str,
+    algorithm: &str,
+    indicator: &str,
+    values: &Vec<f64>,
+) -> std::io::Result<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        file,
+        "{},{},{},{},{},{},{},{},{},{},{}",
+        problem,
+        algorithm,
+        indicator,
+        values.len(),
+        mean(values),
+        std_dev(values),
+        percentile(values, 0.0),
+        percentile(values, 25.0),
+        percentile(values, 50.0),
+        percentile(values, 75.0),
+        percentile(values, 100.0)
+    )
+}
+
+// Compares every algorithm with the control on one indicator of a problem, the p-values are
+// corrected for the number of algorithms compared
+fn compare(
+    problem: &str,
+    indicator: &'static str,
+    i: usize,
+    finals: &BTreeMap<&str, Vec<Final>>,
+    control: &str,
+    comparison: &Comparison,
+) -> Vec<ComparisonRow> {
+    let control_sample = finals.get(control).map(|results| sample(results, i));
+
+    let mut rows = Vec::new();
+    let mut p_values = Vec::new();
+
+    for (&algorithm, results) in finals {
+        let algorithm_sample = sample(results, i);
+        let values: Vec<f64> = algorithm_sample.iter().map(|(_, v)| *v).collect();
+        if values.is_empty() {
+            continue;
+        }
+
+        let mut row = ComparisonRow {
+            problem: problem.to_string(),
+            indicator,
+            algorithm: algorithm.to_string(),
+            values,
+            a12: None,
+            p_value: None,
+            holm_p_value: None,
+            result: "",
+        };
+
+        match &control_sample {
+            Some(_) if algorithm == control => row.result = "control",
+            Some(control_sample) if !control_sample.is_empty() => {
+                let control_values: Vec<f64> = control_sample.iter().map(|(_, v)| *v).collect();
+                row.a12 = Some(statistics::a12(&row.values, &control_values));
+
+                let p_value = if comparison.paired {
+                    let (x, y): (Vec<f64>, Vec<f64>) = algorithm_sample
+                        .iter()
+                        .filter_map(|(run, x)| {
+                            control_sample
+                                .iter()
+                                .find(|(control_run, _)| control_run == run)
+                                .map(|(_, y)| (*x, *y))
+                        })
+                        .unzip();
+                    statistics::signed_rank(&x, &y)
+                } else {
+                    statistics::rank_sum(&row.values, &control_values)
+                };
+
+                row.p_value = Some(p_value);
+                p_values.push((rows.len(), p_value));
+            }
+            _ => {}
+        }
+
+        rows.push(row);
+    }
+
+    let adjusted = statistics::holm(&p_values.iter().map(|(_, p)| *p).collect::<Vec<f64>>());
+    for ((j, _), holm_p_value) in p_values.into_iter().zip(adjusted) {
+        let row = &mut rows[j];
+        row.holm_p_value = Some(holm_p_value);
+
+        // Only a larger hypervolume is better
+        let is_larger = row.a12.unwrap() > 0.5;
+        row.result = if holm_p_value >= comparison.alpha {
+            "equal"
+        } else if is_larger == (indicator == "hypervolume") {
+            "better"
+        } else {
+            "worse"
+        };
+    }
+
+    rows
+}
+
+fn write_comparison(output: &Path, rows: &[ComparisonRow]) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(output.join("comparison.csv"))?);
+
+    writeln!(
+        file,
+        "problem,indicator,algorithm,runs,mean,stdev,median,a12,p_value,holm_p_value,result"
+    )?;
+
+    let optional = |value: Option<f64>| value.map_or(String::new(), |value| value.to_string());
+
+    for row in rows {
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            row.problem,
+            row.indicator,
+            row.algorithm,
+            row.values.len(),
+            mean(&row.values),
+            std_dev(&row.values),
+            percentile(&row.values, 50.0),
+            optional(row.a12),
+            optional(row.p_value),
+            optional(row.holm_p_value),
+            row.result
+        )?;
+    }
+
+    Ok(())
+}
+
+// One table per indicator with a row per problem and a column per algorithm, the control first
+fn write_latex(
+    output: &Path,
+    rows: &[ComparisonRow],
+    algorithms: &[String],
+    control: &str,
+    comparison: &Comparison,
+) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(output.join("comparison.tex"))?);
+
+    let mut columns: Vec<&str> = vec![control];
+    columns.extend(
+        algorithms
+            .iter()
+            .map(|a| a.as_str())
+            .filter(|a| *a != control),
+    );
+
+    let test = if comparison.paired {
+        "signed-rank"
+    } else {
+        "rank-sum"
+    };
+
+    for indicator in summary_indicators() {
+        let indicator_rows: Vec<&ComparisonRow> = rows
+            .iter()
+            .filter(|row| row.indicator == indicator)
+            .collect();
+        if indicator_rows.is_empty() {
+            continue;
+        }
+
+        writeln!(file, "\\begin{{table}}")?;
+        writeln!(file, "\\centering")?;
+        writeln!(
+            file,
+            "\\caption{{Mean (standard deviation) of {}. $+$, $-$ and $\\approx$ mark results significantly better than, worse than or no different to {} (Wilcoxon {} test, Holm corrected, $\\alpha = {}$).}}",
+            latex_escape(indicator),
+            control,
+            test,
+            comparison.alpha
+        )?;
+        writeln!(file, "\\begin{{tabular}}{{l{}}}", "c".repeat(columns.len()))?;
+        writeln!(file, "\\hline")?;
+        writeln!(file, "Problem & {} \\\\", columns.join(" & "))?;
+        writeln!(file, "\\hline")?;
+
+        let mut problems: Vec<&str> = indicator_rows
+            .iter()
+            .map(|row| row.problem.as_str())
+            .collect();
+        problems.dedup();
+
+        for problem in problems {
+            let cells: Vec<String> = columns
+                .iter()
+                .map(|algorithm| {
+                    let row = indicator_rows
+                        .iter()
+                        .find(|row| row.problem == problem && row.algorithm == *algorithm);
+
+                    match row {
+                        Some(row) => {
+                            let marker = match row.result {
+                                "better" => " $+$",
+                                "worse" => " $-$",
+                                "equal" => " $\\approx$",
+                                _ => "",
+                            };
+
+                            format!(
+                                "{} ({}){}",
+                                latex_number(mean(&row.values)),
+                                latex_number(std_dev(&row.values)),
+                                marker
+                            )
+                        }
+                        None => "--".to_string(),
+                    }
+                })
+                .collect();
+
+            writeln!(
+                file,
+                "{} & {} \\\\",
+                latex_escape(problem),
+                cells.join(" & ")
+            )?;
+        }
+
+        writeln!(file, "\\hline")?;
+        writeln!(file, "\\end{{tabular}}")?;
+        writeln!(file, "\\end{{table}}")?;
+        writeln!(file)?;
+    }
+
+    Ok(())
+}
+
+fn latex_escape(text: &str) -> String {
+    text.replace("_", "\\_")
+}
+
+// Four significant figures, in scientific notation when very large or small
+fn latex_number(value: f64) -> String {
+    if value == 0.0 || (value.abs() >= 1e-3 && value.abs() < 1e4) {
+        format!("{:.4}", value)
+    } else {
+        let formatted = format!("{:.3e}", value);
+        let mut parts = formatted.splitn(2, 'e');
+        let mantissa = parts.next().unwrap();
+        let exponent = parts.next().unwrap();
+        format!("${} \\times 10^{{{}}}$", mantissa, exponent)
+    }
+}
+
+// Adds every folder below `folder` that holds .objs files
+fn find_run_folders(folder: &Path, run_folders: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut has_objs = false;
+
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            find_run_folders(&path, run_folders)?;
+        } else if is_objs(&path) {
+            has_objs = true;
+        }
+    }
+
+    if has_objs {
+        run_folders.push(folder.to_path_buf());
+    }
+
+    Ok(())
+}
+
+fn read_run(results_folder: &Path, folder: &Path) -> Result<Option<Run>, Box<dyn Error>> {
+    let components: Vec<String> = folder
+        .strip_prefix(results_folder)?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    if components.len() < 2 {
+        eprintln!("Skipping {:?}, it is not in an algorithm folder", folder);
+        return Ok(None);
+    }
+
+    let num_components = components.len();
+    let problem = if num_components > 2 {
+        components[..num_components - 2].join("/")
+    } else {
+        ".".to_string()
+    };
+
+    let params_path = folder.join(PARAMETERS_FILE);
+    let objectives = if params_path.exists() {
+        Parameters::load(&params_path)?.objectives()
+    } else {
+        DEFAULT_OBJECTIVES.to_vec()
+    };
+    let energy = objectives
+        .iter()
+        .position(|&objective| objective == Objective::Energy);
+
+    let mut fronts = Vec::new();
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        if is_objs(&path) {
+            fronts.push(read_front(&path, energy)?);
+        }
+    }
+    fronts.sort_by_key(|front| front.evaluations);
+
+    let time_path = folder.join("running_time.out");
+    let running_time = if time_path.exists() {
+        let time = fs::read_to_string(&time_path)?;
+        let time = time
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid running time in {:?}", time_path))?;
+        Some(time)
+    } else {
+        None
+    };
+
+    Ok(Some(Run {
+        problem,
+        algorithm: components[num_components - 2].clone(),
+        run: components[num_components - 1].clone(),
+        fronts,
+        running_time,
+    }))
+}
+
+// Reads the objectives of the feasible solutions, giving the energy, the objective at
+// `energy`, per service
+fn read_front(path: &Path, energy: Option<usize>) -> Result<Front, Box<dyn Error>> {
+    let invalid_name = || format!("Expected <services>_<evaluations>.objs, found {:?}", path);
+
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    let mut parts = stem.splitn(2, '_');
+    let num_services: f64 = parts
+        .next()
+        .and_then(|part| part.parse().ok())
+        .ok_or_else(invalid_name)?;
+    let evaluations = parts
+        .next()
+        .and_then(|part| part.parse().ok())
+        .ok_or_else(invalid_name)?;
+
+    let mut points = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line == "Infeasible" {
+            continue;
+        }
+
+        let mut point = Vec::new();
+        for value in line.split(',') {
+            let value: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid objective '{}' in {:?}", value, path))?;
+            point.push(value);
+        }
+
+        if let Some(energy) = energy {
+            if energy >= point.len() {
+                return Err(
+                    format!("Expected the energy at objective {} in {:?}", energy, path).into(),
+                );
+            }
+
+            point[energy] /= num_services;
+        }
+
+        points.push(point);
+    }
+
+    Ok(Front {
+        evaluations,
+        points,
+    })
+}
+
+fn is_objs(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .map_or(false, |extension| extension == "objs")
+}
+
+// Sorts run folders numerically where they are numbers
+fn natural_key(name: &str) -> (usize, &str) {
+    (name.parse().unwrap_or(std::usize::MAX), name)
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyse() {
+        let folder = std::env::temp_dir().join("nfv_mls_conf_test_analysis");
+        let _ = fs::remove_dir_all(&folder);
+
+        let write = |path: &str, contents: &str| {
+            let path = folder.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        };
+
+        write("FatTree/16/NSGAII/0/2_100.objs", "3,3,4\nInfeasible\n");
+        write("FatTree/16/NSGAII/0/2_200.objs", "1,3,4\n3,1,4\n3,3,6\n");
+        write("FatTree/16/NSGAII/0/running_time.out", "12");
+        write("FatTree/16/PPLS/0/2_200.objs", "1,3,2\n");
+        write("FatTree/16/PPLS/0/running_time.out", "4");
+
+        let output = folder.join(ANALYSIS_FOLDER);
+        let comparison = Comparison {
+            control: None,
+            paired: false,
+            alpha: 0.05,
+        };
+        analyse(&folder, &output, &comparison).unwrap();
+
+        let runs = fs::read_to_string(output.join("runs.csv")).unwrap();
+        let runs: Vec<&str> = runs.lines().collect();
+
+        assert_eq!(runs.len(), 4);
+        assert!(runs[1].starts_with("FatTree/16,NSGAII,0,100,1,"));
+        assert!(runs[2].starts_with("FatTree/16,NSGAII,0,200,2,"));
+        assert!(runs[3].starts_with("FatTree/16,PPLS,0,200,1,"));
+
+        // Only the final fronts and the running times are summarised
+        let summary = fs::read_to_string(output.join("summary.csv")).unwrap();
+        let summary: Vec<&str> = summary.lines().collect();
+
+        assert_eq!(summary.len(), 1 + 2 * (INDICATORS.len() + 1));
+        assert!(summary.contains(&"FatTree/16,NSGAII,running_time,1,12,0,12,12,12,12,12"));
+        assert!(summary.contains(&"FatTree/16,PPLS,running_time,1,4,0,4,4,4,4,4"));
+        assert!(summary
+            .iter()
+            .any(|line| line.starts_with("FatTree/16,PPLS,hypervolume,1,")));
+
+        // NSGAII is the control, a single run each is never a significant difference
+        let comparison = fs::read_to_string(output.join("comparison.csv")).unwrap();
+        let comparison: Vec<&str> = comparison.lines().collect();
+
+        assert_eq!(comparison.len(), 1 + 2 * (INDICATORS.len() + 1));
+        assert!(comparison.contains(&"FatTree/16,running_time,NSGAII,1,12,0,12,,,,control"));
+        assert!(comparison.contains(&"FatTree/16,running_time,PPLS,1,4,0,4,0,1,1,equal"));
+        assert!(output.join("comparison.tex").exists());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_read_front() {
+        assert!(read_front(Path::new("results.objs"), None).is_err());
+        assert!(read_front(Path::new("10_x.objs"), None).is_err());
+
+        let folder = std::env::temp_dir().join("nfv_mls_conf_test_read_front");
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(folder.join("NSGAII/0")).unwrap();
+        fs::write(folder.join("NSGAII/0/2_10.objs"), "4,1,6\nInfeasible\n").unwrap();
+
+        let front = read_front(&folder.join("NSGAII/0/2_10.objs"), Some(0)).unwrap();
+        assert_eq!(front.evaluations, 10);
+        assert_eq!(front.points, vec![vec![2.0, 1.0, 6.0]]);
+        assert!(read_front(&folder.join("NSGAII/0/2_10.objs"), Some(3)).is_err());
+
+        // Without parameters the energy is the third of the default objectives
+        let run = read_run(&folder, &folder.join("NSGAII/0"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.fronts[0].points, vec![vec![4.0, 1.0, 3.0]]);
+
+        let mut params = Parameters::default();
+        params.objectives = vec![Objective::MeanLatency, Objective::ActiveServers];
+        params.resolve_seeds();
+        fs::write(
+            folder.join("NSGAII/0").join(PARAMETERS_FILE),
+            serde_json::to_string(&params).unwrap(),
+        )
+        .unwrap();
+        fs::write(folder.join("NSGAII/0/2_10.objs"), "4,1\n").unwrap();
+
+        let run = read_run(&folder, &folder.join("NSGAII/0"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.fronts[0].points, vec![vec![4.0, 1.0]]);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,325 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{
+        datacentre::NodeID,
+        resources::Resources,
+        service::{Service, ServiceID},
+    },
+    operators::{
+        mapping::{Mapping, NodeType, RouteNode},
+        solution::{Constraint, Solution, Violation},
+    },
+};
+
+/// Format of the exported solutions, both hold the same structure
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Bincode,
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Json => write!(f, "json"),
+            ExportFormat::Bincode => write!(f, "bincode"),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "bincode" | "binary" => Ok(ExportFormat::Bincode),
+            _ => Err(format!(
+                "Unknown export format '{}', expected json or bincode",
+                s
+            )),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Name of the file the solutions reported after `evaluations` are exported to
+    pub fn file_name(&self, num_services: usize, evaluations: usize) -> String {
+        let extension = match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Bincode => "bin",
+        };
+
+        format!("{}_{}.solutions.{}", num_services, evaluations, extension)
+    }
+}
+
+/**
+ * The solutions of a population with their placements decoded, so they can be deployed or
+ * inspected rather than only their objectives. Files ending in .json hold it as JSON and any
+ * other extension holds it in bincode.
+ **/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SolutionExport {
+    pub num_servers: usize,
+
+    /// The largest capacity of each resource type over the servers
+    pub server_capacity: Resources,
+    pub evaluations: usize,
+    pub solutions: Vec<ExportedSolution>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExportedSolution {
+    pub objectives: Constraint<Vec<f64>, Violation>,
+
+    /// Ids of the services whose first VNF is placed from each server, the encoded solution
+    pub placement: Vec<Vec<ServiceID>>,
+
+    /// Servers hosting at least one VNF, by id
+    pub servers: Vec<ServerUsage>,
+
+    /// Route graph of each service instance that was placed
+    pub routes: Vec<ServiceRoute>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ServerUsage {
+    pub server: NodeID,
+    pub capacity_used: Resources,
+
+    /// (service id, stage) of each VNF instance on the server
+    pub vnfs: Vec<(ServiceID, usize)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ServiceRoute {
+    pub service: ServiceID,
+    pub route: Vec<RouteNode>,
+}
+
+impl SolutionExport {
+    /// Decodes the solutions of a population with the mapping used to evaluate them
+    pub fn new<'a, M>(
+        mapping: &M,
+        services: &[Service],
+        capacities: &[Resources],
+        evaluations: usize,
+        pop: &[Solution<Vec<&'a Service>>],
+    ) -> SolutionExport
+    where
+        M: Mapping<Vec<&'a Service>>,
+    {
+        let solutions = pop
+            .iter()
+            .map(|solution| export_solution(mapping, services, capacities, solution))
+            .collect();
+
+        SolutionExport {
+            num_servers: capacities.len(),
+            server_capacity: capacities
+                .iter()
+                .fold(Resources::scalar(0), |max, capacity| max.max(capacity)),
+            evaluations,
+            solutions,
+        }
+    }
+
+    /// Reads an export, a file with anything after the export is not one
+    pub fn load(path: &Path) -> Result<SolutionExport, Box<dyn Error>> {
+        let file = File::open(path)
+            .map_err(|err| format!("Could not open solutions {:?}: {}", path, err))?;
+        let mut reader = BufReader::new(file);
+
+        if is_json(path) {
+            Ok(serde_json::from_reader(reader)?)
+        } else {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+
+            let options = bincode::DefaultOptions::new().with_fixint_encoding();
+            Ok(options.deserialize(&bytes)?)
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+
+        let writer = BufWriter::new(File::create(path)?);
+
+        if is_json(path) {
+            serde_json::to_writer_pretty(writer, self)?;
+        } else {
+            bincode::serialize_into(writer, self)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn export_solution<'a, M>(
+    mapping: &M,
+    services: &[Service],
+    capacities: &[Resources],
+    solution: &Solution<Vec<&'a Service>>,
+) -> ExportedSolution
+where
+    M: Mapping<Vec<&'a Service>>,
+{
+    let placement = solution
+        .point
+        .iter()
+        .map(|server| server.iter().map(|service| service.id).collect())
+        .collect();
+
+    let mut servers: Vec<ServerUsage> = (0..capacities.len())
+        .map(|server| ServerUsage {
+            server,
+            capacity_used: Resources::scalar(0),
+            vnfs: Vec::new(),
+        })
+        .collect();
+
+    let routes: Vec<ServiceRoute> = mapping
+        .apply(solution)
+        .into_iter()
+        .map(|(service, route)| ServiceRoute { service, route })
+        .collect();
+
+    // The VNF nodes of the routes are the instances actually placed
+    for service_route in &routes {
+        for node in &service_route.route {
+            if let NodeType::VNF(server, stage) = node.node_type {
+                let usage = &mut servers[server];
+                usage.capacity_used += services[service_route.service].vnfs[stage].size;
+                usage.vnfs.push((service_route.service, stage));
+            }
+        }
+    }
+
+    servers.retain(|usage| !usage.vnfs.is_empty());
+
+    ExportedSolution {
+        objectives: solution.objectives.clone(),
+        placement,
+        servers,
+        routes,
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("json"))
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            datacentre::FatTree,
+            routing::get_tables,
+            service::{Sla, VNF},
+        },
+        operators::{
+            distance_matrix::build_cache, mapping::ServiceToRouteMapping,
+            placement_strategies::FirstFit,
+        },
+    };
+
+    #[test]
+    fn test_export() {
+        let dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+        let dm = build_cache(&dc, dc.num_servers);
+        let capacities = vec![Resources::scalar(100); dc.num_servers];
+        let mapping = ServiceToRouteMapping::new(FirstFit::new(), &capacities, &dm, &rt);
+
+        let vnf = |size| VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(size),
+        };
+        let services = vec![
+            Service {
+                id: 0,
+                prod_rate: 5.0,
+                vnfs: vec![vnf(60), vnf(60)],
+                sla: Sla::default(),
+            },
+            Service {
+                id: 1,
+                prod_rate: 5.0,
+                vnfs: vec![vnf(30)],
+                sla: Sla::default(),
+            },
+        ];
+
+        let mut point = vec![Vec::new(); dc.num_servers];
+        point[2] = vec![&services[0], &services[1]];
+        let mut solution = Solution::new(point);
+        solution.objectives = Constraint::Feasible(vec![1.0, 0.0, 2.0]);
+
+        let export = SolutionExport::new(&mapping, &services, &capacities, 100, &[solution]);
+        assert_eq!(export.num_servers, dc.num_servers);
+        assert_eq!(export.server_capacity, Resources::scalar(100));
+
+        let exported = &export.solutions[0];
+        assert_eq!(exported.placement[2], vec![0, 1]);
+        assert_eq!(exported.routes.len(), 2);
+        assert_eq!(exported.routes[0].service, 0);
+
+        // Both VNFs of the first service do not fit on one server
+        let used: usize = exported
+            .servers
+            .iter()
+            .map(|usage| usage.capacity_used[0])
+            .sum();
+        assert_eq!(used, 150);
+        assert!(exported.servers.len() >= 2);
+        assert!(exported
+            .servers
+            .iter()
+            .all(|usage| usage.capacity_used[0] <= 100));
+        assert!(exported
+            .servers
+            .iter()
+            .any(|usage| usage.vnfs.contains(&(0, 1))));
+
+        let folder = std::env::temp_dir().join("nfv_mls_conf_test_export");
+        for format in &[ExportFormat::Json, ExportFormat::Bincode] {
+            let path = folder.join(format.file_name(2, 100));
+            export.save(&path).unwrap();
+
+            assert_eq!(SolutionExport::load(&path).unwrap(), export);
+        }
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_export_format() {
+        assert_eq!("JSON".parse(), Ok(ExportFormat::Json));
+        assert_eq!("binary".parse(), Ok(ExportFormat::Bincode));
+        assert!("csv".parse::<ExportFormat>().is_err());
+
+        assert_eq!(
+            ExportFormat::Json.file_name(10, 500),
+            "10_500.solutions.json"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{resources::Resources, service::Service};
+
+pub const INSTANCE_FILE: &str = "instance.json";
+
+/**
+ * A problem instance, the services to place in a datacentre with `num_servers` servers that
+ * each have `server_capacity`, or the capacity in `server_capacities` when they differ. With
+ * several resource types each capacity and VNF size is a list, e.g. `[32, 64, 10]`. Files
+ * ending in .json hold it as JSON:
+ *
+ * {
+ *   "num_servers": 16,
+ *   "server_capacity": 100,
+ *   "services": [
+ *     {
+ *       "id": 0,
+ *       "prod_rate": 10.3,
+ *       "vnfs": [
+ *         { "service_rate": 9.8, "queue_length": 20, "size": 41 },
+ *         { "service_rate": 12.1, "queue_length": 20, "size": 35 }
+ *       ]
+ *     }
+ *   ]
+ * }
+ *
+ * any other extension holds the same structure in bincode. The id of a service is its
+ * position in the list, as the models index the services by id.
+ **/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub num_servers: usize,
+
+    /// The largest capacity of each resource type over the servers
+    pub server_capacity: Resources,
+
+    /// The capacity of each server, empty when they all have `server_capacity`
+    #[serde(default)]
+    pub server_capacities: Vec<Resources>,
+
+    pub services: Vec<Service>,
+}
+
+impl Instance {
+    /// An instance for servers with the given capacities
+    pub fn new(capacities: &[Resources], services: Vec<Service>) -> Instance {
+        let server_capacity = capacities
+            .iter()
+            .fold(Resources::scalar(0), |max, capacity| max.max(capacity));
+
+        let server_capacities = if capacities.iter().all(|&c| c == server_capacity) {
+            Vec::new()
+        } else {
+            capacities.to_vec()
+        };
+
+        Instance {
+            num_servers: capacities.len(),
+            server_capacity,
+            server_capacities,
+            services,
+        }
+    }
+
+    /// The capacity of each server
+    pub fn capacities(&self) -> Vec<Resources> {
+        if self.server_capacities.is_empty() {
+            vec![self.server_capacity; self.num_servers]
+        } else {
+            self.server_capacities.clone()
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Instance, Box<dyn Error>> {
+        let file = File::open(path)
+            .map_err(|err| format!("Could not open instance {:?}: {}", path, err))?;
+        let reader = BufReader::new(file);
+
+        let instance: Instance = if is_json(path) {
+            serde_json::from_reader(reader)?
+        } else {
+            bincode::deserialize_from(reader)?
+        };
+
+        instance
+            .validate()
+            .map_err(|err| format!("Invalid instance {:?}: {}", path, err))?;
+
+        Ok(instance)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+
+        let writer = BufWriter::new(File::create(path)?);
+
+        if is_json(path) {
+            serde_json::to_writer_pretty(writer, self)?;
+        } else {
+            bincode::serialize_into(writer, self)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.services.is_empty() {
+            return Err("there are no services".to_string());
+        }
+        if !self.server_capacities.is_empty() && self.server_capacities.len() != self.num_servers {
+            return Err(format!(
+                "there are capacities for {} of the {} servers",
+                self.server_capacities.len(),
+                self.num_servers
+            ));
+        }
+
+        let capacities = self.capacities();
+
+        for (i, service) in self.services.iter().enumerate() {
+            if service.id != i {
+                return Err(format!("service {} has id {}", i, service.id));
+            }
+            if service.vnfs.is_empty() {
+                return Err(format!("service {} has no VNFs", i));
+            }
+            if !(service.prod_rate > 0.0) {
+                return Err(format!("service {} has a non-positive production rate", i));
+            }
+            if !service.sla.is_valid() {
+                return Err(format!("service {} has a non-positive SLA bound", i));
+            }
+
+            for vnf in &service.vnfs {
+                if !(vnf.service_rate > 0.0) || vnf.queue_length == 0 {
+                    return Err(format!(
+                        "service {} has a VNF without a positive service rate and queue length",
+                        i
+                    ));
+                }
+                if vnf.size.is_zero() || !capacities.iter().any(|c| c.fits(&vnf.size)) {
+                    return Err(format!(
+                        "service {} has a VNF of size {} which does not fit in any server",
+                        i, vnf.size
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the instance was made for the datacentre it is about to be placed in
+    pub fn check_datacentre(
+        &self,
+        num_servers: usize,
+        capacities: &[Resources],
+    ) -> Result<(), String> {
+        if self.num_servers != num_servers {
+            return Err(format!(
+                "Instance is for {} servers, but the datacentre has {} servers",
+                self.num_servers, num_servers
+            ));
+        }
+        if self.capacities() != capacities {
+            return Err(format!(
+                "Instance is for servers with capacities up to {}, but the datacentre's servers have different capacities",
+                self.server_capacity
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("json"))
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::service::{Sla, VNF};
+
+    fn get_instance() -> Instance {
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(40),
+        };
+
+        let services = (0..3)
+            .map(|id| Service {
+                id,
+                prod_rate: 5.0 + id as f64,
+                vnfs: vec![vnf; id + 1],
+                sla: Sla::default(),
+            })
+            .collect();
+
+        Instance::new(&[Resources::scalar(100); 16], services)
+    }
+
+    #[test]
+    fn test_save_load() {
+        let folder = std::env::temp_dir().join("nfv_mls_conf_test_instance");
+        let instance = get_instance();
+
+        for file in &["instance.json", "instance.dat"] {
+            let path = folder.join(file);
+            instance.save(&path).unwrap();
+
+            assert_eq!(Instance::load(&path).unwrap(), instance);
+        }
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(get_instance().validate().is_ok());
+
+        let mut instance = get_instance();
+        instance.services.swap(0, 1);
+        assert!(instance.validate().is_err());
+
+        let mut instance = get_instance();
+        instance.services[2].vnfs[0].size = Resources::scalar(101);
+        assert!(instance.validate().is_err());
+
+        let capacities = [Resources::scalar(100); 16];
+        let instance = get_instance();
+        assert!(instance.check_datacentre(16, &capacities).is_ok());
+        assert!(instance
+            .check_datacentre(20, &[Resources::scalar(100); 20])
+            .is_err());
+        assert!(instance
+            .check_datacentre(16, &[Resources::scalar(80); 16])
+            .is_err());
+    }
+
+    #[test]
+    fn test_server_capacities() {
+        let small = Resources::new(&[48, 32]).unwrap();
+        let large = Resources::new(&[64, 64]).unwrap();
+        let capacities: Vec<_> = (0..16)
+            .map(|server| if server < 8 { small } else { large })
+            .collect();
+
+        let mut instance = get_instance();
+        instance.services[2].vnfs[0].size = Resources::new(&[20, 10]).unwrap();
+        let instance = Instance::new(&capacities, instance.services);
+        assert_eq!(instance.server_capacity, large);
+        assert_eq!(instance.capacities(), capacities);
+        assert!(instance.validate().is_ok());
+        assert!(instance.check_datacentre(16, &capacities).is_ok());
+        assert!(instance.check_datacentre(16, &[large; 16]).is_err());
+
+        let mut invalid = instance.clone();
+        invalid.services[2].vnfs[0].size = Resources::new(&[20, 70]).unwrap();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = instance.clone();
+        invalid.server_capacities.pop();
+        assert!(invalid.validate().is_err());
+
+        let uniform = Instance::new(&[large; 16], Vec::new());
+        assert!(uniform.server_capacities.is_empty());
+        assert_eq!(uniform.capacities(), vec![large; 16]);
+    }
+}
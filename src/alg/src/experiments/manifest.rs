@@ -0,0 +1,249 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use config::Config;
+use serde::Serialize;
+
+use super::{instance::INSTANCE_FILE, parameters::Parameters};
+use crate::{algorithms::Algorithm, models::datacentre::Topology};
+
+pub const PARAMETERS_FILE: &str = "parameters.json";
+
+/// Written to the folder of a job once its run has completed
+pub const FINISHED_FILE: &str = "finished";
+
+/**
+ * A single run of one algorithm on one problem instance.
+ **/
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Job {
+    pub topology: Topology,
+    pub scale: usize,
+    pub utilisation: f64,
+    pub algorithm: Algorithm,
+    pub run: usize,
+    pub seed: u64,
+}
+
+impl Job {
+    /// <results_folder>/<topology>/<scale>/<algorithm>/<run>, studies over several
+    /// utilisations get an extra utilisation_<u> folder after the results folder.
+    pub fn folder(&self, params: &Parameters) -> PathBuf {
+        let mut folder = params.results_folder.clone();
+
+        if params.utilisations.len() > 1 {
+            folder.push(format!("utilisation_{}", self.utilisation));
+        }
+
+        folder
+            .join(self.topology.to_string())
+            .join(self.scale.to_string())
+            .join(self.algorithm.to_string())
+            .join(self.run.to_string())
+    }
+
+    pub fn is_finished(&self, params: &Parameters) -> bool {
+        self.folder(params).join(FINISHED_FILE).exists()
+    }
+
+    /// The study parameters narrowed down to this job, using the instance saved with it
+    pub fn parameters(&self, params: &Parameters) -> Parameters {
+        let mut job_params = params.clone();
+        job_params.topologies = vec![self.topology];
+        job_params.scales = vec![self.scale];
+        job_params.utilisations = vec![self.utilisation];
+        job_params.algorithms = vec![self.algorithm];
+        job_params.num_instances = 1;
+        job_params.seeds = vec![self.seed];
+        job_params.instance = Some(self.folder(params).join(INSTANCE_FILE));
+
+        job_params
+    }
+}
+
+/**
+ * Expands the study into one job per combination of its factors. A manifest is a TOML or
+ * JSON file with the same keys as the config file, where the lists (topologies, scales,
+ * utilisations and algorithms) and num_instances are the factors. Jobs are ordered by
+ * topology, scale, utilisation, run and then algorithm, so all algorithms of a run are next
+ * to each other and share a problem instance. The seeds must have been resolved.
+ **/
+pub fn expand(params: &Parameters) -> Vec<Job> {
+    let mut jobs = Vec::new();
+
+    for &topology in &params.topologies {
+        for &scale in &params.scales {
+            for &utilisation in &params.utilisations {
+                for run in 0..params.num_instances {
+                    for &algorithm in &params.algorithms {
+                        jobs.push(Job {
+                            topology,
+                            scale,
+                            utilisation,
+                            algorithm,
+                            run,
+                            seed: params.seeds[run],
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    jobs
+}
+
+/**
+ * Saves a copy of the manifest, if there is one, and the resolved parameters of the
+ * whole study in the results folder.
+ **/
+pub fn save_study(params: &Parameters, manifest: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(&params.results_folder)?;
+
+    if let Some(manifest) = manifest {
+        let file_name = match manifest.extension() {
+            Some(extension) => format!("manifest.{}", extension.to_string_lossy()),
+            None => "manifest".to_string(),
+        };
+
+        fs::copy(manifest, params.results_folder.join(file_name))
+            .map_err(|err| format!("Could not copy manifest {:?}: {}", manifest, err))?;
+    }
+
+    write_parameters(&params.results_folder, params)
+}
+
+/**
+ * A resumed study must use the seeds it started with, so when none are given they are
+ * taken from the parameters saved in the results folder, if there are any.
+ **/
+pub fn resume_seeds(params: &mut Parameters) -> Result<(), Box<dyn Error>> {
+    let path = params.results_folder.join(PARAMETERS_FILE);
+    if params.seed.is_some() || !params.seeds.is_empty() || !path.exists() {
+        return Ok(());
+    }
+
+    let mut settings = Config::default();
+    settings.merge(config::File::from(path.as_path()))?;
+    let saved = Parameters::from_config(&settings)?;
+
+    params.seed = saved.seed;
+    params.seeds = saved.seeds;
+
+    Ok(())
+}
+
+/**
+ * Saves the parameters of the job in its results folder, running them as a manifest
+ * repeats just this job.
+ **/
+pub fn save_job(job: &Job, params: &Parameters) -> Result<(), Box<dyn Error>> {
+    let folder = job.folder(params);
+    fs::create_dir_all(&folder)?;
+
+    write_parameters(&folder, &job.parameters(params))
+}
+
+fn write_parameters(folder: &Path, params: &Parameters) -> Result<(), Box<dyn Error>> {
+    let file = File::create(folder.join(PARAMETERS_FILE))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), params)?;
+
+    Ok(())
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let mut params = Parameters::default();
+        params.topologies = vec![Topology::FatTree, Topology::DCell];
+        params.scales = vec![500];
+        params.utilisations = vec![0.4, 0.8];
+        params.algorithms = vec![Algorithm::NSGAII, Algorithm::PPLS];
+        params.num_instances = 3;
+        params.resolve_seeds();
+
+        let jobs = expand(&params);
+        assert_eq!(jobs.len(), 2 * 2 * 2 * 3);
+
+        // Algorithms of the same run are adjacent
+        assert_eq!(jobs[0].algorithm, Algorithm::NSGAII);
+        assert_eq!(jobs[1].algorithm, Algorithm::PPLS);
+        assert_eq!(jobs[0].run, jobs[1].run);
+        assert_eq!(jobs[2].run, 1);
+        assert_eq!(jobs[2].seed, params.seeds[1]);
+
+        assert_eq!(
+            jobs[1].folder(&params),
+            PathBuf::from("results/utilisation_0.4/FatTree/500/PPLS/0")
+        );
+
+        params.utilisations = vec![0.4];
+        assert_eq!(
+            expand(&params)[1].folder(&params),
+            PathBuf::from("results/FatTree/500/PPLS/0")
+        );
+    }
+
+    #[test]
+    fn test_resume_seeds() {
+        let mut params = Parameters::default();
+        params.results_folder = std::env::temp_dir().join("nfv_mls_conf_test_resume");
+        params.num_instances = 3;
+        params.resolve_seeds();
+        save_study(&params, None).unwrap();
+
+        let mut resumed = params.clone();
+        resumed.seed = None;
+        resumed.seeds.clear();
+        resume_seeds(&mut resumed).unwrap();
+
+        assert_eq!(resumed.seed, params.seed);
+        assert_eq!(resumed.seeds, params.seeds);
+
+        // Seeds that are given win
+        let mut other = params.clone();
+        other.seed = Some(1);
+        other.seeds.clear();
+        resume_seeds(&mut other).unwrap();
+        assert!(other.seeds.is_empty());
+
+        fs::remove_dir_all(&params.results_folder).unwrap();
+    }
+
+    #[test]
+    fn test_job_parameters_round_trip() {
+        let mut params = Parameters::default();
+        params.utilisations = vec![0.4, 0.8];
+        params.pop_size = 64;
+        params.resolve_seeds();
+
+        let job = &expand(&params)[5];
+        let json = serde_json::to_string(&job.parameters(&params)).unwrap();
+
+        let mut settings = Config::default();
+        settings
+            .merge(config::File::from_str(&json, config::FileFormat::Json))
+            .unwrap();
+        let resolved = Parameters::from_config(&settings).unwrap();
+
+        let expected = Job {
+            run: 0,
+            ..job.clone()
+        };
+
+        assert_eq!(expand(&resolved), vec![expected]);
+        assert_eq!(resolved.pop_size, 64);
+        assert_eq!(
+            resolved.instance,
+            Some(job.folder(&params).join(INSTANCE_FILE))
+        );
+    }
+}
@@ -0,0 +1,543 @@
+pub mod analysis;
+pub mod export;
+pub mod instance;
+pub mod manifest;
+pub mod parameters;
+pub mod placement;
+pub mod simulation;
+pub mod workload;
+
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{prelude::*, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    algorithms::{
+        checkpoint::{Checkpoint, CHECKPOINT_FILE},
+        cnsgaii, moead, nsgaii, pnsgaii, pplsd, sppls, Algorithm,
+    },
+    models::{
+        datacentre::{Datacentre, Topology},
+        resources::Resources,
+        routing::{self, RoutingTable},
+        service::Service,
+    },
+    operators::{
+        codec::ServiceCodec,
+        crossover::UniformCrossover,
+        distance_matrix,
+        evaluation::{Evaluation, QueueingEval},
+        initialisation::ServiceAwareInitialisation,
+        mapping::ServiceToRouteMapping,
+        mutation::AddRemoveSwapMutation,
+        neighbour_gen::AddSwapNeighbour,
+        placement_strategies::FirstFit,
+        solution::Solution,
+    },
+    utilities::{
+        rng::{self, thread_rng},
+        stopwatch::Stopwatch,
+    },
+};
+use export::SolutionExport;
+use instance::{Instance, INSTANCE_FILE};
+use manifest::Job;
+use parameters::Parameters;
+use workload::Workload;
+
+/// Runs every job of the study, when resuming finished jobs are skipped
+pub fn run_experiments(
+    params: &Parameters,
+    manifest: Option<&Path>,
+    resume: bool,
+) -> Result<(), Box<dyn Error>> {
+    let jobs = manifest::expand(params);
+    manifest::save_study(params, manifest)?;
+
+    for jobs in group_jobs(&jobs, |a, b| a.topology == b.topology && a.scale == b.scale) {
+        println!("Starting {} {}", jobs[0].topology, jobs[0].scale);
+        run_basic_tests(params, jobs, resume)?;
+    }
+
+    Ok(())
+}
+
+// Splits the jobs into runs of consecutive jobs that are the same according to `same`
+fn group_jobs<F>(jobs: &[Job], same: F) -> Vec<&[Job]>
+where
+    F: Fn(&Job, &Job) -> bool,
+{
+    let mut groups = Vec::new();
+    let mut start = 0;
+
+    for i in 1..=jobs.len() {
+        if i == jobs.len() || !same(&jobs[start], &jobs[i]) {
+            groups.push(&jobs[start..i]);
+            start = i;
+        }
+    }
+
+    groups
+}
+
+// Runs jobs that all share a topology and scale
+fn run_basic_tests(params: &Parameters, jobs: &[Job], resume: bool) -> Result<(), Box<dyn Error>> {
+    let (topology, scale) = (jobs[0].topology, jobs[0].scale);
+    let (mut dc, rt) = load_topology(&params.topology_folder, &topology, scale)?;
+    params.set_links(&mut dc);
+
+    // The distance matrix breaks ties randomly and is shared by all runs
+    if let Some(seed) = params.seed {
+        rng::seed(seed);
+    }
+
+    // Mapping + Fitness function
+    let num_nearest = dc.num_servers;
+    let dm = distance_matrix::build_cache(&dc, num_nearest);
+
+    let capacities = load_capacities(
+        &params.topology_folder,
+        &topology,
+        scale,
+        dc.num_servers,
+        params.server_capacity,
+    )?;
+
+    let qm = placement::queueing_model(params, &dc);
+
+    let stored = match &params.instance {
+        Some(path) => {
+            let instance = Instance::load(path)?;
+            instance.check_datacentre(dc.num_servers, &capacities)?;
+            Some(instance)
+        }
+        None => None,
+    };
+
+    let same_instance = |a: &Job, b: &Job| a.utilisation == b.utilisation && a.run == b.run;
+
+    for jobs in group_jobs(jobs, same_instance) {
+        rng::seed(jobs[0].seed);
+
+        // Every algorithm starts from the same state so they all see the same random numbers.
+        // Drawn before the instance, so runs on a stored instance repeat the generated ones.
+        let algorithm_seed = rng::next_seed();
+
+        let instance = match &stored {
+            Some(instance) => instance.clone(),
+            None => generate_instance(&params.workload, &capacities, jobs[0].utilisation)?,
+        };
+        let services = &instance.services;
+        let codec = ServiceCodec::new(services);
+
+        let node_selection = FirstFit::new();
+
+        // --- Genetic Operators ---
+        let mut evaluate = QueueingEval::new(
+            qm.clone(),
+            &rt,
+            &dm,
+            &capacities,
+            services,
+            node_selection.clone(),
+        );
+        evaluate.link_overload = params.link_overload;
+        evaluate.aggregation = params.violation_aggregation;
+        evaluate.objectives = params.objectives();
+
+        // Initialisation
+        let init_pop = ServiceAwareInitialisation::new(services, dc.num_servers);
+
+        // Mapping
+        let strm = ServiceToRouteMapping::new(node_selection.clone(), &capacities, &dm, &rt);
+
+        // Mutation
+        let items = services.iter().map(|s| s).collect();
+        let mutation = AddRemoveSwapMutation::new(items, params.pm);
+
+        // Crossover
+        let crossover = UniformCrossover::new(params.pc);
+
+        // Neighbour generation for the local search algorithms
+        let items = services.iter().map(|s| s).collect();
+        let neighbour_gen = AddSwapNeighbour::new(items);
+
+        for job in jobs {
+            let alg_folder = job.folder(params);
+
+            if resume && job.is_finished(params) {
+                println!("Skipping finished {:?}", alg_folder);
+                continue;
+            }
+
+            manifest::save_job(job, params)?;
+            instance.save(&alg_folder.join(INSTANCE_FILE))?;
+
+            // A rerun in the same folder is unfinished until it completes
+            let finished_path = alg_folder.join(manifest::FINISHED_FILE);
+            if finished_path.exists() {
+                fs::remove_file(&finished_path)?;
+            }
+
+            let checkpoint_path = alg_folder.join(CHECKPOINT_FILE);
+            let checkpoint = Checkpoint::new(
+                &codec,
+                checkpoint_path.clone(),
+                params.checkpoint_interval,
+                resume,
+            );
+
+            rng::seed(algorithm_seed);
+
+            // The evaluation is shared by the jobs on this instance
+            let failures_before = evaluate.num_failures();
+
+            let mut stopwatch = Stopwatch::new();
+            stopwatch.start();
+
+            // The run carries on when its results cannot be written, the first error is
+            // reported once it has finished. PPLS/D and SPPLS report from several threads.
+            let write_error: Mutex<Option<String>> = Mutex::new(None);
+
+            let observer = |evaluations: usize, pop: &Vec<Solution<Vec<&Service>>>| {
+                let time = stopwatch.stop();
+
+                let write = || -> Result<(), Box<dyn Error>> {
+                    let file_name = format!("{}_{}.objs", services.len(), evaluations);
+                    print_population_objectives(&alg_folder, file_name, pop)?;
+
+                    if !params.export.is_empty() {
+                        let export =
+                            SolutionExport::new(&strm, services, &capacities, evaluations, pop);
+
+                        for format in &params.export {
+                            let path =
+                                alg_folder.join(format.file_name(services.len(), evaluations));
+                            export.save(&path)?;
+                        }
+                    }
+
+                    let mut file = get_file(&alg_folder, "running_time.out")?;
+                    write!(file, "{}", time)?;
+
+                    Ok(())
+                };
+
+                if let Err(err) = write() {
+                    let mut write_error = write_error.lock().unwrap();
+                    if write_error.is_none() {
+                        *write_error = Some(err.to_string());
+                    }
+                }
+            };
+
+            let result = match job.algorithm {
+                Algorithm::NSGAII => nsgaii::run(
+                    &init_pop,
+                    &strm,
+                    &evaluate,
+                    &mutation,
+                    &crossover,
+                    params.pop_size,
+                    params.max_evaluations,
+                    params.report_interval,
+                    &checkpoint,
+                    observer,
+                ),
+                Algorithm::CNSGAII => cnsgaii::run(
+                    &init_pop,
+                    &strm,
+                    &evaluate,
+                    &mutation,
+                    &crossover,
+                    params.pop_size,
+                    params.max_evaluations,
+                    params.report_interval,
+                    &checkpoint,
+                    observer,
+                ),
+                Algorithm::PNSGAII => pnsgaii::run(
+                    &init_pop,
+                    &strm,
+                    &evaluate,
+                    &mutation,
+                    &crossover,
+                    params.pop_size,
+                    params.max_evaluations,
+                    params.num_epochs,
+                    params.report_interval,
+                    &checkpoint,
+                    observer,
+                ),
+                Algorithm::PPLS => pplsd::run(
+                    &init_pop,
+                    &strm,
+                    &evaluate,
+                    &neighbour_gen,
+                    params.ppls_pop_size,
+                    params.max_evaluations,
+                    params.ppls_neighbours,
+                    evaluate.num_objectives(),
+                    params.weight_design,
+                    params.report_interval,
+                    &checkpoint,
+                    observer,
+                ),
+                Algorithm::SPPLS => sppls::run(
+                    &init_pop,
+                    &strm,
+                    &evaluate,
+                    &neighbour_gen,
+                    params.ppls_pop_size,
+                    params.max_evaluations,
+                    params.ppls_neighbours,
+                    evaluate.num_objectives(),
+                    params.weight_design,
+                    params.report_interval,
+                    &checkpoint,
+                    observer,
+                ),
+                Algorithm::MOEAD => moead::run(
+                    &init_pop,
+                    &strm,
+                    &evaluate,
+                    &mutation,
+                    &crossover,
+                    params.pop_size,
+                    params.max_evaluations,
+                    evaluate.num_objectives(),
+                    params.weight_design,
+                    params.scalarisation,
+                    params.moead_neighbours,
+                    params.moead_delta,
+                    params.moead_max_replacements,
+                    params.report_interval,
+                    &checkpoint,
+                    observer,
+                ),
+            };
+            result.map_err(|err| format!("Could not run {:?}: {}", alg_folder, err))?;
+            if let Some(err) = write_error.into_inner().unwrap() {
+                return Err(
+                    format!("Could not write the results of {:?}: {}", alg_folder, err).into(),
+                );
+            }
+
+            // Counts the evaluations since the run started or was resumed
+            let failures = evaluate.num_failures() - failures_before;
+            if failures > 0 {
+                println!(
+                    "The queueing model did not converge in {} evaluations of {:?}",
+                    failures, alg_folder
+                );
+            }
+            let mut file = get_file(&alg_folder, "convergence_failures.out")?;
+            write!(file, "{}", failures)?;
+
+            if checkpoint_path.exists() {
+                fs::remove_file(&checkpoint_path)?;
+            }
+            File::create(finished_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Samples the services of a new problem instance from the current thread's generator
+pub fn generate_instance(
+    workload: &Workload,
+    capacities: &[Resources],
+    utilisation: f64,
+) -> Result<Instance, String> {
+    let services = workload.generate(&mut thread_rng(), capacities, utilisation)?;
+
+    Ok(Instance::new(capacities, services))
+}
+
+/// Builds a topology of each size and saves it with its routing tables and the links of `params`
+pub fn gen_topology(
+    params: &Parameters,
+    topology: Topology,
+    sizes: &[usize],
+) -> Result<(), Box<dyn Error>> {
+    for &size in sizes {
+        let mut dc = topology.build(size);
+        params.set_links(&mut dc);
+        println!(
+            "{} {}: {} ports, {} servers, {} components",
+            topology,
+            size,
+            dc.num_ports,
+            dc.num_servers,
+            dc.num_components()
+        );
+
+        let rt = routing::get_tables(&dc);
+        save_topology(&params.topology_folder, &topology, size, &dc, &rt)?;
+    }
+
+    Ok(())
+}
+
+fn topology_paths(folder: &Path, topology: &Topology, size: usize) -> (PathBuf, PathBuf) {
+    (
+        folder.join(format!("{}_{}.dat", topology, size)),
+        folder.join(format!("{}_routing_{}.dat", topology, size)),
+    )
+}
+
+fn save_topology(
+    folder: &Path,
+    topology: &Topology,
+    size: usize,
+    dc: &Datacentre,
+    rt: &Vec<RoutingTable>,
+) -> bincode::Result<()> {
+    fs::create_dir_all(folder)?;
+    let (dc_path, rt_path) = topology_paths(folder, topology, size);
+
+    let file = File::create(dc_path)?;
+    bincode::serialize_into(BufWriter::new(file), dc)?;
+
+    let file = File::create(rt_path)?;
+    bincode::serialize_into(BufWriter::new(file), rt)?;
+
+    Ok(())
+}
+
+// Topologies saved before their links had attributes, which get the default links
+#[derive(Deserialize)]
+struct UnlinkedDatacentre {
+    graph: Vec<Vec<usize>>,
+    num_ports: usize,
+    num_servers: usize,
+}
+
+pub fn load_topology(
+    folder: &Path,
+    topology: &Topology,
+    size: usize,
+) -> Result<(Datacentre, Vec<RoutingTable>), Box<dyn Error>> {
+    let (dc_path, rt_path) = topology_paths(folder, topology, size);
+
+    let open = |path: &PathBuf| {
+        File::open(path).map_err(|err| {
+            format!(
+                "Could not open {:?} ({}), create it with `gen_topology {} {}`",
+                path, err, topology, size
+            )
+        })
+    };
+
+    let mut bytes = Vec::new();
+    BufReader::new(open(&dc_path)?).read_to_end(&mut bytes)?;
+    let dc = match bincode::deserialize::<Datacentre>(&bytes) {
+        Ok(dc) => dc,
+        Err(_) => {
+            let dc: UnlinkedDatacentre = bincode::deserialize(&bytes)?;
+            Datacentre::new(dc.graph, dc.num_ports, dc.num_servers)
+        }
+    };
+
+    let reader = BufReader::new(open(&rt_path)?);
+    let rt: Vec<RoutingTable> = bincode::deserialize_from(reader)?;
+
+    Ok((dc, rt))
+}
+
+/**
+ * The capacity of each server of a topology, read from `{topology}_capacities_{size}.json` in
+ * the topology folder when it exists, e.g. `[[32, 64, 10], [32, 64, 10], [16, 32, 10], ...]`
+ * for a datacentre mixing server generations. Otherwise every server has `server_capacity`.
+ **/
+pub fn load_capacities(
+    folder: &Path,
+    topology: &Topology,
+    size: usize,
+    num_servers: usize,
+    server_capacity: Resources,
+) -> Result<Vec<Resources>, Box<dyn Error>> {
+    let path = folder.join(format!("{}_capacities_{}.json", topology, size));
+    if !path.exists() {
+        return Ok(vec![server_capacity; num_servers]);
+    }
+
+    let file = File::open(&path)
+        .map_err(|err| format!("Could not open capacities {:?}: {}", path, err))?;
+    let capacities: Vec<Resources> = serde_json::from_reader(BufReader::new(file))
+        .map_err(|err| format!("Invalid capacities {:?}: {}", path, err))?;
+
+    if capacities.len() != num_servers {
+        return Err(format!(
+            "{:?} has capacities for {} servers, but {} {} has {}",
+            path,
+            capacities.len(),
+            topology,
+            size,
+            num_servers
+        )
+        .into());
+    }
+    if let Some(server) = capacities
+        .iter()
+        .position(|capacity| !capacity.all_positive())
+    {
+        return Err(format!(
+            "Server {} must have at least 1 of every resource in {:?}",
+            server, path
+        )
+        .into());
+    }
+
+    Ok(capacities)
+}
+
+fn print_population_objectives<X>(
+    folder: &PathBuf,
+    file_name: String,
+    pop: &Vec<Solution<X>>,
+) -> std::io::Result<()> {
+    let mut file = get_file(&folder, &file_name)?;
+
+    for ind in pop {
+        let objectives = &ind.objectives;
+
+        if objectives.is_feasible() {
+            let objectives = objectives.unwrap();
+
+            for (i, objective) in objectives.iter().enumerate() {
+                write!(file, "{}", objective)?;
+
+                if i < objectives.len() - 1 {
+                    write!(file, ",")?;
+                }
+            }
+        } else {
+            write!(file, "Infeasible")?;
+        }
+
+        writeln!(file)?;
+    }
+
+    Ok(())
+}
+
+pub fn get_file(folder: &PathBuf, file: &str) -> std::io::Result<BufWriter<File>> {
+    fs::create_dir_all(folder)?;
+    let path = folder.join(file);
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+
+    Ok(BufWriter::new(file))
+}
@@ -0,0 +1,977 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use clap::ArgMatches;
+use config::{Config, ConfigError};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use serde::Serialize;
+
+use super::{export::ExportFormat, workload::Workload};
+use crate::{
+    algorithms::{moead::Scalarisation, weights::WeightDesign, Algorithm},
+    models::{
+        datacentre::{Datacentre, Link, Tier, Topology},
+        power::{Curve, PowerModel, SleepPolicy},
+        queueing_model::DEFAULT_MAX_ITERATIONS,
+        queues::Discipline,
+        resources::Resources,
+    },
+    operators::{
+        evaluation::{LinkOverload, Objective, DEFAULT_OBJECTIVES},
+        solution::Aggregation,
+    },
+    utilities::rng,
+};
+
+/**
+ * Every setting of an experiment. Values are taken from, in increasing order of priority,
+ * the defaults below, the config file or manifest, `APP_` environment variables and the
+ * command line. The lists are the factors of the study, every combination is run.
+ * Serialises with the same keys it is read from, so a dump can be used as a manifest.
+ **/
+#[derive(Serialize, Clone, Debug)]
+pub struct Parameters {
+    pub results_folder: PathBuf,
+    pub topology_folder: PathBuf,
+    pub topologies: Vec<Topology>,
+    pub scales: Vec<usize>,
+    pub algorithms: Vec<Algorithm>,
+    pub max_evaluations: usize,
+
+    // Seeding, each run has its own seed which is drawn from the master seed if not given
+    pub seed: Option<u64>,
+    pub seeds: Vec<u64>,
+
+    // Problem, a stored instance is used for every run instead of generating one per run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<PathBuf>,
+    pub num_instances: usize,
+    pub utilisations: Vec<f64>,
+    pub server_capacity: Resources,
+    pub workload: Workload,
+
+    // Queueing model
+    pub switch_service_rate: f64,
+    pub switch_queue_length: usize,
+    pub accuracy: f64,
+    pub converged_iterations: usize,
+    pub max_iterations: usize,
+    pub relaxation: f64,
+    pub switch_queue: Discipline,
+    pub vnf_queue: Discipline,
+    pub active_cost: f64,
+    pub idle_cost: f64,
+
+    // Power curves of the servers and each tier of switches, linear from idle_cost to
+    // active_cost if not given, and whether unused components sleep
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_power: Option<Curve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_power: Option<Curve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggregation_power: Option<Curve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core_power: Option<Curve>,
+    pub sleep: SleepPolicy,
+
+    // Links, unlimited unless a bandwidth is given, links to the core switches can be given
+    // their own, and how overloaded links count against a placement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_bandwidth: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core_link_bandwidth: Option<f64>,
+    pub link_latency: f64,
+    pub link_overload: LinkOverload,
+
+    // How the constraint violations of an infeasible placement are combined to rank it
+    pub violation_aggregation: Aggregation,
+
+    // Quantities of the queueing model minimised by the algorithms
+    pub objectives: Vec<Objective>,
+
+    // Algorithms
+    pub pop_size: usize,
+    pub ppls_pop_size: usize,
+    pub ppls_neighbours: usize,
+    pub weight_design: WeightDesign,
+    pub scalarisation: Scalarisation,
+    pub moead_neighbours: usize,
+    pub moead_delta: f64,
+    pub moead_max_replacements: usize,
+    pub num_epochs: usize,
+    pub pm: f64,
+    pub pc: f64,
+
+    // Evaluations between checkpoints of a run, 0 disables them
+    pub checkpoint_interval: usize,
+
+    // Evaluations between reports of the population, 0 only reports the final one
+    pub report_interval: usize,
+
+    // Formats the reported solutions are exported in with their placements and routes
+    pub export: Vec<ExportFormat>,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Parameters {
+            results_folder: PathBuf::from("results"),
+            topology_folder: PathBuf::from("topology"),
+            topologies: vec![Topology::FatTree, Topology::DCell, Topology::LeafSpine],
+            scales: vec![16000],
+            algorithms: vec![
+                Algorithm::NSGAII,
+                Algorithm::CNSGAII,
+                Algorithm::PNSGAII,
+                Algorithm::PPLS,
+            ],
+            max_evaluations: 12000,
+            seed: None,
+            seeds: Vec::new(),
+            instance: None,
+            num_instances: 30,
+            utilisations: vec![0.6],
+            server_capacity: Resources::scalar(100),
+            workload: Workload::default(),
+            switch_service_rate: 20.0,
+            switch_queue_length: 20,
+            accuracy: 5.0,
+            converged_iterations: 10,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            relaxation: 0.0,
+            switch_queue: Discipline::MM1K,
+            vnf_queue: Discipline::MM1K,
+            active_cost: 30.0,
+            idle_cost: 10.0,
+            server_power: None,
+            edge_power: None,
+            aggregation_power: None,
+            core_power: None,
+            sleep: SleepPolicy::default(),
+            link_bandwidth: None,
+            core_link_bandwidth: None,
+            link_latency: 0.0,
+            link_overload: LinkOverload::default(),
+            violation_aggregation: Aggregation::default(),
+            objectives: DEFAULT_OBJECTIVES.to_vec(),
+            pop_size: 128,
+            ppls_pop_size: 16,
+            ppls_neighbours: 10,
+            weight_design: WeightDesign::default(),
+            scalarisation: Scalarisation::default(),
+            moead_neighbours: 20,
+            moead_delta: 0.9,
+            moead_max_replacements: 2,
+            num_epochs: 10,
+            pm: 0.4,
+            pc: 0.4,
+            checkpoint_interval: 0,
+            report_interval: 0,
+            export: Vec::new(),
+        }
+    }
+}
+
+impl Parameters {
+    /// Reads the parameters written with a study or run, such as its parameters.json
+    pub fn load(path: &Path) -> Result<Parameters, Box<dyn Error>> {
+        let mut settings = Config::default();
+        settings
+            .merge(config::File::from(path))
+            .map_err(|err| format!("Could not read parameters {:?}: {}", path, err))?;
+
+        Parameters::from_config(&settings)
+    }
+
+    pub fn from_config(settings: &Config) -> Result<Parameters, Box<dyn Error>> {
+        let mut params = Parameters::default();
+
+        set_from_config(settings, "results_folder", &mut params.results_folder)?;
+        set_from_config(settings, "topology_folder", &mut params.topology_folder)?;
+        set_from_config(settings, "max_evaluations", &mut params.max_evaluations)?;
+        set_from_config(settings, "server_capacity", &mut params.server_capacity)?;
+        set_from_config(
+            settings,
+            "switch_service_rate",
+            &mut params.switch_service_rate,
+        )?;
+        set_from_config(
+            settings,
+            "switch_queue_length",
+            &mut params.switch_queue_length,
+        )?;
+        set_from_config(settings, "accuracy", &mut params.accuracy)?;
+        set_from_config(
+            settings,
+            "converged_iterations",
+            &mut params.converged_iterations,
+        )?;
+        set_from_config(settings, "max_iterations", &mut params.max_iterations)?;
+        set_from_config(settings, "relaxation", &mut params.relaxation)?;
+        set_parsed_from_config(settings, "switch_queue", &mut params.switch_queue)?;
+        set_parsed_from_config(settings, "vnf_queue", &mut params.vnf_queue)?;
+        set_from_config(settings, "active_cost", &mut params.active_cost)?;
+        set_from_config(settings, "idle_cost", &mut params.idle_cost)?;
+        set_parsed_from_config(settings, "sleep", &mut params.sleep)?;
+
+        let mut curves = [
+            ("server_power", &mut params.server_power),
+            ("edge_power", &mut params.edge_power),
+            ("aggregation_power", &mut params.aggregation_power),
+            ("core_power", &mut params.core_power),
+        ];
+        for (key, value) in curves.iter_mut() {
+            let mut curve = Curve::linear(0.0, 0.0);
+            if set_parsed_from_config(settings, *key, &mut curve)? {
+                **value = Some(curve);
+            }
+        }
+
+        let mut bandwidths = [
+            ("link_bandwidth", &mut params.link_bandwidth),
+            ("core_link_bandwidth", &mut params.core_link_bandwidth),
+        ];
+        for (key, value) in bandwidths.iter_mut() {
+            let mut bandwidth = 0.0;
+            if set_from_config(settings, *key, &mut bandwidth)? {
+                **value = Some(bandwidth);
+            }
+        }
+        set_from_config(settings, "link_latency", &mut params.link_latency)?;
+        set_parsed_from_config(settings, "link_overload", &mut params.link_overload)?;
+        set_parsed_from_config(
+            settings,
+            "violation_aggregation",
+            &mut params.violation_aggregation,
+        )?;
+        set_from_config(settings, "pop_size", &mut params.pop_size)?;
+        set_from_config(settings, "ppls_pop_size", &mut params.ppls_pop_size)?;
+        set_from_config(settings, "ppls_neighbours", &mut params.ppls_neighbours)?;
+        set_parsed_from_config(settings, "weight_design", &mut params.weight_design)?;
+        set_parsed_from_config(settings, "scalarisation", &mut params.scalarisation)?;
+        set_from_config(settings, "moead_neighbours", &mut params.moead_neighbours)?;
+        set_from_config(settings, "moead_delta", &mut params.moead_delta)?;
+        set_from_config(
+            settings,
+            "moead_max_replacements",
+            &mut params.moead_max_replacements,
+        )?;
+        set_from_config(settings, "num_epochs", &mut params.num_epochs)?;
+        set_from_config(settings, "pm", &mut params.pm)?;
+        set_from_config(settings, "pc", &mut params.pc)?;
+        set_from_config(
+            settings,
+            "checkpoint_interval",
+            &mut params.checkpoint_interval,
+        )?;
+        set_from_config(settings, "report_interval", &mut params.report_interval)?;
+
+        // Either the name of a profile or the profile itself
+        match settings.get::<String>("workload") {
+            Ok(name) => params.workload = Workload::from_name(&name)?,
+            Err(ConfigError::NotFound(_)) => {}
+            Err(_) => {
+                set_from_config(settings, "workload", &mut params.workload)?;
+            }
+        }
+
+        let mut instance = PathBuf::new();
+        if set_from_config(settings, "instance", &mut instance)? {
+            params.instance = Some(instance);
+        }
+
+        let mut seed = 0;
+        if set_from_config(settings, "seed", &mut seed)? {
+            params.seed = Some(seed);
+        }
+
+        // Giving the seeds of the runs also gives their number
+        set_list_from_config(settings, "seeds", &mut params.seeds)?;
+        if !set_from_config(settings, "num_instances", &mut params.num_instances)?
+            && !params.seeds.is_empty()
+        {
+            params.num_instances = params.seeds.len();
+        }
+
+        set_list_from_config(settings, "scales", &mut params.scales)?;
+        if !set_list_from_config(settings, "utilisations", &mut params.utilisations)? {
+            let mut utilisation = 0.0;
+            if set_from_config(settings, "utilisation", &mut utilisation)? {
+                params.utilisations = vec![utilisation];
+            }
+        }
+        set_list_from_config(settings, "algorithms", &mut params.algorithms)?;
+        set_list_from_config(settings, "export", &mut params.export)?;
+        set_list_from_config(settings, "objectives", &mut params.objectives)?;
+
+        // Older config files enable each topology with its own flag
+        if !set_list_from_config(settings, "topologies", &mut params.topologies)? {
+            let flags = [
+                ("fat_tree", Topology::FatTree),
+                ("dcell", Topology::DCell),
+                ("leaf_spine", Topology::LeafSpine),
+            ];
+
+            let mut topologies = Vec::new();
+            let mut any_flag = false;
+
+            for &(key, topology) in &flags {
+                let mut enabled = false;
+                any_flag |= set_from_config(settings, key, &mut enabled)?;
+
+                if enabled {
+                    topologies.push(topology);
+                }
+            }
+
+            if any_flag {
+                params.topologies = topologies;
+            }
+        }
+
+        Ok(params)
+    }
+
+    pub fn apply_args(&mut self, args: &ArgMatches) -> Result<(), Box<dyn Error>> {
+        set_from_arg(args, "results-folder", &mut self.results_folder)?;
+        set_from_arg(args, "topology-folder", &mut self.topology_folder)?;
+        set_from_arg(args, "max-evaluations", &mut self.max_evaluations)?;
+        if let Some(seed) = args.value_of("seed") {
+            self.seed = Some(
+                seed.trim()
+                    .parse()
+                    .map_err(|err| format!("Invalid value '{}' for '--seed': {}", seed, err))?,
+            );
+        }
+
+        if args.is_present("seeds") {
+            set_list_from_arg(args, "seeds", &mut self.seeds)?;
+            self.num_instances = self.seeds.len();
+        }
+        if let Some(instance) = args.value_of("instance") {
+            self.instance = Some(PathBuf::from(instance));
+        }
+        set_from_arg(args, "instances", &mut self.num_instances)?;
+        set_from_arg(args, "server-capacity", &mut self.server_capacity)?;
+        if let Some(workload) = args.value_of("workload") {
+            self.workload = Workload::from_name(workload)?;
+        }
+        set_from_arg(args, "switch-service-rate", &mut self.switch_service_rate)?;
+        set_from_arg(args, "switch-queue-length", &mut self.switch_queue_length)?;
+        set_from_arg(args, "accuracy", &mut self.accuracy)?;
+        set_from_arg(args, "converged-iterations", &mut self.converged_iterations)?;
+        set_from_arg(args, "max-iterations", &mut self.max_iterations)?;
+        set_from_arg(args, "relaxation", &mut self.relaxation)?;
+        set_from_arg(args, "switch-queue", &mut self.switch_queue)?;
+        set_from_arg(args, "vnf-queue", &mut self.vnf_queue)?;
+        set_from_arg(args, "active-cost", &mut self.active_cost)?;
+        set_from_arg(args, "idle-cost", &mut self.idle_cost)?;
+        set_from_arg(args, "sleep", &mut self.sleep)?;
+
+        let mut curves = [
+            ("server-power", &mut self.server_power),
+            ("edge-power", &mut self.edge_power),
+            ("aggregation-power", &mut self.aggregation_power),
+            ("core-power", &mut self.core_power),
+        ];
+        for (name, value) in curves.iter_mut() {
+            if args.value_of(*name).is_some() {
+                let mut curve = Curve::linear(0.0, 0.0);
+                set_from_arg(args, *name, &mut curve)?;
+                **value = Some(curve);
+            }
+        }
+
+        let mut bandwidths = [
+            ("link-bandwidth", &mut self.link_bandwidth),
+            ("core-link-bandwidth", &mut self.core_link_bandwidth),
+        ];
+        for (name, value) in bandwidths.iter_mut() {
+            if args.value_of(*name).is_some() {
+                let mut bandwidth = 0.0;
+                set_from_arg(args, *name, &mut bandwidth)?;
+                **value = Some(bandwidth);
+            }
+        }
+        set_from_arg(args, "link-latency", &mut self.link_latency)?;
+        set_from_arg(args, "link-overload", &mut self.link_overload)?;
+        set_from_arg(
+            args,
+            "violation-aggregation",
+            &mut self.violation_aggregation,
+        )?;
+        set_from_arg(args, "pop-size", &mut self.pop_size)?;
+        set_from_arg(args, "ppls-pop-size", &mut self.ppls_pop_size)?;
+        set_from_arg(args, "ppls-neighbours", &mut self.ppls_neighbours)?;
+        set_from_arg(args, "weight-design", &mut self.weight_design)?;
+        set_from_arg(args, "scalarisation", &mut self.scalarisation)?;
+        set_from_arg(args, "moead-neighbours", &mut self.moead_neighbours)?;
+        set_from_arg(args, "moead-delta", &mut self.moead_delta)?;
+        set_from_arg(
+            args,
+            "moead-max-replacements",
+            &mut self.moead_max_replacements,
+        )?;
+        set_from_arg(args, "epochs", &mut self.num_epochs)?;
+        set_from_arg(args, "pm", &mut self.pm)?;
+        set_from_arg(args, "pc", &mut self.pc)?;
+        set_from_arg(args, "checkpoint-interval", &mut self.checkpoint_interval)?;
+        set_from_arg(args, "report-interval", &mut self.report_interval)?;
+
+        set_list_from_arg(args, "topologies", &mut self.topologies)?;
+        set_list_from_arg(args, "scales", &mut self.scales)?;
+        set_list_from_arg(args, "utilisations", &mut self.utilisations)?;
+        set_list_from_arg(args, "algorithms", &mut self.algorithms)?;
+        set_list_from_arg(args, "export", &mut self.export)?;
+        set_list_from_arg(args, "objectives", &mut self.objectives)?;
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = self.instance_errors();
+
+        if self.topologies.is_empty() {
+            errors.push("at least one topology is required".to_string());
+        }
+        if self.scales.is_empty() || self.scales.contains(&0) {
+            errors.push("scales must be a non-empty list of positive server counts".to_string());
+        }
+        if self.algorithms.is_empty() {
+            errors.push("at least one algorithm is required".to_string());
+        }
+        if self.num_instances == 0 {
+            errors.push("num_instances must be at least 1".to_string());
+        }
+        if !self.seeds.is_empty() && self.seeds.len() != self.num_instances {
+            errors.push(format!(
+                "expected a seed for each of the {} instances, got {} seeds",
+                self.num_instances,
+                self.seeds.len()
+            ));
+        }
+        if self.instance.is_some() && (self.topologies.len() > 1 || self.scales.len() > 1) {
+            errors.push("a stored instance fits a single topology and scale".to_string());
+        }
+        if self.instance.is_some() && self.utilisations.len() > 1 {
+            errors.push("a stored instance has a fixed utilisation, give only one".to_string());
+        }
+        if !(self.switch_service_rate > 0.0) {
+            errors.push("switch_service_rate must be positive".to_string());
+        }
+        if self.switch_queue_length == 0 {
+            errors.push("switch_queue_length must be at least 1".to_string());
+        }
+        if !(self.accuracy > 0.0) {
+            errors.push("accuracy must be positive".to_string());
+        }
+        if self.converged_iterations == 0 {
+            errors.push("converged_iterations must be at least 1".to_string());
+        }
+        if self.max_iterations != 0 && self.max_iterations < self.converged_iterations {
+            errors.push("max_iterations must be 0 or at least converged_iterations".to_string());
+        }
+        if !(self.relaxation >= 0.0 && self.relaxation <= 1.0) {
+            errors.push("relaxation must be in [0, 1]".to_string());
+        }
+        if self.active_cost < 0.0 || self.idle_cost < 0.0 {
+            errors.push("active_cost and idle_cost must not be negative".to_string());
+        }
+        if self
+            .link_bandwidth
+            .iter()
+            .chain(&self.core_link_bandwidth)
+            .any(|bandwidth| !(*bandwidth > 0.0))
+        {
+            errors.push("link_bandwidth and core_link_bandwidth must be positive".to_string());
+        }
+        if !(self.link_latency >= 0.0) {
+            errors.push("link_latency must not be negative".to_string());
+        }
+
+        let objectives = self.objectives();
+        if objectives.is_empty() {
+            errors.push("at least one objective is required".to_string());
+        }
+        for (i, objective) in objectives.iter().enumerate() {
+            if objectives[..i].contains(objective) {
+                errors.push(format!("objective {} is given more than once", objective));
+            }
+        }
+        // The decomposition algorithms divide the objective space between their weights
+        if objectives.len() < 2
+            && (self.algorithms.contains(&Algorithm::PPLS)
+                || self.algorithms.contains(&Algorithm::SPPLS)
+                || self.algorithms.contains(&Algorithm::MOEAD))
+        {
+            errors.push("PPLS, SPPLS and MOEA/D need at least two objectives".to_string());
+        }
+        if self.pop_size < 2 || self.pop_size % 2 != 0 {
+            errors.push(format!(
+                "pop_size must be an even number of at least 2, got {}",
+                self.pop_size
+            ));
+        }
+        if self.ppls_pop_size == 0 || self.ppls_neighbours == 0 {
+            errors.push("ppls_pop_size and ppls_neighbours must be at least 1".to_string());
+        }
+        if self.moead_neighbours < 2 || self.moead_max_replacements == 0 {
+            errors.push(
+                "moead_neighbours must be at least 2 and moead_max_replacements at least 1"
+                    .to_string(),
+            );
+        }
+        if !(0.0..=1.0).contains(&self.moead_delta) {
+            errors.push("moead_delta must be a probability in [0, 1]".to_string());
+        }
+        if self.num_epochs == 0 {
+            errors.push("num_epochs must be at least 1".to_string());
+        }
+        if self.algorithms.contains(&Algorithm::PNSGAII) && self.pop_size < 2 * num_cpus::get() {
+            errors.push(format!(
+                "P-NSGA-II needs a pop_size of at least twice the number of cores ({})",
+                2 * num_cpus::get()
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.pm) || !(0.0..=1.0).contains(&self.pc) {
+            errors.push("pm and pc must be probabilities in [0, 1]".to_string());
+        }
+
+        let min_evaluations = self.pop_size.max(self.ppls_pop_size);
+        if self.max_evaluations <= min_evaluations {
+            errors.push(format!(
+                "max_evaluations must exceed the population size ({})",
+                min_evaluations
+            ));
+        }
+
+        to_result(errors)
+    }
+
+    /// Checks only the settings an instance is generated from, for the generate command
+    pub fn validate_instance(&self) -> Result<(), String> {
+        to_result(self.instance_errors())
+    }
+
+    fn instance_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self
+            .seeds
+            .iter()
+            .chain(&self.seed)
+            .any(|&seed| seed > std::i64::MAX as u64)
+        {
+            errors.push(format!("seeds must be at most {}", std::i64::MAX));
+        }
+        if self.utilisations.is_empty() {
+            errors.push("at least one utilisation is required".to_string());
+        }
+        for utilisation in &self.utilisations {
+            if !(*utilisation > 0.0 && *utilisation <= 1.0) {
+                errors.push(format!(
+                    "utilisation must be in (0, 1], got {}",
+                    utilisation
+                ));
+            }
+        }
+        if let Err(err) = self.workload.validate() {
+            errors.push(err);
+        }
+        if !self.server_capacity.all_positive() {
+            errors.push("server_capacity must be at least 1 of every resource".to_string());
+        }
+
+        errors
+    }
+
+    /// Power drawn by the components of `dc` with the curves and sleep policy of the study
+    pub fn power_model(&self, dc: &Datacentre) -> PowerModel {
+        let linear = Curve::linear(self.idle_cost, self.active_cost);
+        let curve = |curve: &Option<Curve>| curve.unwrap_or(linear);
+
+        PowerModel::new(
+            dc,
+            &curve(&self.server_power),
+            &curve(&self.edge_power),
+            &curve(&self.aggregation_power),
+            &curve(&self.core_power),
+            self.sleep,
+        )
+    }
+
+    /// The objectives of the study, with the largest link utilisation if links are one
+    pub fn objectives(&self) -> Vec<Objective> {
+        let mut objectives = self.objectives.clone();
+
+        if self.link_overload == LinkOverload::Objective
+            && !objectives.contains(&Objective::MaxLinkUtilisation)
+        {
+            objectives.push(Objective::MaxLinkUtilisation);
+        }
+
+        objectives
+    }
+
+    /**
+     * Sets the bandwidth and latency of the links of `dc`. The links stored with the
+     * topology are kept unless one of the link settings is given.
+     **/
+    pub fn set_links(&self, dc: &mut Datacentre) {
+        if self.link_bandwidth.is_none()
+            && self.core_link_bandwidth.is_none()
+            && self.link_latency == 0.0
+        {
+            return;
+        }
+
+        let link = Link {
+            bandwidth: self.link_bandwidth.unwrap_or(std::f64::INFINITY),
+            latency: self.link_latency,
+        };
+        let core_link = Link {
+            bandwidth: self.core_link_bandwidth.unwrap_or(link.bandwidth),
+            ..link
+        };
+
+        dc.set_links(|a, b| {
+            if a == Tier::Core || b == Tier::Core {
+                core_link
+            } else {
+                link
+            }
+        });
+    }
+
+    /**
+     * Fills in the master seed and the seed of every run, so that the resolved parameters
+     * repeat the study exactly. Without a master seed one is taken from entropy.
+     **/
+    pub fn resolve_seeds(&mut self) {
+        let seed = *self.seed.get_or_insert_with(rng::random_seed);
+
+        if self.seeds.is_empty() {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            self.seeds = (0..self.num_instances)
+                .map(|_| rng::to_seed(rng.gen()))
+                .collect();
+        }
+    }
+}
+
+// Combines the problems found by validation into one error
+fn to_result(errors: Vec<String>) -> Result<(), String> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid parameters:\n  - {}",
+            errors.join("\n  - ")
+        ))
+    }
+}
+
+// Overwrites `value` if the key is in the config file, returns whether it was present
+fn set_from_config<T>(settings: &Config, key: &str, value: &mut T) -> Result<bool, Box<dyn Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    match settings.get::<T>(key) {
+        Ok(new_value) => {
+            *value = new_value;
+            Ok(true)
+        }
+        Err(ConfigError::NotFound(_)) => Ok(false),
+        Err(err) => Err(format!("Invalid value for '{}' in config: {}", key, err).into()),
+    }
+}
+
+// Like set_from_config for values written as a string, such as `mg1k:0.5`
+fn set_parsed_from_config<T>(
+    settings: &Config,
+    key: &str,
+    value: &mut T,
+) -> Result<bool, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let mut text = String::new();
+    if !set_from_config(settings, key, &mut text)? {
+        return Ok(false);
+    }
+
+    *value = text
+        .trim()
+        .parse()
+        .map_err(|err| format!("Invalid value for '{}' in config: {}", key, err))?;
+    Ok(true)
+}
+
+// Lists can be given as an array or as a comma separated string
+fn set_list_from_config<T>(
+    settings: &Config,
+    key: &str,
+    value: &mut Vec<T>,
+) -> Result<bool, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let items = match settings.get::<Vec<String>>(key) {
+        Ok(items) => items,
+        Err(ConfigError::NotFound(_)) => return Ok(false),
+        Err(_) => match settings.get::<String>(key) {
+            Ok(items) => items.split(',').map(|item| item.to_string()).collect(),
+            Err(err) => {
+                return Err(format!("Invalid value for '{}' in config: {}", key, err).into())
+            }
+        },
+    };
+
+    *value = parse_list(&items).map_err(|err| format!("Invalid value for '{}': {}", key, err))?;
+    Ok(true)
+}
+
+fn set_from_arg<T>(args: &ArgMatches, name: &str, value: &mut T) -> Result<(), Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Some(arg) = args.value_of(name) {
+        *value = arg
+            .trim()
+            .parse()
+            .map_err(|err| format!("Invalid value '{}' for '--{}': {}", arg, name, err))?;
+    }
+
+    Ok(())
+}
+
+fn set_list_from_arg<T>(
+    args: &ArgMatches,
+    name: &str,
+    value: &mut Vec<T>,
+) -> Result<(), Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Some(arg) = args.value_of(name) {
+        let items: Vec<String> = arg.split(',').map(|item| item.to_string()).collect();
+        *value =
+            parse_list(&items).map_err(|err| format!("Invalid value for '--{}': {}", name, err))?;
+    }
+
+    Ok(())
+}
+
+fn parse_list<T>(items: &[String]) -> Result<Vec<T>, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    items
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.parse().map_err(|err| format!("'{}' ({})", item, err)))
+        .collect()
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::datacentre::FatTree;
+
+    #[test]
+    fn test_from_config() {
+        let mut settings = Config::default();
+        settings.set("max_evaluations", "5000").unwrap();
+        settings.set("dcell", "true").unwrap();
+        settings.set("fat_tree", "false").unwrap();
+        settings.set("algorithms", "NSGAII, PPLS").unwrap();
+        settings.set("scales", vec![500, 1000]).unwrap();
+        settings.set("utilisation", 0.8).unwrap();
+        settings.set("vnf_queue", "mg1k:0.5").unwrap();
+        settings.set("server_power", "cubic:10:30").unwrap();
+        settings.set("sleep", "never").unwrap();
+
+        let params = Parameters::from_config(&settings).unwrap();
+
+        assert_eq!(params.max_evaluations, 5000);
+        assert_eq!(params.topologies, vec![Topology::DCell]);
+        assert_eq!(params.algorithms, vec![Algorithm::NSGAII, Algorithm::PPLS]);
+        assert_eq!(params.scales, vec![500, 1000]);
+        assert_eq!(params.utilisations, vec![0.8]);
+        assert_eq!(params.pop_size, 128);
+        assert_eq!(params.switch_queue, Discipline::MM1K);
+        assert_eq!(params.vnf_queue, Discipline::MG1K(0.5));
+        assert_eq!(params.server_power, Some("cubic:10:30".parse().unwrap()));
+        assert_eq!(params.edge_power, None);
+        assert_eq!(params.sleep, SleepPolicy::Never);
+        assert_eq!(params.link_bandwidth, None);
+        assert_eq!(params.link_overload, LinkOverload::Ignore);
+    }
+
+    #[test]
+    fn test_from_config_invalid() {
+        let mut settings = Config::default();
+        settings.set("algorithms", "NSGAII,MOGA").unwrap();
+        assert!(Parameters::from_config(&settings).is_err());
+
+        let mut settings = Config::default();
+        settings.set("utilisations", "0.4,high").unwrap();
+        assert!(Parameters::from_config(&settings).is_err());
+
+        let mut settings = Config::default();
+        settings.set("switch_queue", "mmck:0").unwrap();
+        assert!(Parameters::from_config(&settings).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut params = Parameters::default();
+        params.algorithms = vec![Algorithm::NSGAII];
+        assert!(params.validate().is_ok());
+
+        params.utilisations = vec![0.5, 1.5];
+        params.pop_size = 15;
+        let err = params.validate().unwrap_err();
+
+        assert!(err.contains("utilisation"));
+        assert!(err.contains("pop_size"));
+
+        // Generating an instance does not depend on the algorithms
+        let err = params.validate_instance().unwrap_err();
+        assert!(err.contains("utilisation"));
+        assert!(!err.contains("pop_size"));
+
+        params.utilisations = vec![0.5];
+        params.algorithms = Vec::new();
+        assert!(params.validate_instance().is_ok());
+
+        let mut params = Parameters::default();
+        params.instance = Some(PathBuf::from("instance.json"));
+        assert!(params.validate().unwrap_err().contains("stored instance"));
+    }
+
+    #[test]
+    fn test_resolve_seeds() {
+        let mut settings = Config::default();
+        settings.set("seeds", vec![3, 5, 7]).unwrap();
+
+        let mut params = Parameters::from_config(&settings).unwrap();
+        params.resolve_seeds();
+
+        assert_eq!(params.num_instances, 3);
+        assert_eq!(params.seeds, vec![3, 5, 7]);
+        assert!(params.seed.is_some());
+
+        let mut params = Parameters::default();
+        params.seed = Some(1);
+        params.num_instances = 4;
+        params.resolve_seeds();
+
+        let mut other = params.clone();
+        other.seeds.clear();
+        other.resolve_seeds();
+
+        assert_eq!(params.seeds.len(), 4);
+        assert_eq!(params.seeds, other.seeds);
+    }
+
+    #[test]
+    fn test_set_links() {
+        let mut settings = Config::default();
+        settings.set("link_bandwidth", 100.0).unwrap();
+        settings.set("core_link_bandwidth", 40.0).unwrap();
+        settings.set("link_overload", "constraint").unwrap();
+        settings.set("violation_aggregation", "max:1:1:10").unwrap();
+
+        let params = Parameters::from_config(&settings).unwrap();
+        assert_eq!(params.link_overload, LinkOverload::Constraint);
+        assert_eq!(
+            params.violation_aggregation,
+            Aggregation::Max([1.0, 1.0, 10.0])
+        );
+        assert_eq!(params.objectives(), DEFAULT_OBJECTIVES.to_vec());
+
+        // Edge 16 is linked to aggregation 24, which is linked to core 32
+        let mut dc = FatTree::new(4);
+        params.set_links(&mut dc);
+        assert_eq!(dc.link(0, 16).unwrap().bandwidth, 100.0);
+        assert_eq!(dc.link(16, 24).unwrap().bandwidth, 100.0);
+        assert_eq!(dc.link(32, 24).unwrap().bandwidth, 40.0);
+
+        let mut params = Parameters::default();
+        params.link_bandwidth = Some(0.0);
+        assert!(params.validate().unwrap_err().contains("link_bandwidth"));
+    }
+
+    #[test]
+    fn test_objectives() {
+        let mut settings = Config::default();
+        settings
+            .set("objectives", "max_latency, latency_p95,energy")
+            .unwrap();
+        settings.set("link_overload", "objective").unwrap();
+        settings.set("weight_design", "riesz").unwrap();
+
+        let mut params = Parameters::from_config(&settings).unwrap();
+        assert_eq!(params.weight_design, WeightDesign::RieszEnergy);
+        assert_eq!(
+            params.objectives(),
+            vec![
+                Objective::MaxLatency,
+                Objective::LatencyPercentile(95.0),
+                Objective::Energy,
+                Objective::MaxLinkUtilisation
+            ]
+        );
+
+        params.algorithms = vec![Algorithm::PPLS];
+        assert!(params.validate().is_ok());
+
+        params.objectives = vec![Objective::Energy];
+        params.link_overload = LinkOverload::Ignore;
+        assert!(params.validate().unwrap_err().contains("two objectives"));
+
+        params.algorithms = vec![Algorithm::NSGAII];
+        assert!(params.validate().is_ok());
+
+        params.objectives.push(Objective::Energy);
+        assert!(params.validate().unwrap_err().contains("more than once"));
+
+        let mut settings = Config::default();
+        settings.set("objectives", "latency_p0").unwrap();
+        assert!(Parameters::from_config(&settings).is_err());
+    }
+
+    #[test]
+    fn test_moead_settings() {
+        let mut settings = Config::default();
+        settings.set("algorithms", "MOEA/D").unwrap();
+        settings.set("scalarisation", "pbi:2").unwrap();
+        settings.set("moead_neighbours", 15).unwrap();
+
+        let mut params = Parameters::from_config(&settings).unwrap();
+        assert_eq!(params.algorithms, vec![Algorithm::MOEAD]);
+        assert_eq!(params.scalarisation, Scalarisation::PBI(2.0));
+        assert_eq!(params.moead_neighbours, 15);
+        assert!(params.validate().is_ok());
+
+        params.moead_delta = 1.5;
+        assert!(params.validate().unwrap_err().contains("moead_delta"));
+
+        params.moead_delta = 0.9;
+        params.objectives = vec![Objective::Energy];
+        assert!(params.validate().unwrap_err().contains("two objectives"));
+    }
+}
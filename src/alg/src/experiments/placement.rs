@@ -0,0 +1,641 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fs::File,
+    io::{prelude::*, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::{
+    export::SolutionExport,
+    instance::{Instance, INSTANCE_FILE},
+    load_capacities, load_topology,
+    parameters::Parameters,
+};
+use crate::{
+    models::{
+        datacentre::{Datacentre, Topology},
+        heuristic_model::HeuristicModel,
+        queueing_model::{ComponentReport, LinkReport, QueueingModel, QueueingReport},
+        routing::RoutingTable,
+        service::{Service, ServiceID},
+        utilisation_model::UtilisationModel,
+    },
+    operators::{
+        distance_matrix,
+        evaluation::sla_violations,
+        mapping::{Mapping, RouteNode, ServiceToRouteMapping},
+        placement_strategies::FirstFit,
+        solution::Solution,
+    },
+    utilities::{metrics::mean, rng},
+};
+
+/// Number of switches and links listed when a placement is evaluated
+pub const BUSIEST_SWITCHES: usize = 5;
+
+/// The ids of the services whose first VNF is placed from each server
+pub type Placement = Vec<Vec<ServiceID>>;
+
+/**
+ * A placement evaluated by each of the models. Unlike the evaluations used by the
+ * algorithms, services that could not be placed do not make the placement infeasible, the
+ * models are run on the instances that were placed and the others are reported.
+ **/
+#[derive(Debug, Clone)]
+pub struct PlacementEvaluation {
+    /// Number of instances of each service that were placed
+    pub instances: Vec<usize>,
+
+    // Queueing model, with the state of each component
+    pub queueing: QueueingReport,
+
+    // Utilisation model
+    pub utilisations: Vec<f64>,
+    pub utilisation_energy: f64,
+
+    // Heuristic model, the proportion of components used and mean route length
+    pub components_used: f64,
+    pub route_length: f64,
+
+    /// How far each service misses its SLA in the queueing model, 0 for those that meet it
+    pub sla_violations: Vec<f64>,
+}
+
+impl PlacementEvaluation {
+    pub fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "{:>8} {:>10} {:>14} {:>14} {:>14}",
+            "service", "instances", "latency", "packet loss", "utilisation"
+        )?;
+
+        for i in 0..self.instances.len() {
+            if self.instances[i] == 0 {
+                writeln!(writer, "{:>8} {:>10} {:>14}", i, 0, "unplaced")?;
+                continue;
+            }
+
+            writeln!(
+                writer,
+                "{:>8} {:>10} {:>14.6} {:>14.6} {:>14.6}",
+                i,
+                self.instances[i],
+                self.queueing.latencies[i],
+                self.queueing.packet_losses[i],
+                self.utilisations[i]
+            )?;
+        }
+
+        let placed = |values: &Vec<f64>| -> Vec<f64> {
+            (0..values.len())
+                .filter(|&i| self.instances[i] > 0)
+                .map(|i| values[i])
+                .collect()
+        };
+
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "Queueing model:    mean latency {:.6}, mean packet loss {:.6}, energy {:.6}",
+            mean(&placed(&self.queueing.latencies)),
+            mean(&placed(&self.queueing.packet_losses)),
+            self.queueing.energy
+        )?;
+        match &self.queueing.error {
+            Some(error) => writeln!(writer, "                   {}", error)?,
+            None => writeln!(
+                writer,
+                "                   converged after {} iterations, max diff {:.6}",
+                self.queueing.iterations, self.queueing.max_diff
+            )?,
+        }
+        writeln!(
+            writer,
+            "Utilisation model: mean utilisation {:.6}, energy {:.6}",
+            mean(&placed(&self.utilisations)),
+            self.utilisation_energy
+        )?;
+        writeln!(
+            writer,
+            "Heuristic model:   components used {:.6}, mean route length {:.6}",
+            self.components_used, self.route_length
+        )?;
+
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{:>8} {:>14} {:>14} {:>14} {:>14}",
+            "switch", "utilisation", "packet loss", "occupancy", "arrival rate"
+        )?;
+        for switch in self.busiest_switches(BUSIEST_SWITCHES) {
+            writeln!(
+                writer,
+                "{:>8} {:>14.6} {:>14.6} {:>14.6} {:>14.6}",
+                switch.id,
+                switch.utilisation,
+                switch.packet_loss,
+                switch.queue_occupancy,
+                switch.arrival_rate
+            )?;
+        }
+
+        // Only links with a bandwidth have a utilisation
+        let busiest_links = self.busiest_links(BUSIEST_SWITCHES);
+        if !busiest_links.is_empty() {
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "{:>8} {:>8} {:>14} {:>14}",
+                "from", "to", "utilisation", "load"
+            )?;
+            for link in busiest_links {
+                writeln!(
+                    writer,
+                    "{:>8} {:>8} {:>14.6} {:>14.6}",
+                    link.from, link.to, link.utilisation, link.load
+                )?;
+            }
+        }
+
+        let num_unplaced = self.instances.iter().filter(|&&count| count == 0).count();
+        if num_unplaced > 0 {
+            writeln!(
+                writer,
+                "{} services could not be placed, the placement is infeasible",
+                num_unplaced
+            )?;
+        }
+
+        let num_missed = self
+            .sla_violations
+            .iter()
+            .filter(|&&violation| violation > 0.0)
+            .count();
+        if num_missed > 0 {
+            writeln!(
+                writer,
+                "{} services miss their SLA, the placement is infeasible",
+                num_missed
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// The `n` most utilised switches, the likely bottlenecks of the placement
+    pub fn busiest_switches(&self, n: usize) -> Vec<&ComponentReport> {
+        let mut switches: Vec<&ComponentReport> = self
+            .queueing
+            .components
+            .iter()
+            .filter(|component| !component.is_server && component.arrival_rate > 0.0)
+            .collect();
+
+        switches.sort_by(|a, b| {
+            b.utilisation
+                .partial_cmp(&a.utilisation)
+                .unwrap_or(Ordering::Equal)
+        });
+        switches.truncate(n);
+
+        switches
+    }
+
+    /// The `n` most utilised links, those over 1 carry more than their bandwidth
+    pub fn busiest_links(&self, n: usize) -> Vec<&LinkReport> {
+        let mut links: Vec<&LinkReport> = self
+            .queueing
+            .links
+            .iter()
+            .filter(|link| link.utilisation > 0.0)
+            .collect();
+
+        links.sort_by(|a, b| {
+            b.utilisation
+                .partial_cmp(&a.utilisation)
+                .unwrap_or(Ordering::Equal)
+        });
+        links.truncate(n);
+
+        links
+    }
+
+    /// Writes the state of every component and VNF found by the queueing model to a CSV file
+    pub fn write_diagnostics(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        writeln!(
+            file,
+            "kind,id,service,stage,arrival_rate,utilisation,packet_loss,queue_occupancy,energy"
+        )?;
+
+        for component in &self.queueing.components {
+            writeln!(
+                file,
+                "{},{},,,{},{},{},{},{}",
+                if component.is_server {
+                    "server"
+                } else {
+                    "switch"
+                },
+                component.id,
+                component.arrival_rate,
+                component.utilisation,
+                component.packet_loss,
+                component.queue_occupancy,
+                component.energy
+            )?;
+        }
+
+        for vnf in &self.queueing.vnfs {
+            writeln!(
+                file,
+                "vnf,{},{},{},{},{},{},{},",
+                vnf.server,
+                vnf.service,
+                vnf.stage,
+                vnf.arrival_rate,
+                vnf.utilisation,
+                vnf.packet_loss,
+                vnf.queue_occupancy
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Evaluates a stored placement with the queueing, utilisation and heuristic models, to see
+ * how solutions found with a surrogate model hold up under the others. The placement is
+ * decoded with first fit like during the runs, with the seed of the run the ties in the
+ * distance matrix are broken the same way. Without an instance the one saved beside the
+ * placement by the run is used. The state of every component in the queueing model can be
+ * written to `diagnostics` to look for bottlenecks.
+ **/
+pub fn evaluate(
+    params: &Parameters,
+    topology: Topology,
+    scale: usize,
+    placement_path: &Path,
+    instance_path: Option<&Path>,
+    solution: usize,
+    diagnostics: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let (dc, rt, instance, placement) = load_evaluation(
+        params,
+        topology,
+        scale,
+        placement_path,
+        instance_path,
+        solution,
+    )?;
+
+    let evaluation = evaluate_placement(params, &dc, &rt, &instance, &placement)?;
+
+    println!(
+        "{} services on {} {} from {:?}",
+        instance.services.len(),
+        topology,
+        dc.num_servers,
+        placement_path
+    );
+
+    let stdout = std::io::stdout();
+    evaluation.write(&mut stdout.lock())?;
+
+    if let Some(path) = diagnostics {
+        evaluation.write_diagnostics(path)?;
+        println!("Wrote the state of each component to {:?}", path);
+    }
+
+    Ok(())
+}
+
+/**
+ * Loads the datacentre, instance and placement to evaluate as described by `evaluate`, and
+ * seeds the generator with the master seed if one is given
+ **/
+pub fn load_evaluation(
+    params: &Parameters,
+    topology: Topology,
+    scale: usize,
+    placement_path: &Path,
+    instance_path: Option<&Path>,
+    solution: usize,
+) -> Result<(Datacentre, Vec<RoutingTable>, Instance, Placement), Box<dyn Error>> {
+    let (mut dc, rt) = load_topology(&params.topology_folder, &topology, scale)?;
+    params.set_links(&mut dc);
+
+    let instance_path = match instance_path {
+        Some(path) => path.to_path_buf(),
+        None => placement_path.parent().map_or_else(
+            || PathBuf::from(INSTANCE_FILE),
+            |folder| folder.join(INSTANCE_FILE),
+        ),
+    };
+    let instance = Instance::load(&instance_path)?;
+    let capacities = load_capacities(
+        &params.topology_folder,
+        &topology,
+        scale,
+        dc.num_servers,
+        params.server_capacity,
+    )?;
+    instance.check_datacentre(dc.num_servers, &capacities)?;
+
+    let placement = load_placement(placement_path, solution)?;
+
+    if let Some(seed) = params.seed {
+        rng::seed(seed);
+    }
+
+    Ok((dc, rt, instance, placement))
+}
+
+/// Reads a placement as JSON, or bincode for other extensions, or the `solution`-th
+/// solution of a solutions export
+pub fn load_placement(path: &Path, solution: usize) -> Result<Placement, Box<dyn Error>> {
+    // Bincode does not describe its contents, so a file that is not an export is read as
+    // a placement
+    let mut export = match SolutionExport::load(path) {
+        Ok(export) => export,
+        Err(_) => return read_placement(path),
+    };
+
+    if solution >= export.solutions.len() {
+        return Err(format!(
+            "Solution {} is not in {:?}, which has {} solutions",
+            solution,
+            path,
+            export.solutions.len()
+        )
+        .into());
+    }
+
+    Ok(export.solutions.swap_remove(solution).placement)
+}
+
+fn read_placement(path: &Path) -> Result<Placement, Box<dyn Error>> {
+    let file =
+        File::open(path).map_err(|err| format!("Could not open placement {:?}: {}", path, err))?;
+    let reader = BufReader::new(file);
+
+    let is_json = path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        Ok(serde_json::from_reader(reader)?)
+    } else {
+        Ok(bincode::deserialize_from(reader)?)
+    }
+}
+
+pub fn evaluate_placement(
+    params: &Parameters,
+    dc: &Datacentre,
+    rt: &Vec<RoutingTable>,
+    instance: &Instance,
+    placement: &Placement,
+) -> Result<PlacementEvaluation, String> {
+    let services = &instance.services;
+    let routes = decode_placement(dc, rt, instance, placement)?;
+
+    let mut instances = vec![0; services.len()];
+    for (id, _) in &routes {
+        instances[*id] += 1;
+    }
+
+    let qm = queueing_model(params, dc);
+    let queueing = qm.evaluate_detailed(services, &routes);
+
+    let (sw_sr, sw_ql) = switch_settings(params, dc);
+    let um = UtilisationModel::new(dc, qm, sw_sr, sw_ql);
+    let (utilisations, utilisation_energy) = um.evaluate(services, &routes, |util| util);
+
+    let hm = HeuristicModel::new(dc);
+    let (components_used, route_length) = hm.evaluate(&routes);
+
+    let sla_violations = sla_violations(services, &queueing.latencies, &queueing.packet_losses);
+
+    Ok(PlacementEvaluation {
+        instances,
+        queueing,
+        utilisations,
+        utilisation_energy,
+        components_used,
+        route_length,
+        sla_violations,
+    })
+}
+
+/// Routes of the service instances of a placement, decoded with first fit like during the runs
+pub fn decode_placement(
+    dc: &Datacentre,
+    rt: &Vec<RoutingTable>,
+    instance: &Instance,
+    placement: &Placement,
+) -> Result<Vec<(ServiceID, Vec<RouteNode>)>, String> {
+    let services = &instance.services;
+
+    if placement.len() != dc.num_servers {
+        return Err(format!(
+            "The placement is for {} servers, but the datacentre has {}",
+            placement.len(),
+            dc.num_servers
+        ));
+    }
+
+    let mut point: Vec<Vec<&Service>> = Vec::with_capacity(placement.len());
+    for server in placement {
+        let mut server_services = Vec::with_capacity(server.len());
+
+        for &id in server {
+            let service = services
+                .get(id)
+                .ok_or_else(|| format!("Unknown service {} in the placement", id))?;
+            server_services.push(service);
+        }
+
+        point.push(server_services);
+    }
+
+    let capacities = instance.capacities();
+    let dm = distance_matrix::build_cache(dc, dc.num_servers);
+    let mapping = ServiceToRouteMapping::new(FirstFit::new(), &capacities, &dm, rt);
+
+    Ok(mapping.apply(&Solution::new(point)))
+}
+
+/// Service rate and queue length of the switches, which scale with their number of ports
+pub fn switch_settings(params: &Parameters, dc: &Datacentre) -> (f64, usize) {
+    (
+        params.switch_service_rate * dc.num_ports as f64,
+        params.switch_queue_length * dc.num_ports,
+    )
+}
+
+/// The queueing model with the settings of the parameters
+pub fn queueing_model<'a>(params: &Parameters, dc: &'a Datacentre) -> QueueingModel<'a> {
+    let (sw_sr, sw_ql) = switch_settings(params, dc);
+
+    let mut qm = QueueingModel::new(
+        dc,
+        sw_sr,
+        sw_ql,
+        params.accuracy,
+        params.converged_iterations,
+        params.active_cost,
+        params.idle_cost,
+    );
+    qm.max_iterations = params.max_iterations;
+    qm.relaxation = params.relaxation;
+    qm.switch_queue = params.switch_queue.build();
+    qm.vnf_queue = params.vnf_queue.build();
+    qm.power = Arc::new(params.power_model(dc));
+
+    qm
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        experiments::gen_topology,
+        models::{
+            datacentre::FatTree,
+            resources::Resources,
+            routing::get_tables,
+            service::{Sla, VNF},
+        },
+    };
+    use std::fs;
+
+    fn get_instance() -> Instance {
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(60),
+        };
+
+        let services = (0..3)
+            .map(|id| Service {
+                id,
+                prod_rate: 5.0,
+                vnfs: vec![vnf; 2],
+                sla: Sla::default(),
+            })
+            .collect();
+
+        Instance::new(&[Resources::scalar(100); 16], services)
+    }
+
+    #[test]
+    fn test_evaluate_placement() {
+        let dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+        let params = Parameters::default();
+        let mut instance = get_instance();
+
+        // Its two VNFs alone take longer than that
+        instance.services[1].sla.latency = Some(0.01);
+
+        let mut placement = vec![Vec::new(); dc.num_servers];
+        placement[0] = vec![0, 0];
+        placement[5] = vec![1];
+
+        let evaluation = evaluate_placement(&params, &dc, &rt, &instance, &placement).unwrap();
+        assert_eq!(evaluation.instances, vec![2, 1, 0]);
+        assert!(evaluation.queueing.latencies[0] > 0.0 && evaluation.queueing.latencies[1] > 0.0);
+        assert!(evaluation.queueing.energy > 0.0 && evaluation.utilisation_energy > 0.0);
+
+        let busiest = evaluation.busiest_switches(3);
+        assert_eq!(busiest.len(), 3);
+        assert!(busiest.iter().all(|switch| !switch.is_server));
+        assert!(busiest[0].utilisation >= busiest[2].utilisation);
+        assert!(evaluation.components_used > 0.0 && evaluation.components_used < 1.0);
+        assert!(evaluation.busiest_links(3).is_empty());
+        assert_eq!(evaluation.sla_violations[0], 0.0);
+        assert!(evaluation.sla_violations[1] > 0.0);
+
+        let mut output = Vec::new();
+        evaluation.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("unplaced"));
+        assert!(output.contains("1 services could not be placed"));
+        assert!(output.contains("1 services miss their SLA"));
+
+        placement[1] = vec![3];
+        assert!(evaluate_placement(&params, &dc, &rt, &instance, &placement).is_err());
+        assert!(evaluate_placement(&params, &dc, &rt, &instance, &vec![Vec::new(); 4]).is_err());
+    }
+
+    #[test]
+    fn test_load_placement() {
+        let folder = std::env::temp_dir().join("nfv_mls_conf_test_placement");
+        fs::create_dir_all(&folder).unwrap();
+
+        let path = folder.join("placement.json");
+        fs::write(&path, "[[0, 1], [], [2]]").unwrap();
+        assert_eq!(
+            load_placement(&path, 0).unwrap(),
+            vec![vec![0, 1], vec![], vec![2]]
+        );
+
+        let path = folder.join("placement.dat");
+        let placement: Placement = vec![vec![2], vec![0, 1]];
+        fs::write(&path, bincode::serialize(&placement).unwrap()).unwrap();
+        assert_eq!(load_placement(&path, 0).unwrap(), placement);
+
+        let export = SolutionExport {
+            num_servers: 2,
+            server_capacity: Resources::scalar(100),
+            evaluations: 10,
+            solutions: Vec::new(),
+        };
+        let path = folder.join("1_10.solutions.json");
+        export.save(&path).unwrap();
+        assert!(load_placement(&path, 0).is_err());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_load_evaluation() {
+        let folder = std::env::temp_dir().join("nfv_mls_conf_test_load_evaluation");
+        let mut params = Parameters::default();
+        params.topology_folder = folder.clone();
+        params.link_latency = 0.5;
+        gen_topology(&params, Topology::FatTree, &[16]).unwrap();
+        params.link_latency = 0.0;
+
+        let instance_path = folder.join(INSTANCE_FILE);
+        get_instance().save(&instance_path).unwrap();
+        let placement_path = folder.join("placement.json");
+        fs::write(&placement_path, "[[0, 1]]").unwrap();
+
+        let load = |params: &Parameters| {
+            load_evaluation(params, Topology::FatTree, 16, &placement_path, None, 0)
+        };
+
+        // The links are stored with the topology
+        let (dc, _, _, placement) = load(&params).unwrap();
+        assert_eq!(dc.link(0, 16).unwrap().latency, 0.5);
+        assert_eq!(placement, vec![vec![0, 1]]);
+
+        // The instance was made for servers of 100
+        params.server_capacity = Resources::scalar(80);
+        assert!(load(&params).is_err());
+
+        params.server_capacity = Resources::scalar(100);
+        let capacities_path = folder.join("FatTree_capacities_16.json");
+        fs::write(&capacities_path, format!("[{}]", vec!["80"; 16].join(", "))).unwrap();
+        assert!(load(&params).is_err());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
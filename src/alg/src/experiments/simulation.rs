@@ -0,0 +1,377 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fs::File,
+    io::{prelude::*, BufWriter},
+    path::Path,
+};
+
+use super::{
+    instance::Instance,
+    parameters::Parameters,
+    placement::{
+        decode_placement, load_evaluation, queueing_model, switch_settings, Placement,
+        BUSIEST_SWITCHES,
+    },
+};
+use crate::{
+    models::{
+        datacentre::{Datacentre, NodeID, Topology},
+        queueing_model::{ConvergenceError, QueueingReport},
+        routing::RoutingTable,
+        simulator::{QueueStats, ServiceComparison, Simulator, VnfStats},
+    },
+    utilities::metrics::mean,
+};
+
+/**
+ * A placement simulated and evaluated by the queueing model, showing where the model's
+ * assumptions of independent Poisson arrivals at every queue hold up.
+ **/
+#[derive(Debug, Clone)]
+pub struct SimulationComparison {
+    pub services: Vec<ServiceComparison>,
+
+    /// (id, model, simulation) of the switches that received packets
+    pub switches: Vec<(NodeID, QueueStats, QueueStats)>,
+
+    /// Simulated queues of the VNFs after the first of each service
+    pub vnfs: Vec<VnfStats>,
+
+    /// Why the queueing model did not converge, its values are then from the last iteration
+    pub error: Option<ConvergenceError>,
+}
+
+impl SimulationComparison {
+    pub fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "{:>8} {:>10} {:>12} {:>12} {:>10} {:>12} {:>12}",
+            "service", "packets", "latency", "simulated", "error", "packet loss", "simulated"
+        )?;
+
+        for service in &self.services {
+            writeln!(
+                writer,
+                "{:>8} {:>10} {:>12.6} {:>12.6} {:>9.2}% {:>12.6} {:>12.6}",
+                service.service,
+                service.packets,
+                service.model_latency,
+                service.simulated_latency,
+                service.latency_error() * 100.0,
+                service.model_packet_loss,
+                service.simulated_packet_loss
+            )?;
+        }
+
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "Mean absolute latency error {:.2}%, mean absolute packet loss error {:.6}",
+            self.latency_error() * 100.0,
+            self.packet_loss_error()
+        )?;
+        if let Some(error) = &self.error {
+            writeln!(writer, "Warning: {}", error)?;
+        }
+
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{:>8} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "switch",
+            "arrivals",
+            "simulated",
+            "utilisation",
+            "simulated",
+            "packet loss",
+            "simulated"
+        )?;
+        for (id, model, simulated) in self.busiest_switches(BUSIEST_SWITCHES) {
+            writeln!(
+                writer,
+                "{:>8} {:>12.4} {:>12.4} {:>12.6} {:>12.6} {:>12.6} {:>12.6}",
+                id,
+                model.arrival_rate,
+                simulated.arrival_rate,
+                model.utilisation,
+                simulated.utilisation,
+                model.packet_loss,
+                simulated.packet_loss
+            )?;
+        }
+
+        if !self.vnfs.is_empty() {
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "{:>8} {:>8} {:>6} {:>12} {:>12} {:>12}",
+                "server", "service", "stage", "arrivals", "utilisation", "packet loss"
+            )?;
+            for vnf in self.busiest_vnfs(BUSIEST_SWITCHES) {
+                writeln!(
+                    writer,
+                    "{:>8} {:>8} {:>6} {:>12.4} {:>12.6} {:>12.6}",
+                    vnf.server,
+                    vnf.service,
+                    vnf.stage,
+                    vnf.stats.arrival_rate,
+                    vnf.stats.utilisation,
+                    vnf.stats.packet_loss
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Mean of the absolute relative errors of the model's latencies
+    pub fn latency_error(&self) -> f64 {
+        let errors: Vec<f64> = self
+            .services
+            .iter()
+            .map(|service| service.latency_error().abs())
+            .collect();
+
+        mean(&errors)
+    }
+
+    /// Mean of the absolute differences of the model's packet losses
+    pub fn packet_loss_error(&self) -> f64 {
+        let errors: Vec<f64> = self
+            .services
+            .iter()
+            .map(|service| service.packet_loss_error().abs())
+            .collect();
+
+        mean(&errors)
+    }
+
+    /// The `n` switches the simulation found most utilised
+    pub fn busiest_switches(&self, n: usize) -> Vec<&(NodeID, QueueStats, QueueStats)> {
+        let mut switches: Vec<_> = self.switches.iter().collect();
+
+        switches.sort_by(|a, b| {
+            b.2.utilisation
+                .partial_cmp(&a.2.utilisation)
+                .unwrap_or(Ordering::Equal)
+        });
+        switches.truncate(n);
+
+        switches
+    }
+
+    /// The `n` VNFs the simulation found most utilised
+    pub fn busiest_vnfs(&self, n: usize) -> Vec<&VnfStats> {
+        let mut vnfs: Vec<_> = self.vnfs.iter().collect();
+
+        vnfs.sort_by(|a, b| {
+            b.stats
+                .utilisation
+                .partial_cmp(&a.stats.utilisation)
+                .unwrap_or(Ordering::Equal)
+        });
+        vnfs.truncate(n);
+
+        vnfs
+    }
+
+    /// Writes the comparison of every service to a CSV file
+    pub fn write_csv(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        writeln!(
+            file,
+            "service,packets,latency,simulated_latency,latency_error,packet_loss,simulated_packet_loss"
+        )?;
+
+        for service in &self.services {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{}",
+                service.service,
+                service.packets,
+                service.model_latency,
+                service.simulated_latency,
+                service.latency_error(),
+                service.model_packet_loss,
+                service.simulated_packet_loss
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Simulates a stored placement for `duration` after `warm_up` and compares what is
+ * measured with the queueing model. The placement, instance and seed are found like for
+ * `evaluate`, the seed also seeds the simulation. The comparison of every service can be
+ * written to `output` as CSV.
+ **/
+pub fn simulate(
+    params: &Parameters,
+    topology: Topology,
+    scale: usize,
+    placement_path: &Path,
+    instance_path: Option<&Path>,
+    solution: usize,
+    duration: f64,
+    warm_up: f64,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let (dc, rt, instance, placement) = load_evaluation(
+        params,
+        topology,
+        scale,
+        placement_path,
+        instance_path,
+        solution,
+    )?;
+
+    println!(
+        "Simulating {} services on {} {} from {:?} for {}",
+        instance.services.len(),
+        topology,
+        dc.num_servers,
+        placement_path,
+        duration
+    );
+
+    let comparison =
+        simulate_placement(params, &dc, &rt, &instance, &placement, duration, warm_up)?;
+
+    let stdout = std::io::stdout();
+    comparison.write(&mut stdout.lock())?;
+
+    if let Some(path) = output {
+        comparison.write_csv(path)?;
+        println!("Wrote the comparison of each service to {:?}", path);
+    }
+
+    Ok(())
+}
+
+pub fn simulate_placement(
+    params: &Parameters,
+    dc: &Datacentre,
+    rt: &Vec<RoutingTable>,
+    instance: &Instance,
+    placement: &Placement,
+    duration: f64,
+    warm_up: f64,
+) -> Result<SimulationComparison, String> {
+    if !(duration > 0.0) || !(warm_up >= 0.0) {
+        return Err("The duration must be positive and the warm up not negative".to_string());
+    }
+
+    let services = &instance.services;
+    let routes = decode_placement(dc, rt, instance, placement)?;
+
+    let QueueingReport {
+        latencies,
+        packet_losses,
+        components,
+        error,
+        ..
+    } = queueing_model(params, dc).evaluate_detailed(services, &routes);
+
+    let (sw_sr, sw_ql) = switch_settings(params, dc);
+    let mut sim = Simulator::new(dc, sw_sr, sw_ql);
+    sim.duration = duration;
+    sim.warm_up = warm_up;
+    sim.switch_queue = params.switch_queue;
+    sim.vnf_queue = params.vnf_queue;
+
+    let report = sim.simulate(services, &routes);
+
+    let switches = components
+        .iter()
+        .zip(&report.components)
+        .filter(|(model, _)| !model.is_server && model.arrival_rate > 0.0)
+        .map(|(model, simulated)| {
+            let model_stats = QueueStats {
+                arrival_rate: model.arrival_rate,
+                utilisation: model.utilisation,
+                packet_loss: model.packet_loss,
+            };
+
+            (model.id, model_stats, simulated.clone())
+        })
+        .collect();
+
+    Ok(SimulationComparison {
+        services: report.compare(&latencies, &packet_losses),
+        switches,
+        vnfs: report.vnfs,
+        error,
+    })
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            datacentre::FatTree,
+            resources::Resources,
+            routing::get_tables,
+            service::{Service, Sla, VNF},
+            simulator::relative_error,
+        },
+        utilities::rng,
+    };
+
+    #[test]
+    fn test_simulate_placement() {
+        rng::seed(3);
+
+        let dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+        let params = Parameters::default();
+
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(60),
+        };
+        let services = (0..2)
+            .map(|id| Service {
+                id,
+                prod_rate: 5.0,
+                vnfs: vec![vnf; 3],
+                sla: Sla::default(),
+            })
+            .collect();
+        let instance = Instance::new(&vec![Resources::scalar(100); dc.num_servers], services);
+
+        let mut placement = vec![Vec::new(); dc.num_servers];
+        placement[0] = vec![0];
+        placement[6] = vec![1];
+
+        let comparison =
+            simulate_placement(&params, &dc, &rt, &instance, &placement, 2000.0, 100.0).unwrap();
+        assert_eq!(comparison.services.len(), 2);
+        assert!(comparison.error.is_none());
+        assert!(comparison.latency_error() < 0.15);
+        assert!(comparison.packet_loss_error() < 0.01);
+
+        let busiest = comparison.busiest_switches(2);
+        assert_eq!(busiest.len(), 2);
+        for (_, model, simulated) in busiest {
+            assert!(relative_error(model.arrival_rate, simulated.arrival_rate).abs() < 0.1);
+        }
+
+        let vnfs = comparison.busiest_vnfs(10);
+        assert_eq!(vnfs.len(), 4);
+        assert!(vnfs[0].stats.utilisation >= vnfs[3].stats.utilisation);
+        assert!(vnfs.iter().all(|vnf| vnf.stats.arrival_rate > 0.0));
+
+        let mut output = Vec::new();
+        comparison.write(&mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("latency error"));
+
+        assert!(simulate_placement(&params, &dc, &rt, &instance, &placement, 0.0, 100.0).is_err());
+    }
+}
@@ -0,0 +1,668 @@
+use std::path::Path;
+
+use config::Config;
+use rand::{distributions::WeightedIndex, Rng};
+use rand_distr::{Distribution as _, LogNormal, Normal, Pareto};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    resources::{Resources, MAX_RESOURCES},
+    service::{Service, Sla, VNF},
+};
+
+/// Names of the built in workload profiles
+pub const PROFILES: [&str; 4] = ["default", "heavy_tailed", "bimodal", "web_security"];
+
+// Attempts at sampling services that fit in the datacentre before giving up
+const MAX_ATTEMPTS: usize = 1000;
+
+// Rates are kept above this so the queueing models stay defined
+const MIN_RATE: f64 = 0.01;
+
+/**
+ * A distribution to sample a workload property from. In a config file it is a table with a
+ * `type` and the parameters of that type, e.g. `{ type = "LogNormal", mu = 2.2, sigma = 0.4 }`.
+ * `Bounded` clamps the samples of another distribution and `Empirical` draws uniformly from
+ * a list of observed values, e.g. taken from a trace.
+ **/
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum Distribution {
+    Constant {
+        value: f64,
+    },
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    Normal {
+        mean: f64,
+        std_dev: f64,
+    },
+    LogNormal {
+        mu: f64,
+        sigma: f64,
+    },
+    Pareto {
+        scale: f64,
+        shape: f64,
+    },
+    Bimodal {
+        first: Box<Distribution>,
+        second: Box<Distribution>,
+        p_first: f64,
+    },
+    Empirical {
+        values: Vec<f64>,
+    },
+    Bounded {
+        distribution: Box<Distribution>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+    },
+}
+
+impl Distribution {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match self {
+            Distribution::Constant { value } => *value,
+            Distribution::Uniform { min, max } => rng.gen_range(*min, *max),
+            Distribution::Normal { mean, std_dev } => {
+                Normal::new(*mean, *std_dev).unwrap().sample(rng)
+            }
+            Distribution::LogNormal { mu, sigma } => {
+                LogNormal::new(*mu, *sigma).unwrap().sample(rng)
+            }
+            Distribution::Pareto { scale, shape } => {
+                Pareto::new(*scale, *shape).unwrap().sample(rng)
+            }
+            Distribution::Bimodal {
+                first,
+                second,
+                p_first,
+            } => {
+                if rng.gen_bool(*p_first) {
+                    first.sample(rng)
+                } else {
+                    second.sample(rng)
+                }
+            }
+            Distribution::Empirical { values } => values[rng.gen_range(0, values.len())],
+            Distribution::Bounded {
+                distribution,
+                min,
+                max,
+            } => {
+                let mut value = distribution.sample(rng);
+                if let Some(max) = max {
+                    value = value.min(*max);
+                }
+                if let Some(min) = min {
+                    value = value.max(*min);
+                }
+                value
+            }
+        }
+    }
+
+    /// Mean of the distribution, ignoring any bounds. Infinite for heavy enough tails.
+    pub fn mean(&self) -> f64 {
+        match self {
+            Distribution::Constant { value } => *value,
+            Distribution::Uniform { min, max } => (min + max) / 2.0,
+            Distribution::Normal { mean, .. } => *mean,
+            Distribution::LogNormal { mu, sigma } => (mu + sigma * sigma / 2.0).exp(),
+            Distribution::Pareto { scale, shape } => {
+                if *shape > 1.0 {
+                    shape * scale / (shape - 1.0)
+                } else {
+                    std::f64::INFINITY
+                }
+            }
+            Distribution::Bimodal {
+                first,
+                second,
+                p_first,
+            } => p_first * first.mean() + (1.0 - p_first) * second.mean(),
+            Distribution::Empirical { values } => values.iter().sum::<f64>() / values.len() as f64,
+            Distribution::Bounded { distribution, .. } => distribution.mean(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let valid = match self {
+            Distribution::Constant { value } => value.is_finite(),
+            Distribution::Uniform { min, max } => min < max,
+            Distribution::Normal { mean, std_dev } => Normal::new(*mean, *std_dev).is_ok(),
+            Distribution::LogNormal { mu, sigma } => LogNormal::new(*mu, *sigma).is_ok(),
+            Distribution::Pareto { scale, shape } => Pareto::new(*scale, *shape).is_ok(),
+            Distribution::Bimodal {
+                first,
+                second,
+                p_first,
+            } => {
+                first.validate()?;
+                second.validate()?;
+                (0.0..=1.0).contains(p_first)
+            }
+            Distribution::Empirical { values } => {
+                !values.is_empty() && values.iter().all(|value| value.is_finite())
+            }
+            Distribution::Bounded {
+                distribution,
+                min,
+                max,
+            } => {
+                distribution.validate()?;
+                match (min, max) {
+                    (Some(min), Some(max)) => min <= max,
+                    _ => true,
+                }
+            }
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("invalid distribution {:?}", self))
+        }
+    }
+
+    fn bounded(distribution: Distribution, min: f64, max: f64) -> Distribution {
+        Distribution::Bounded {
+            distribution: Box::new(distribution),
+            min: Some(min),
+            max: Some(max),
+        }
+    }
+
+    fn at_least(distribution: Distribution, min: f64) -> Distribution {
+        Distribution::Bounded {
+            distribution: Box::new(distribution),
+            min: Some(min),
+            max: None,
+        }
+    }
+}
+
+/**
+ * A kind of service chain, e.g. short web chains or long security chains. The weights of
+ * the classes of a workload give the expected share of services of each class.
+ **/
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ServiceClass {
+    pub name: String,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    pub chain_length: Distribution,
+    pub prod_rate: Distribution,
+    pub service_rate: Distribution,
+    pub size: Distribution,
+    #[serde(default = "default_queue_length")]
+    pub queue_length: Distribution,
+
+    /// Sizes of the VNFs in the resource types after the first, e.g. memory and bandwidth
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<Distribution>,
+
+    /// Bounds on the latency and packet loss of every service of the class
+    #[serde(default, skip_serializing_if = "Sla::is_empty")]
+    pub sla: Sla,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_queue_length() -> Distribution {
+    Distribution::Constant { value: 20.0 }
+}
+
+/**
+ * Describes how the services of a problem instance are sampled. Set with the `workload` key
+ * of the config file, which is either the name of a built in profile (see `PROFILES`), the
+ * path of a TOML or JSON file holding a profile, or an inline table:
+ *
+ * [workload]
+ * name = "mixed"
+ *
+ * [[workload.classes]]
+ * name = "web"
+ * weight = 0.7
+ * chain_length = { type = "Bounded", distribution = { type = "Normal", mean = 3, std_dev = 0.5 }, min = 2, max = 5 }
+ * prod_rate = { type = "LogNormal", mu = 2.2, sigma = 0.4 }
+ * service_rate = { type = "Normal", mean = 10, std_dev = 3 }
+ * size = { type = "Empirical", values = [20, 25, 40] }
+ *
+ * `size` is the first resource type of the servers, a class can list the sizes of the other
+ * types in order, e.g. `resources = [{ type = "Normal", mean = 16, std_dev = 4 }]`. The
+ * services of a class can be given an SLA, e.g. `sla = { latency = 2.5, packet_loss = 0.01 }`.
+ **/
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Workload {
+    pub name: String,
+    pub classes: Vec<ServiceClass>,
+}
+
+impl Default for Workload {
+    fn default() -> Self {
+        Workload::profile("default").unwrap()
+    }
+}
+
+impl Workload {
+    /// Loads a built in profile or, if there is none with that name, a profile file
+    pub fn from_name(name: &str) -> Result<Workload, String> {
+        if let Some(workload) = Workload::profile(name) {
+            return Ok(workload);
+        }
+
+        if !Path::new(name).is_file() {
+            return Err(format!(
+                "'{}' is neither a workload profile ({}) nor a profile file",
+                name,
+                PROFILES.join(", ")
+            ));
+        }
+
+        let mut settings = Config::default();
+        settings
+            .merge(config::File::with_name(name))
+            .and_then(|settings| settings.clone().try_into())
+            .map_err(|err| format!("Could not read workload profile {}: {}", name, err))
+    }
+
+    /// The built in profiles
+    pub fn profile(name: &str) -> Option<Workload> {
+        use Distribution::*;
+
+        let normal = |mean, std_dev| Normal { mean, std_dev };
+        let rate = |distribution| Distribution::at_least(distribution, 2.0);
+
+        // The workload the experiments were originally run with
+        let standard = ServiceClass {
+            name: "standard".to_string(),
+            weight: 1.0,
+            chain_length: Distribution::bounded(normal(5.0, 1.0), 2.0, 12.0),
+            prod_rate: rate(normal(10.0, 3.0)),
+            service_rate: rate(normal(10.0, 3.0)),
+            size: normal(40.0, 10.0),
+            queue_length: default_queue_length(),
+            resources: Vec::new(),
+            sla: Sla::default(),
+        };
+
+        let classes = match name {
+            "default" => vec![standard],
+            "heavy_tailed" => vec![ServiceClass {
+                name: "heavy_tailed".to_string(),
+                prod_rate: rate(LogNormal {
+                    mu: 2.2,
+                    sigma: 0.5,
+                }),
+                service_rate: rate(LogNormal {
+                    mu: 2.2,
+                    sigma: 0.5,
+                }),
+                size: Pareto {
+                    scale: 20.0,
+                    shape: 2.0,
+                },
+                ..standard
+            }],
+            "bimodal" => vec![ServiceClass {
+                name: "bimodal".to_string(),
+                chain_length: Distribution::bounded(
+                    Bimodal {
+                        first: Box::new(normal(3.0, 0.5)),
+                        second: Box::new(normal(9.0, 1.0)),
+                        p_first: 0.5,
+                    },
+                    2.0,
+                    12.0,
+                ),
+                ..standard
+            }],
+            "web_security" => vec![
+                ServiceClass {
+                    name: "web".to_string(),
+                    weight: 0.7,
+                    chain_length: Distribution::bounded(normal(3.0, 0.5), 2.0, 5.0),
+                    size: normal(30.0, 5.0),
+                    ..standard.clone()
+                },
+                ServiceClass {
+                    name: "security".to_string(),
+                    weight: 0.3,
+                    chain_length: Distribution::bounded(normal(8.0, 1.5), 5.0, 12.0),
+                    service_rate: rate(normal(8.0, 2.0)),
+                    size: normal(55.0, 10.0),
+                    ..standard
+                },
+            ],
+            _ => return None,
+        };
+
+        Some(Workload {
+            name: name.to_string(),
+            classes,
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.classes.is_empty() {
+            return Err(format!("workload '{}' has no service classes", self.name));
+        }
+
+        for class in &self.classes {
+            let distributions = [
+                &class.chain_length,
+                &class.prod_rate,
+                &class.service_rate,
+                &class.size,
+                &class.queue_length,
+            ];
+
+            for distribution in distributions.iter().cloned().chain(&class.resources) {
+                distribution
+                    .validate()
+                    .map_err(|err| format!("service class '{}' has an {}", class.name, err))?;
+            }
+
+            if !(class.weight > 0.0) {
+                return Err(format!(
+                    "service class '{}' needs a positive weight",
+                    class.name
+                ));
+            }
+            if class.resources.len() >= MAX_RESOURCES {
+                return Err(format!(
+                    "service class '{}' has more than {} resource types",
+                    class.name, MAX_RESOURCES
+                ));
+            }
+            if !class.sla.is_valid() {
+                return Err(format!(
+                    "service class '{}' needs positive SLA bounds",
+                    class.name
+                ));
+            }
+        }
+
+        let mean = self.mean_chain_length();
+        if !(mean.is_finite() && mean > 0.0) {
+            return Err(format!(
+                "workload '{}' needs a finite positive mean chain length",
+                self.name
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Expected number of VNFs in a service, weighted by the share of each class
+    pub fn mean_chain_length(&self) -> f64 {
+        let total_weight: f64 = self.classes.iter().map(|class| class.weight).sum();
+
+        self.classes
+            .iter()
+            .map(|class| class.weight * class.chain_length.mean())
+            .sum::<f64>()
+            / total_weight
+    }
+
+    /**
+     * Samples services until their VNFs use `utilisation` of the datacentre capacity on
+     * average, for servers with the given `capacities`. Instances that need more than the
+     * total capacity of any resource type, or with a VNF that fits in no server, are
+     * discarded and sampled again, as they cannot be solved.
+     **/
+    pub fn generate<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        capacities: &[Resources],
+        utilisation: f64,
+    ) -> Result<Vec<Service>, String> {
+        let num_servers = capacities.len();
+        let num_services =
+            (utilisation * (1.0 / self.mean_chain_length()) * num_servers as f64).max(1.0) as usize;
+
+        let weights = self.classes.iter().map(|class| class.weight);
+        let class_distr = WeightedIndex::new(weights).map_err(|err| err.to_string())?;
+
+        let total_capacity = capacities
+            .iter()
+            .fold(Resources::scalar(0), |total, &capacity| total + capacity);
+        let largest = capacities
+            .iter()
+            .fold(Resources::scalar(0), |max, capacity| max.max(capacity));
+
+        // VNFs are clamped to the largest capacity of each type, which may not be one server
+        let uniform = capacities.iter().all(|&capacity| capacity == largest);
+        let fits_a_server =
+            |vnf: &VNF| uniform || capacities.iter().any(|capacity| capacity.fits(&vnf.size));
+
+        for _ in 0..MAX_ATTEMPTS {
+            let mut services = Vec::with_capacity(num_services);
+            let mut used_capacity = Resources::scalar(0);
+            let mut fits = true;
+
+            for service_id in 0..num_services {
+                // Only draw a class if there is a choice, so single class workloads use the
+                // random numbers exactly as the original generator did
+                let class = match self.classes.len() {
+                    1 => &self.classes[0],
+                    _ => &self.classes[class_distr.sample(rng)],
+                };
+
+                let service = class.sample(rng, service_id, &largest);
+                for vnf in &service.vnfs {
+                    used_capacity += vnf.size;
+                    fits = fits && fits_a_server(vnf);
+                }
+
+                services.push(service);
+            }
+
+            // Filter out some unsolveable problems
+            if fits && total_capacity.fits(&used_capacity) {
+                return Ok(services);
+            }
+        }
+
+        Err(format!(
+            "Workload '{}' did not fit in {} servers in {} attempts, lower the utilisation",
+            self.name, num_servers, MAX_ATTEMPTS
+        ))
+    }
+}
+
+impl ServiceClass {
+    fn sample<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        id: usize,
+        server_capacity: &Resources,
+    ) -> Service {
+        let prod_rate = self.prod_rate.sample(rng).max(MIN_RATE);
+
+        let num_vnfs = self.chain_length.sample(rng).max(1.0) as usize;
+
+        let vnfs = (0..num_vnfs)
+            .map(|_| {
+                let service_rate = self.service_rate.sample(rng).max(MIN_RATE);
+
+                let mut size = [0; MAX_RESOURCES];
+                let amount = self.size.sample(rng);
+                size[0] = amount.min(server_capacity[0] as f64).max(1.0) as usize;
+
+                let queue_length = self.queue_length.sample(rng).max(1.0) as usize;
+
+                // Sampled last so classes with one resource type draw the same numbers
+                for (d, distribution) in self.resources.iter().enumerate() {
+                    let amount = distribution.sample(rng);
+                    size[d + 1] = amount.min(server_capacity[d + 1] as f64).max(0.0) as usize;
+                }
+                let size = Resources::new(&size[..self.resources.len() + 1]).unwrap();
+
+                VNF {
+                    service_rate,
+                    queue_length,
+                    size,
+                }
+            })
+            .collect();
+
+        Service {
+            id,
+            prod_rate,
+            vnfs,
+            sla: self.sla,
+        }
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn test_profiles() {
+        let mut rng = Pcg64::seed_from_u64(3);
+
+        for name in PROFILES.iter() {
+            let workload = Workload::from_name(name).unwrap();
+            assert!(workload.validate().is_ok());
+
+            let capacities = [Resources::scalar(100); 100];
+            let services = workload.generate(&mut rng, &capacities, 0.5).unwrap();
+            assert!(!services.is_empty());
+
+            for (i, service) in services.iter().enumerate() {
+                assert_eq!(service.id, i);
+                assert!(service.prod_rate >= 2.0);
+                assert!(service.vnfs.len() >= 2 && service.vnfs.len() <= 12);
+                assert!(service
+                    .vnfs
+                    .iter()
+                    .all(|vnf| vnf.size[0] >= 1 && vnf.size[0] <= 100));
+            }
+        }
+
+        assert!(Workload::from_name("unknown").is_err());
+    }
+
+    #[test]
+    fn test_class_mix() {
+        let workload = Workload::profile("web_security").unwrap();
+        let mut rng = Pcg64::seed_from_u64(7);
+
+        let capacities = vec![Resources::scalar(1000); 10000];
+        let services = workload.generate(&mut rng, &capacities, 0.5).unwrap();
+        let long = services.iter().filter(|s| s.vnfs.len() >= 5).count();
+        let share = long as f64 / services.len() as f64;
+
+        // 30% of the services are security chains, plus the occasional long web chain
+        assert!(share > 0.25 && share < 0.4, "share {}", share);
+    }
+
+    #[test]
+    fn test_distributions() {
+        let mut rng = Pcg64::seed_from_u64(11);
+
+        let bimodal = Distribution::Bimodal {
+            first: Box::new(Distribution::Constant { value: 1.0 }),
+            second: Box::new(Distribution::Constant { value: 5.0 }),
+            p_first: 0.25,
+        };
+        assert_eq!(bimodal.mean(), 4.0);
+
+        let empirical = Distribution::Empirical {
+            values: vec![2.0, 4.0],
+        };
+        for _ in 0..100 {
+            let value = empirical.sample(&mut rng);
+            assert!(value == 2.0 || value == 4.0);
+        }
+
+        let pareto = Distribution::Pareto {
+            scale: 1.0,
+            shape: 0.5,
+        };
+        assert!(pareto.mean().is_infinite());
+
+        let bounded = Distribution::Bounded {
+            distribution: Box::new(pareto),
+            min: None,
+            max: Some(10.0),
+        };
+        assert!((0..100).all(|_| bounded.sample(&mut rng) <= 10.0));
+
+        assert!(Distribution::Uniform { min: 1.0, max: 0.0 }
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_resource_types() {
+        let mut workload = Workload::default();
+        workload.classes[0].resources = vec![Distribution::Uniform {
+            min: 10.0,
+            max: 60.0,
+        }];
+        assert!(workload.validate().is_ok());
+
+        // Half the servers have little memory
+        let small = Resources::new(&[100, 20]).unwrap();
+        let large = Resources::new(&[60, 80]).unwrap();
+        let capacities: Vec<_> = (0..50)
+            .map(|i| if i % 2 == 0 { small } else { large })
+            .collect();
+
+        let mut rng = Pcg64::seed_from_u64(5);
+        let services = workload.generate(&mut rng, &capacities, 0.3).unwrap();
+
+        for vnf in services.iter().flat_map(|service| &service.vnfs) {
+            assert_eq!(vnf.size.as_slice().len(), 2);
+            assert!(capacities.iter().any(|capacity| capacity.fits(&vnf.size)));
+        }
+
+        workload.classes[0].resources = vec![Distribution::Constant { value: 1.0 }; 4];
+        assert!(workload.validate().is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let toml = r#"
+            name = "trace"
+
+            [[classes]]
+            name = "web"
+            weight = 2
+            chain_length = { type = "Constant", value = 3 }
+            prod_rate = { type = "LogNormal", mu = 2.2, sigma = 0.4 }
+            service_rate = { type = "Empirical", values = [8.0, 9.5, 12.0] }
+            size = { type = "Bounded", distribution = { type = "Normal", mean = 30, std_dev = 5 }, min = 10 }
+        "#;
+
+        let mut settings = Config::default();
+        settings
+            .merge(config::File::from_str(toml, config::FileFormat::Toml))
+            .unwrap();
+        let workload: Workload = settings.try_into().unwrap();
+
+        assert!(workload.validate().is_ok());
+        assert_eq!(workload.classes[0].weight, 2.0);
+        assert_eq!(workload.classes[0].queue_length, default_queue_length());
+        assert_eq!(workload.mean_chain_length(), 3.0);
+
+        // Dumps read back the same
+        let json = serde_json::to_string(&workload).unwrap();
+        assert_eq!(serde_json::from_str::<Workload>(&json).unwrap(), workload);
+    }
+}
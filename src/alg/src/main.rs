@@ -1,404 +1,269 @@
+#[macro_use]
+mod utilities;
+
 mod algorithms;
+mod cli;
+mod experiments;
 mod models;
 mod operators;
-mod utilities;
 
-use algorithms::{cnsgaii, nsgaii, pnsgaii, pplsd};
-use utilities::stopwatch::Stopwatch;
-
-use models::{
-    datacentre::{Datacentre, Topology},
-    queueing_model::QueueingModel,
-    routing,
-    service::{Service, VNF},
-};
-use operators::{
-    distance_matrix, evaluation::QueueingEval, initialisation::ServiceAwareInitialisation,
-    mapping::ServiceToRouteMapping, mutation::AddRemoveSwapMutation,
-    neighbour_gen::AddSwapNeighbour, placement_strategies::FirstFit, solution::Solution,
-};
-use rand::thread_rng;
-use rand_distr::{Distribution, Normal};
-use routing::RoutingTable;
 use std::{
-    fs::{self, File, OpenOptions},
-    io::{prelude::*, BufReader, BufWriter},
-    path::PathBuf,
+    error::Error,
+    io::prelude::*,
+    path::{Path, PathBuf},
 };
 
-use crate::operators::crossover::UniformCrossover;
-
-fn main() {
-    let mut settings = config::Config::default();
-    settings
-        .merge(config::File::with_name("Config"))
-        .unwrap()
-        .merge(config::Environment::with_prefix("APP"))
-        .unwrap();
-
-    // Get output folder
-    let results_folder: String = settings.get("results_folder").unwrap();
-    let results_folder = PathBuf::new().join(&results_folder);
-
-    let max_evaluations: usize = settings.get("max_evaluations").unwrap();
+use clap::ArgMatches;
+use config::{Config, ConfigError};
 
-    if settings.get("test_num_cores").unwrap() {
-        let mut file = get_file(&results_folder, "NumCores.txt").unwrap();
-        writeln!(&mut file, "{}", num_cpus::get()).unwrap();
-
-        return;
-    }
-
-    if settings.get("fat_tree").unwrap() {
-        println!("Starting Fat Tree");
-        run_basic_tests(Topology::FatTree, &results_folder, max_evaluations);
-    }
+use algorithms::Algorithm;
+use experiments::{
+    analysis::{Comparison, ANALYSIS_FOLDER},
+    parameters::Parameters,
+};
+use models::{
+    datacentre::Topology,
+    simulator::{DEFAULT_DURATION, DEFAULT_WARM_UP},
+};
 
-    if settings.get("dcell").unwrap() {
-        println!("Starting DCell");
-        run_basic_tests(Topology::DCell, &results_folder, max_evaluations);
-    }
+fn main() {
+    let matches = cli::app().get_matches();
 
-    if settings.get("leaf_spine").unwrap() {
-        println!("Starting Leaf Spine");
-        run_basic_tests(Topology::LeafSpine, &results_folder, max_evaluations);
+    if let Err(err) = dispatch(&matches) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
     }
 }
 
-fn run_basic_tests(topology: Topology, results_folder: &PathBuf, max_evaluations: usize) {
-    let scales = [16000];
-
-    let sw_sr_base = 20.0;
-    let sw_ql_base = 20;
-    let accuracy = 5.0;
-    let converged_iterations = 10;
-    let active_cost = 30.0;
-    let idle_cost = 10.0;
-
-    let utilisation = 0.6;
-
-    for scale in scales.iter() {
-        let (dc, rt) = load_topology(&topology, *scale);
-
-        let sw_sr = sw_sr_base * dc.num_ports as f64;
-        let sw_ql = sw_ql_base * dc.num_ports;
-
-        // Mapping + Fitness function
-        let num_nearest = dc.num_servers;
-        let dm = distance_matrix::build_cache(&dc, num_nearest);
-
-        let capacities = vec![100; dc.num_servers];
-
-        let qm = QueueingModel::new(
-            &dc,
-            sw_sr,
-            sw_ql,
-            accuracy,
-            converged_iterations,
-            active_cost,
-            idle_cost,
-        );
-
-        for pi in 0..30 {
-            let problem_folder = results_folder
-                .join(topology.to_string())
-                .join(scale.to_string());
-
-            let services = create_problem_instance(dc.num_servers, utilisation);
+fn dispatch(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    // A manifest describes the whole study, so it takes the place of the config file
+    let manifest = matches
+        .subcommand_matches("run")
+        .and_then(|args| args.value_of("manifest"))
+        .map(Path::new);
+
+    let settings = match manifest {
+        Some(manifest) => load_config(manifest.to_str())?,
+        None => load_config(matches.value_of("config"))?,
+    };
+
+    match matches.subcommand() {
+        ("gen_topology", Some(args)) => {
+            let topology: Topology = args.value_of("topology").unwrap().parse()?;
+
+            let mut sizes = Vec::new();
+            for size in args.values_of("num_servers").unwrap() {
+                let size = size
+                    .parse()
+                    .map_err(|_| format!("Invalid number of servers '{}'", size))?;
+                sizes.push(size);
+            }
 
-            let node_selection = FirstFit::new();
+            let mut params = Parameters::from_config(&settings)?;
+            params.apply_args(args)?;
 
-            // --- Genetic Operators ---
-            let evaluate = QueueingEval::new(
-                qm.clone(),
-                &rt,
-                &dm,
+            experiments::gen_topology(&params, topology, &sizes)
+        }
+        ("generate", Some(args)) => {
+            let topology: Topology = args.value_of("topology").unwrap().parse()?;
+            let num_servers = args.value_of("num_servers").unwrap();
+            let num_servers = num_servers
+                .parse()
+                .map_err(|_| format!("Invalid number of servers '{}'", num_servers))?;
+
+            let mut params = Parameters::from_config(&settings)?;
+            params.apply_args(args)?;
+            params.validate_instance()?;
+
+            let seed = params.seed.unwrap_or_else(utilities::rng::random_seed);
+            utilities::rng::seed(seed);
+
+            let dc = topology.build(num_servers);
+            let capacities = experiments::load_capacities(
+                &params.topology_folder,
+                &topology,
+                num_servers,
+                dc.num_servers,
+                params.server_capacity,
+            )?;
+            let instance = experiments::generate_instance(
+                &params.workload,
                 &capacities,
-                &services,
-                node_selection.clone(),
-            );
-
-            // Initialisation
-            let init_pop = ServiceAwareInitialisation::new(&services, dc.num_servers);
-
-            // Mapping
-            let strm = ServiceToRouteMapping::new(node_selection.clone(), &capacities, &dm, &rt);
-
-            // Mutation
-            let pm = 0.4;
-
-            let items = services.iter().map(|s| s).collect();
-
-            let mutation = AddRemoveSwapMutation::new(items, pm);
-
-            // Crossover
-            let pc = 0.4;
-            let crossover = UniformCrossover::new(pc);
-
-            // --- NSGA-II ---
-            let alg_folder = problem_folder.join("NSGAII").join(pi.to_string());
-
-            let mut ns_st = Stopwatch::new();
-            ns_st.start();
-
-            nsgaii::run(
-                &init_pop,
-                &strm,
-                &evaluate,
-                &mutation,
-                &crossover,
-                128,
-                max_evaluations,
-                |evaluations, pop| {
-                    let time = ns_st.stop();
-
-                    let file_name = format!("{}_{}.objs", services.len(), evaluations);
-                    print_population_objectives(&alg_folder, file_name, pop).unwrap();
-
-                    let mut file = get_file(&alg_folder, "running_time.out").unwrap();
-                    write!(file, "{}", time).unwrap();
-                },
-            );
-
-            // --- C-NSGA-II ---
-            let alg_folder = problem_folder.join("CNSGAII").join(pi.to_string());
-
-            let mut cs_st = Stopwatch::new();
-            cs_st.start();
-
-            cnsgaii::run(
-                &init_pop,
-                &strm,
-                &evaluate,
-                &mutation,
-                &crossover,
-                128,
-                max_evaluations,
-                |evaluations, pop| {
-                    let time = cs_st.stop();
-
-                    let file_name = format!("{}_{}.objs", services.len(), evaluations);
-                    print_population_objectives(&alg_folder, file_name, pop).unwrap();
-
-                    let mut file = get_file(&alg_folder, "running_time.out").unwrap();
-                    write!(file, "{}", time).unwrap();
-                },
-            );
-
-            // --- P-NSGA-II ---
-            let alg_folder = problem_folder.join("PNSGAII").join(pi.to_string());
-            let num_epochs = 10;
-
-            let mut ps_st = Stopwatch::new();
-            ps_st.start();
-
-            pnsgaii::run(
-                &init_pop,
-                &strm,
-                &evaluate,
-                &mutation,
-                &crossover,
-                128,
-                max_evaluations,
-                num_epochs,
-                |evaluations, pop| {
-                    let time = ps_st.stop();
-
-                    let file_name = format!("{}_{}.objs", services.len(), evaluations);
-                    print_population_objectives(&alg_folder, file_name, pop).unwrap();
-
-                    let mut file = get_file(&alg_folder, "running_time.out").unwrap();
-                    write!(file, "{}", time).unwrap();
-                },
+                params.utilisations[0],
+            )?;
+
+            let output = Path::new(args.value_of("output").unwrap());
+            instance.save(output)?;
+            println!(
+                "Saved {} services for {} servers to {:?} (seed {})",
+                instance.services.len(),
+                dc.num_servers,
+                output,
+                seed
             );
 
-            // --- PPLS/D ---
-            let items = services.iter().map(|s| s).collect();
-            let neighbour_gen = AddSwapNeighbour::new(items);
-
-            let alg_folder = problem_folder.join("PPLS").join(pi.to_string());
-
-            let mut pp_st = Stopwatch::new();
-            pp_st.start();
-
-            pplsd::run(
-                &init_pop,
-                &strm,
-                &evaluate,
-                &neighbour_gen,
-                16,
-                max_evaluations,
-                10,
-                3,
-                |evaluations, pop| {
-                    let time = pp_st.stop();
-
-                    let file_name = format!("{}_{}.objs", services.len(), evaluations);
-                    print_population_objectives(&alg_folder, file_name, pop).unwrap();
-
-                    let mut file = get_file(&alg_folder, "running_time.out").unwrap();
-                    write!(file, "{}", time).unwrap();
-                },
-            );
-
-            // // --- SPPLS ---
-            // let items = services.iter().map(|s| s).collect();
-            // let neighbour_gen = AddSwapNeighbour::new(items);
-
-            // let alg_folder = problem_folder.join("SPPLS").join(pi.to_string());
-
-            // let mut sp_st = Stopwatch::new();
-            // sp_st.start();
-
-            // sppls::run(
-            //     &init_pop,
-            //     &strm,
-            //     &evaluate,
-            //     &neighbour_gen,
-            //     128,
-            //     max_evaluations,
-            //     10,
-            //     3,
-            //     |evaluations, pop| {
-            //         let time = sp_st.stop();
-
-            //         let file_name = format!("{}_{}.objs", services.len(), evaluations);
-            //         print_population_objectives(&alg_folder, file_name, pop).unwrap();
-
-            //         let mut file = get_file(&alg_folder, "running_time.out").unwrap();
-            //         write!(file, "{}", time).unwrap();
-            //     },
-            // );
+            Ok(())
         }
-    }
-}
-
-fn create_problem_instance(num_servers: usize, utilisation: f64) -> Vec<Service> {
-    // Problem parameter settings
-    let mean_service_len = 5.0;
-    let variance_service_len = 1.0;
-
-    let mean_prod_rate = 10.0;
-    let variance_prod_rate = 3.0;
-
-    let mean_service_rate = 10.0;
-    let variance_service_rate = 3.0;
-
-    let queue_length = 20;
-
-    let mean_size = 40.0;
-    let variance_size = 10.0;
-
-    // Distributions
-    let service_len_distr = Normal::new(mean_service_len, variance_service_len).unwrap();
-    let prod_rate_distr = Normal::new(mean_prod_rate, variance_prod_rate).unwrap();
-    let service_rate_distr = Normal::new(mean_service_rate, variance_service_rate).unwrap();
-    let size_distr = Normal::new(mean_size, variance_size).unwrap();
+        ("evaluate", Some(args)) => {
+            let topology: Topology = args.value_of("topology").unwrap().parse()?;
+            let num_servers = args.value_of("num_servers").unwrap();
+            let num_servers = num_servers
+                .parse()
+                .map_err(|_| format!("Invalid number of servers '{}'", num_servers))?;
+
+            let solution = match args.value_of("solution") {
+                Some(solution) => solution
+                    .parse()
+                    .map_err(|_| format!("Invalid solution '{}'", solution))?,
+                None => 0,
+            };
 
-    let num_services =
-        (utilisation * (1.0 / mean_service_len) * num_servers as f64).max(1.0) as usize;
+            let mut params = Parameters::from_config(&settings)?;
+            params.apply_args(args)?;
+
+            experiments::placement::evaluate(
+                &params,
+                topology,
+                num_servers,
+                Path::new(args.value_of("placement").unwrap()),
+                args.value_of("instance").map(Path::new),
+                solution,
+                args.value_of("diagnostics").map(Path::new),
+            )
+        }
+        ("simulate", Some(args)) => {
+            let topology: Topology = args.value_of("topology").unwrap().parse()?;
+            let num_servers = args.value_of("num_servers").unwrap();
+            let num_servers = num_servers
+                .parse()
+                .map_err(|_| format!("Invalid number of servers '{}'", num_servers))?;
+
+            let solution = match args.value_of("solution") {
+                Some(solution) => solution
+                    .parse()
+                    .map_err(|_| format!("Invalid solution '{}'", solution))?,
+                None => 0,
+            };
+            let duration = match args.value_of("duration") {
+                Some(duration) => duration
+                    .parse()
+                    .map_err(|_| format!("Invalid duration '{}'", duration))?,
+                None => DEFAULT_DURATION,
+            };
+            let warm_up = match args.value_of("warm-up") {
+                Some(warm_up) => warm_up
+                    .parse()
+                    .map_err(|_| format!("Invalid warm up '{}'", warm_up))?,
+                None => DEFAULT_WARM_UP,
+            };
 
-    let mut rng = thread_rng();
+            let mut params = Parameters::from_config(&settings)?;
+            params.apply_args(args)?;
+
+            experiments::simulation::simulate(
+                &params,
+                topology,
+                num_servers,
+                Path::new(args.value_of("placement").unwrap()),
+                args.value_of("instance").map(Path::new),
+                solution,
+                duration,
+                warm_up,
+                args.value_of("output").map(Path::new),
+            )
+        }
+        ("analyse", Some(args)) => {
+            let mut params = Parameters::from_config(&settings)?;
+            params.apply_args(args)?;
 
-    loop {
-        let mut vnf_id = 0;
-        let mut services = Vec::new();
-        for service_id in 0..num_services {
-            let prod_rate: f64 = prod_rate_distr.sample(&mut rng);
-            let prod_rate = prod_rate.max(2.0);
+            let output = match args.value_of("output") {
+                Some(output) => PathBuf::from(output),
+                None => params.results_folder.join(ANALYSIS_FOLDER),
+            };
 
-            let mut service = Service {
-                id: service_id,
-                prod_rate: prod_rate,
-                vnfs: Vec::new(),
+            // Algorithm names are normalised to their folder names, other names are kept as given
+            let control = args.value_of("control").map(|control| {
+                control
+                    .parse::<Algorithm>()
+                    .map(|algorithm| algorithm.to_string())
+                    .unwrap_or_else(|_| control.to_string())
+            });
+
+            let alpha = match args.value_of("alpha") {
+                Some(alpha) => alpha
+                    .parse()
+                    .map_err(|err| format!("Invalid value '{}' for '--alpha': {}", alpha, err))?,
+                None => 0.05,
             };
 
-            let num_vnfs = service_len_distr.sample(&mut rng).max(2.0).min(12.0);
-            let num_vnfs = num_vnfs as usize;
+            let comparison = Comparison {
+                control,
+                paired: args.is_present("paired"),
+                alpha,
+            };
 
-            for _ in 0..num_vnfs {
-                let service_rate: f64 = service_rate_distr.sample(&mut rng);
-                let service_rate = service_rate.max(2.0);
+            experiments::analysis::analyse(&params.results_folder, &output, &comparison)
+        }
+        ("num_cores", Some(args)) => {
+            let mut params = Parameters::from_config(&settings)?;
+            params.apply_args(args)?;
 
-                let size: f64 = size_distr.sample(&mut rng);
-                let size = size.min(100.0).max(1.0) as usize;
+            write_num_cores(&params.results_folder)
+        }
+        (_, args) => {
+            let mut params = Parameters::from_config(&settings)?;
+            if let Some(args) = args {
+                params.apply_args(args)?;
+            }
 
-                service.vnfs.push(VNF {
-                    service_rate,
-                    queue_length,
-                    size,
-                });
+            // Legacy switch from the Config file
+            if matches.subcommand_name().is_none()
+                && settings.get_bool("test_num_cores").unwrap_or(false)
+            {
+                return write_num_cores(&params.results_folder);
+            }
 
-                vnf_id = vnf_id + 1;
+            let resume = args.map_or(false, |args| args.is_present("resume"));
+            if resume {
+                experiments::manifest::resume_seeds(&mut params)?;
             }
 
-            services.push(service);
-        }
+            params.validate()?;
+            params.resolve_seeds();
+
+            if args.map_or(false, |args| args.is_present("dry-run")) {
+                let jobs = experiments::manifest::expand(&params);
+                for job in &jobs {
+                    println!("{:?}", job.folder(&params));
+                }
+                println!("{} jobs", jobs.len());
 
-        let mut used_capacity = 0;
-        for service in &services {
-            for vnf in &service.vnfs {
-                used_capacity = used_capacity + vnf.size;
+                return Ok(());
             }
-        }
 
-        // Filter out some unsolveable problems
-        let total_capacity = 100 * num_servers;
-        if used_capacity <= total_capacity {
-            return services;
+            experiments::run_experiments(&params, manifest, resume)
         }
     }
 }
 
-fn load_topology(topology: &Topology, size: usize) -> (Datacentre, Vec<RoutingTable>) {
-    let file = File::open(format!("topology/{}_{}.dat", topology, size)).unwrap();
-    let reader = BufReader::new(file);
-    let dc: Datacentre = bincode::deserialize_from(reader).unwrap();
-
-    let file = File::open(format!("topology/{}_routing_{}.dat", topology, size)).unwrap();
-    let reader = BufReader::new(file);
-    let rt: Vec<RoutingTable> = bincode::deserialize_from(reader).unwrap();
-
-    (dc, rt)
-}
-
-fn print_population_objectives<X>(
-    folder: &PathBuf,
-    file_name: String,
-    pop: &Vec<Solution<X>>,
-) -> std::io::Result<()> {
-    let mut file = get_file(&folder, &file_name).unwrap();
+/// Reads settings from the config file and any APP_* environment variables.
+/// The default "Config" file is optional, a file given on the command line is not.
+fn load_config(file: Option<&str>) -> Result<Config, ConfigError> {
+    let mut settings = Config::default();
 
-    for ind in pop {
-        let objectives = &ind.objectives;
+    match file {
+        Some(file) => settings.merge(config::File::with_name(file))?,
+        None => settings.merge(config::File::with_name("Config").required(false))?,
+    };
 
-        if objectives.is_feasible() {
-            let objectives = objectives.unwrap();
+    settings.merge(config::Environment::with_prefix("APP"))?;
 
-            for (i, objective) in objectives.iter().enumerate() {
-                write!(file, "{}", objective)?;
-
-                if i < objectives.len() - 1 {
-                    write!(file, ",")?;
-                }
-            }
-        } else {
-            write!(file, "Infeasible")?;
-        }
-
-        writeln!(file)?;
-    }
-
-    Ok(())
+    Ok(settings)
 }
 
-fn get_file(folder: &PathBuf, file: &str) -> std::io::Result<BufWriter<File>> {
-    println!("{:?}", folder);
-
-    fs::create_dir_all(folder).unwrap();
-    let path = folder.join(file);
+fn write_num_cores(results_folder: &Path) -> Result<(), Box<dyn Error>> {
+    let mut file = experiments::get_file(&results_folder.to_path_buf(), "NumCores.txt")?;
+    writeln!(&mut file, "{}", num_cpus::get())?;
 
-    let file = OpenOptions::new().write(true).create(true).open(path)?;
-
-    Ok(BufWriter::new(file))
+    Ok(())
 }
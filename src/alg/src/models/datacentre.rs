@@ -1,9 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::fmt::Display;
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
 
 pub type NodeID = usize;
 
-#[derive(Copy, Clone)]
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
 pub enum Topology {
     FatTree,
     LeafSpine,
@@ -20,14 +20,119 @@ impl Display for Topology {
     }
 }
 
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace("_", "").as_str() {
+            "dcell" => Ok(Topology::DCell),
+            "fattree" => Ok(Topology::FatTree),
+            "leafspine" => Ok(Topology::LeafSpine),
+            _ => Err(format!(
+                "Unknown topology '{}', expected one of FatTree, LeafSpine or DCell",
+                s
+            )),
+        }
+    }
+}
+
+impl Topology {
+    /**
+     * Builds the datacentre of this topology whose number of servers is closest
+     * to the requested number of servers.
+     **/
+    pub fn build(&self, num_servers: usize) -> Datacentre {
+        match self {
+            Topology::FatTree => FatTree::new(FatTree::ports_for(num_servers)),
+            Topology::LeafSpine => {
+                let num_ports = LeafSpine::ports_for(num_servers);
+                LeafSpine::new(num_ports, num_ports / 2)
+            }
+            Topology::DCell => {
+                let (num_ports, level) = DCell::params_for(num_servers);
+                DCell::new(num_ports, level)
+            }
+        }
+    }
+}
+
+// Returns the parameter whose size is closest to the target, preferring the larger
+// topology when two are equally close. `size` must be increasing in the parameter.
+fn closest_param(
+    target: usize,
+    params: impl Iterator<Item = usize>,
+    size: impl Fn(usize) -> usize,
+) -> (usize, usize) {
+    let mut best = None;
+
+    for param in params {
+        let num_servers = size(param);
+        let diff = (num_servers as i64 - target as i64).abs();
+
+        match best {
+            Some((_, _, best_diff)) if diff > best_diff => break,
+            _ => best = Some((param, num_servers, diff)),
+        }
+    }
+
+    let (param, num_servers, _) = best.unwrap();
+    (param, num_servers)
+}
+
+/// Layer of a component, switches by the number of hops to the nearest server
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
+pub enum Tier {
+    Server,
+    Edge,
+    Aggregation,
+    Core,
+}
+
+/**
+ * A link between two components, its bandwidth in packets per unit of time like the rates
+ * of the services and its propagation delay. Links are unlimited with no delay by default.
+ **/
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct Link {
+    pub bandwidth: f64,
+    pub latency: f64,
+}
+
+impl Default for Link {
+    fn default() -> Self {
+        Link {
+            bandwidth: std::f64::INFINITY,
+            latency: 0.0,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Datacentre {
     pub graph: Vec<Vec<NodeID>>,
     pub num_ports: usize,
     pub num_servers: usize,
+
+    /// The link to each neighbour in `graph`, in the same order
+    pub links: Vec<Vec<Link>>,
 }
 
 impl Datacentre {
+    /// A datacentre whose links are all the default link
+    pub fn new(graph: Vec<Vec<NodeID>>, num_ports: usize, num_servers: usize) -> Datacentre {
+        let links = graph
+            .iter()
+            .map(|neighbours| vec![Link::default(); neighbours.len()])
+            .collect();
+
+        Datacentre {
+            graph,
+            num_ports,
+            num_servers,
+            links,
+        }
+    }
+
     pub fn is_server(&self, node_id: usize) -> bool {
         node_id < self.num_servers
     }
@@ -35,10 +140,69 @@ impl Datacentre {
     pub fn num_components(&self) -> usize {
         self.graph.len()
     }
+
+    /**
+     * The tier of each component. Switches linked to a server are edge switches, those
+     * linked to an edge switch are aggregation switches and the rest are core switches, so
+     * the spine of a leaf-spine is its aggregation tier.
+     **/
+    pub fn tiers(&self) -> Vec<Tier> {
+        let mut hops = vec![std::usize::MAX; self.num_components()];
+        let mut queue: VecDeque<NodeID> = (0..self.num_servers).collect();
+
+        for server in 0..self.num_servers {
+            hops[server] = 0;
+        }
+
+        while let Some(node) = queue.pop_front() {
+            for &next in &self.graph[node] {
+                if hops[next] == std::usize::MAX {
+                    hops[next] = hops[node] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        hops.iter()
+            .map(|&hops| match hops {
+                0 => Tier::Server,
+                1 => Tier::Edge,
+                2 => Tier::Aggregation,
+                _ => Tier::Core,
+            })
+            .collect()
+    }
+
+    /// The link from `from` to its neighbour `to`
+    pub fn link(&self, from: NodeID, to: NodeID) -> Option<&Link> {
+        let position = self.graph[from].iter().position(|&next| next == to)?;
+
+        Some(&self.links[from][position])
+    }
+
+    /// Sets every link to what `link` gives for the tiers of its two ends
+    pub fn set_links(&mut self, link: impl Fn(Tier, Tier) -> Link) {
+        let tiers = self.tiers();
+
+        for (from, neighbours) in self.graph.iter().enumerate() {
+            for (position, &to) in neighbours.iter().enumerate() {
+                self.links[from][position] = link(tiers[from], tiers[to]);
+            }
+        }
+    }
 }
 
 pub struct FatTree;
 impl FatTree {
+    /**
+     * The (even) number of ports per switch that gives the number of servers
+     * closest to `num_servers`, i.e. k^3 / 4 servers for k ports.
+     **/
+    pub fn ports_for(num_servers: usize) -> usize {
+        let (num_ports, _) = closest_param(num_servers, (1..).map(|k| 2 * k), |k| k.pow(3) / 4);
+        num_ports
+    }
+
     pub fn new(num_ports: usize) -> Datacentre {
         let num_servers = num_ports.pow(3) / 4;
         let num_edges = num_ports * (num_ports / 2);
@@ -100,16 +264,21 @@ impl FatTree {
             }
         }
 
-        Datacentre {
-            graph,
-            num_ports,
-            num_servers,
-        }
+        Datacentre::new(graph, num_ports, num_servers)
     }
 }
 
 pub struct LeafSpine;
 impl LeafSpine {
+    /**
+     * The (even) number of ports per switch that gives the number of servers
+     * closest to `num_servers` when half of each leaf's ports connect to the spine.
+     **/
+    pub fn ports_for(num_servers: usize) -> usize {
+        let (num_ports, _) = closest_param(num_servers, (1..).map(|k| 2 * k), |k| (k - k / 2) * k);
+        num_ports
+    }
+
     pub fn new(num_ports: usize, num_spine: usize) -> Datacentre {
         let num_leaf = num_ports;
         let num_servers = (num_ports - num_spine) * num_leaf;
@@ -148,16 +317,39 @@ impl LeafSpine {
             }
         }
 
-        Datacentre {
-            graph,
-            num_ports,
-            num_servers,
-        }
+        Datacentre::new(graph, num_ports, num_servers)
     }
 }
 
 pub struct DCell;
 impl DCell {
+    pub const MAX_PORTS: usize = 16;
+
+    /**
+     * The (number of ports, level) of the DCell with the number of servers closest
+     * to `num_servers`. DCells are built from small commodity switches so the number
+     * of ports is capped at `MAX_PORTS`, otherwise a level 1 DCell with very large
+     * switches would almost always be the closest match.
+     **/
+    pub fn params_for(num_servers: usize) -> (usize, usize) {
+        let mut best = (2, 1);
+        let mut best_diff = std::usize::MAX;
+
+        for level in 1..=3 {
+            let (num_ports, size) = closest_param(num_servers, 2..=DCell::MAX_PORTS, |n| {
+                DCell::num_servers(level, n)
+            });
+            let diff = (size as i64 - num_servers as i64).abs() as usize;
+
+            if diff < best_diff {
+                best = (num_ports, level);
+                best_diff = diff;
+            }
+        }
+
+        best
+    }
+
     pub fn new(num_ports: usize, level: usize) -> Datacentre {
         let num_servers = DCell::num_servers(level, num_ports);
         let num_switches = num_servers / num_ports;
@@ -166,11 +358,7 @@ impl DCell {
         let mut graph = vec![Vec::new(); num_nodes];
         DCell::build_dcells(&mut graph, Vec::new(), num_ports, level, num_servers);
 
-        Datacentre {
-            graph,
-            num_ports,
-            num_servers,
-        }
+        Datacentre::new(graph, num_ports, num_servers)
     }
 
     fn build_dcells(
@@ -279,3 +467,88 @@ impl DCell {
         tuple
     }
 }
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topology_from_str() {
+        assert_eq!("FatTree".parse::<Topology>(), Ok(Topology::FatTree));
+        assert_eq!("leaf_spine".parse::<Topology>(), Ok(Topology::LeafSpine));
+        assert_eq!("DCELL".parse::<Topology>(), Ok(Topology::DCell));
+        assert!("torus".parse::<Topology>().is_err());
+    }
+
+    #[test]
+    fn test_fat_tree_ports_for() {
+        assert_eq!(FatTree::ports_for(16), 4);
+        assert_eq!(FatTree::ports_for(16000), 40);
+        assert_eq!(FatTree::ports_for(1000), 16);
+
+        let dc = Topology::FatTree.build(16000);
+        assert_eq!(dc.num_servers, 16000);
+        assert_eq!(dc.num_ports, 40);
+    }
+
+    #[test]
+    fn test_leaf_spine_ports_for() {
+        assert_eq!(LeafSpine::ports_for(8), 4);
+        assert_eq!(LeafSpine::ports_for(500), 32);
+
+        let dc = Topology::LeafSpine.build(500);
+        assert_eq!(dc.num_servers, 512);
+        assert_eq!(dc.num_components(), 512 + 32 + 16);
+    }
+
+    #[test]
+    fn test_dcell_params_for() {
+        assert_eq!(DCell::params_for(20), (4, 1));
+        assert_eq!(DCell::params_for(420), (4, 2));
+        assert_eq!(DCell::params_for(16000), (11, 2));
+
+        let dc = Topology::DCell.build(420);
+        assert_eq!(dc.num_servers, 420);
+        assert_eq!(dc.num_ports, 4);
+    }
+
+    #[test]
+    fn test_tiers() {
+        let count = |tiers: &Vec<Tier>, tier| tiers.iter().filter(|&&t| t == tier).count();
+
+        let tiers = FatTree::new(4).tiers();
+        assert_eq!(count(&tiers, Tier::Server), 16);
+        assert_eq!(count(&tiers, Tier::Edge), 8);
+        assert_eq!(count(&tiers, Tier::Aggregation), 8);
+        assert_eq!(count(&tiers, Tier::Core), 4);
+
+        let tiers = LeafSpine::new(4, 2).tiers();
+        assert_eq!(count(&tiers, Tier::Edge), 4);
+        assert_eq!(count(&tiers, Tier::Aggregation), 2);
+
+        let tiers = DCell::new(4, 1).tiers();
+        assert_eq!(count(&tiers, Tier::Edge), 5);
+        assert_eq!(count(&tiers, Tier::Core), 0);
+    }
+
+    #[test]
+    fn test_links() {
+        let mut dc = FatTree::new(4);
+        assert_eq!(dc.link(0, 16), Some(&Link::default()));
+        assert_eq!(dc.link(0, 1), None);
+
+        let slow = Link {
+            bandwidth: 10.0,
+            latency: 0.5,
+        };
+        // Links to the core switches
+        dc.set_links(|a, b| match (a, b) {
+            (Tier::Core, _) | (_, Tier::Core) => slow,
+            _ => Link::default(),
+        });
+        assert_eq!(dc.link(0, 16), Some(&Link::default()));
+        assert_eq!(dc.link(24, 32), Some(&slow));
+        assert_eq!(dc.link(32, 24), Some(&slow));
+    }
+}
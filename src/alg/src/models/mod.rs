@@ -4,9 +4,14 @@ pub mod routing;
 pub mod service;
 pub mod utilisation_model;
 pub mod heuristic_model;
+pub mod power;
+pub mod queues;
+pub mod resources;
+pub mod simulator;
 
 use std::collections::{BTreeMap, VecDeque};
 
+use crate::models::queues::QueueDiscipline;
 use crate::models::service::{Service, ServiceID};
 use crate::operators::mapping::{NodeType, RouteNode};
 
@@ -98,9 +103,11 @@ fn set_all_pl(
     sw_srv_rate: f64,
     sw_queue_length: usize,
     servers: &mut Vec<Server>,
+    sw_queue: &dyn QueueDiscipline,
+    vnf_queue: &dyn QueueDiscipline,
 ) {
     for i in 0..sw_pl.len() {
-        sw_pl[i] = calc_pl(sw_arr[i], sw_srv_rate, sw_queue_length);
+        sw_pl[i] = sw_queue.packet_loss(sw_arr[i], sw_srv_rate, sw_queue_length);
     }
 
     for i in 0..servers.len() {
@@ -112,64 +119,11 @@ fn set_all_pl(
 
             let vnf = &services[s_id].vnfs[pos];
             vnf_info.packet_losses =
-                calc_pl(vnf_info.arrival_rate, vnf.service_rate, vnf.queue_length);
+                vnf_queue.packet_loss(vnf_info.arrival_rate, vnf.service_rate, vnf.queue_length);
         }
     }
 }
 
-fn calc_pl(arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
-    let queue_length = queue_length as f64;
-    let rho = arrival_rate / service_rate;
-
-    if rho == 1. {
-        1. / (queue_length + 1.)
-    } else {
-        ((1. - rho) * rho.powf(queue_length)) / (1. - rho.powf(queue_length + 1.))
-    }
-}
-
-fn calc_wt(arrival_rate: f64, service_rate: f64, queue_length: usize, packet_loss: f64) -> f64 {
-    let queue_length = queue_length as f64;
-
-    let rho = arrival_rate / service_rate;
-
-    if arrival_rate == 0. {
-        return 0.;
-    }
-
-    let num_in_system = if rho != 1.0 {
-        let a = rho
-            * (1.0 - (queue_length + 1.0) * rho.powf(queue_length)
-                + queue_length * rho.powf(queue_length + 1.0));
-        let b = (1.0 - rho) * (1.0 - rho.powf(queue_length + 1.0));
-
-        a / b
-    } else {
-        queue_length / 2.0
-    };
-
-    let ar = arrival_rate * (1.0 - packet_loss);
-
-    num_in_system / ar
-}
-
-fn calc_busy(arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
-    if arrival_rate > 0.0 && service_rate == 0.0 {
-        return std::f64::INFINITY;
-    }
-
-    let rho = arrival_rate / service_rate;
-    let k = queue_length as f64;
-
-    let p_empty = if arrival_rate != service_rate {
-        (1.0 - rho) / (1.0 - rho.powf(k + 1.0))
-    } else {
-        1.0 / (k + 1.0)
-    };
-
-    1.0 - p_empty
-}
-
 pub fn get_metrics(
     rn: &RouteNode,
     service_id: usize,
@@ -217,8 +171,9 @@ pub fn set_arrival_rate<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::resources::Resources;
     use crate::models::routing::RoutingTable;
-    use crate::models::service::{Service, VNF};
+    use crate::models::service::{Service, Sla, VNF};
     use crate::operators::mapping::{find_routes, RouteNode};
 
     #[test]
@@ -355,12 +310,13 @@ mod tests {
             id: 0,
             prod_rate: 10.0,
             vnfs: Vec::new(),
+            sla: Sla::default(),
         };
 
         let mut vnfs = Vec::new();
         for _ in 0..length {
             let vnf = VNF {
-                size: 100,
+                size: Resources::scalar(100),
                 queue_length: 20,
                 service_rate: 10.0,
             };
@@ -0,0 +1,324 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use crate::models::datacentre::{Datacentre, NodeID, Tier};
+
+/// Power drawn by a component with `num_ports` connected ports busy for `utilisation` of the time
+pub trait PowerCurve: Send + Sync + std::fmt::Debug {
+    fn power(&self, utilisation: f64, num_ports: usize) -> f64;
+}
+
+/// Power rises linearly from idle to fully utilised
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Linear {
+    pub idle: f64,
+    pub active: f64,
+}
+
+impl PowerCurve for Linear {
+    fn power(&self, utilisation: f64, _num_ports: usize) -> f64 {
+        (self.active * utilisation) + (self.idle * (1.0 - utilisation))
+    }
+}
+
+/**
+ * Power rises with the utilisation to the power of `exponent`. A server scaling its
+ * frequency with its load (DVFS) draws power roughly cubic in the frequency.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Polynomial {
+    pub idle: f64,
+    pub active: f64,
+    pub exponent: f64,
+}
+
+impl PowerCurve for Polynomial {
+    fn power(&self, utilisation: f64, _num_ports: usize) -> f64 {
+        self.idle + (self.active - self.idle) * utilisation.powf(self.exponent)
+    }
+}
+
+/// A switch's chassis draws fixed power and each of its ports rises linearly with the load
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerPort {
+    pub chassis: f64,
+    pub idle_port: f64,
+    pub active_port: f64,
+}
+
+impl PowerCurve for PerPort {
+    fn power(&self, utilisation: f64, num_ports: usize) -> f64 {
+        let port = (self.active_port * utilisation) + (self.idle_port * (1.0 - utilisation));
+
+        self.chassis + num_ports as f64 * port
+    }
+}
+
+/**
+ * The power curves that can be chosen in the config, written as `linear:IDLE:ACTIVE`,
+ * `cubic:IDLE:ACTIVE`, `polynomial:IDLE:ACTIVE:EXPONENT` or `ports:CHASSIS:IDLE:ACTIVE`
+ * with the power of each port.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    Linear(Linear),
+    Polynomial(Polynomial),
+    PerPort(PerPort),
+}
+
+impl Curve {
+    pub fn linear(idle: f64, active: f64) -> Curve {
+        Curve::Linear(Linear { idle, active })
+    }
+
+    pub fn build(&self) -> Arc<dyn PowerCurve> {
+        match *self {
+            Curve::Linear(curve) => Arc::new(curve),
+            Curve::Polynomial(curve) => Arc::new(curve),
+            Curve::PerPort(curve) => Arc::new(curve),
+        }
+    }
+}
+
+impl Display for Curve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Curve::Linear(curve) => write!(f, "linear:{}:{}", curve.idle, curve.active),
+            Curve::Polynomial(curve) if curve.exponent == 3.0 => {
+                write!(f, "cubic:{}:{}", curve.idle, curve.active)
+            }
+            Curve::Polynomial(curve) => write!(
+                f,
+                "polynomial:{}:{}:{}",
+                curve.idle, curve.active, curve.exponent
+            ),
+            Curve::PerPort(curve) => write!(
+                f,
+                "ports:{}:{}:{}",
+                curve.chassis, curve.idle_port, curve.active_port
+            ),
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Unknown power curve '{}', expected linear:IDLE:ACTIVE, cubic:IDLE:ACTIVE, polynomial:IDLE:ACTIVE:EXPONENT or ports:CHASSIS:IDLE:ACTIVE",
+                s
+            )
+        };
+
+        let lower = s.to_lowercase();
+        let mut parts = lower.split(':').map(|part| part.trim());
+        let kind = parts.next().unwrap();
+
+        let mut values = Vec::new();
+        for part in parts {
+            match part.parse::<f64>() {
+                Ok(value) if value >= 0.0 => values.push(value),
+                _ => return Err(invalid()),
+            }
+        }
+
+        match (kind, values.as_slice()) {
+            ("linear", &[idle, active]) => Ok(Curve::linear(idle, active)),
+            ("cubic", &[idle, active]) => Ok(Curve::Polynomial(Polynomial {
+                idle,
+                active,
+                exponent: 3.0,
+            })),
+            ("polynomial", &[idle, active, exponent]) if exponent > 0.0 => {
+                Ok(Curve::Polynomial(Polynomial {
+                    idle,
+                    active,
+                    exponent,
+                }))
+            }
+            ("ports", &[chassis, idle_port, active_port]) => Ok(Curve::PerPort(PerPort {
+                chassis,
+                idle_port,
+                active_port,
+            })),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+serialize_display!(Curve);
+
+/**
+ * What a component with no traffic draws, written as `never` when components are always
+ * on, or `unused` or `unused:POWER` when they sleep drawing the given power, 0 by default.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SleepPolicy {
+    Never,
+    Unused(f64),
+}
+
+impl Default for SleepPolicy {
+    fn default() -> Self {
+        SleepPolicy::Unused(0.0)
+    }
+}
+
+impl Display for SleepPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SleepPolicy::Never => write!(f, "never"),
+            SleepPolicy::Unused(power) if *power == 0.0 => write!(f, "unused"),
+            SleepPolicy::Unused(power) => write!(f, "unused:{}", power),
+        }
+    }
+}
+
+impl FromStr for SleepPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        let mut parts = lower.splitn(2, ':').map(|part| part.trim());
+
+        match (parts.next().unwrap(), parts.next()) {
+            ("never", None) => Ok(SleepPolicy::Never),
+            ("unused", None) => Ok(SleepPolicy::Unused(0.0)),
+            ("unused", Some(power)) => match power.parse::<f64>() {
+                Ok(power) if power >= 0.0 => Ok(SleepPolicy::Unused(power)),
+                _ => Err(format!("Invalid sleep power in '{}'", s)),
+            },
+            _ => Err(format!(
+                "Unknown sleep policy '{}', expected never, unused or unused:POWER",
+                s
+            )),
+        }
+    }
+}
+
+serialize_display!(SleepPolicy);
+
+/**
+ * Power drawn by each component of a datacentre, with a curve for the servers and for each
+ * tier of switches. Only the utilisation of a component is known, so a component with no
+ * traffic is the one that can sleep.
+ **/
+#[derive(Debug, Clone)]
+pub struct PowerModel {
+    tiers: Vec<Tier>,
+    num_ports: Vec<usize>,
+    server: Arc<dyn PowerCurve>,
+    edge: Arc<dyn PowerCurve>,
+    aggregation: Arc<dyn PowerCurve>,
+    core: Arc<dyn PowerCurve>,
+    sleep: SleepPolicy,
+}
+
+impl PowerModel {
+    pub fn new(
+        dc: &Datacentre,
+        server: &Curve,
+        edge: &Curve,
+        aggregation: &Curve,
+        core: &Curve,
+        sleep: SleepPolicy,
+    ) -> PowerModel {
+        PowerModel {
+            tiers: dc.tiers(),
+            num_ports: dc.graph.iter().map(|links| links.len()).collect(),
+            server: server.build(),
+            edge: edge.build(),
+            aggregation: aggregation.build(),
+            core: core.build(),
+            sleep,
+        }
+    }
+
+    /// The same linear curve for every component, switched off when unused
+    pub fn linear(dc: &Datacentre, active_cost: f64, idle_cost: f64) -> PowerModel {
+        let curve = Curve::linear(idle_cost, active_cost);
+
+        PowerModel::new(dc, &curve, &curve, &curve, &curve, SleepPolicy::default())
+    }
+
+    pub fn power(&self, component: NodeID, utilisation: f64) -> f64 {
+        if utilisation == 0.0 {
+            if let SleepPolicy::Unused(power) = self.sleep {
+                return power;
+            }
+        }
+
+        let curve = match self.tiers[component] {
+            Tier::Server => &self.server,
+            Tier::Edge => &self.edge,
+            Tier::Aggregation => &self.aggregation,
+            Tier::Core => &self.core,
+        };
+
+        curve.power(utilisation, self.num_ports[component])
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::datacentre::FatTree;
+
+    #[test]
+    fn test_curves() {
+        let linear = Curve::linear(10.0, 30.0).build();
+        assert_eq!(linear.power(0.5, 4), 20.0);
+
+        let cubic: Curve = "cubic:10:30".parse().unwrap();
+        assert_eq!(cubic.build().power(0.5, 4), 12.5);
+        assert_eq!(cubic.build().power(1.0, 4), 30.0);
+
+        let ports: Curve = "ports:50:1:3".parse().unwrap();
+        assert_eq!(ports.build().power(0.5, 4), 58.0);
+
+        for name in &[
+            "linear:10:30",
+            "cubic:10:30",
+            "polynomial:10:30:2.5",
+            "ports:50:1:3",
+        ] {
+            assert_eq!(name.parse::<Curve>().unwrap().to_string(), *name);
+        }
+        assert!("linear:10".parse::<Curve>().is_err());
+        assert!("linear:-1:30".parse::<Curve>().is_err());
+        assert!("quadratic:10:30".parse::<Curve>().is_err());
+    }
+
+    #[test]
+    fn test_sleep_policy() {
+        assert_eq!("never".parse(), Ok(SleepPolicy::Never));
+        assert_eq!("Unused".parse(), Ok(SleepPolicy::Unused(0.0)));
+        assert_eq!("unused:2.5".parse(), Ok(SleepPolicy::Unused(2.5)));
+        assert!("sometimes".parse::<SleepPolicy>().is_err());
+        assert_eq!(SleepPolicy::Unused(2.5).to_string(), "unused:2.5");
+    }
+
+    #[test]
+    fn test_power_model() {
+        let dc = FatTree::new(4);
+        let model = PowerModel::linear(&dc, 30.0, 10.0);
+        assert_eq!(model.power(0, 0.0), 0.0);
+        assert_eq!(model.power(0, 0.5), 20.0);
+
+        // Servers, edge, aggregation and core switches of a fat tree
+        let model = PowerModel::new(
+            &dc,
+            &"cubic:10:30".parse().unwrap(),
+            &Curve::linear(1.0, 1.0),
+            &Curve::linear(2.0, 2.0),
+            &"ports:5:1:1".parse().unwrap(),
+            SleepPolicy::Never,
+        );
+        assert_eq!(model.power(0, 0.0), 10.0);
+        assert_eq!(model.power(16, 0.5), 1.0);
+        assert_eq!(model.power(24, 0.5), 2.0);
+        assert_eq!(model.power(32, 0.5), 9.0);
+    }
+}
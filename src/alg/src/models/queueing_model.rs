@@ -1,8 +1,11 @@
-use crate::models::datacentre::Datacentre;
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use crate::models::datacentre::{Datacentre, NodeID};
+use crate::models::power::PowerModel;
+use crate::models::queues::{QueueDiscipline, MM1K};
 use crate::models::service::{Service, ServiceID};
 use crate::models::{
-    calc_busy, calc_ma, calc_wt, get_metrics, iterate_route, set_all_arrival_rates, set_all_pl,
-    Server, VnfMetrics,
+    calc_ma, get_metrics, iterate_route, set_all_arrival_rates, set_all_pl, Server, VnfMetrics,
 };
 use crate::operators::mapping::{NodeType, RouteNode};
 
@@ -15,8 +18,130 @@ pub struct QueueingModel<'a> {
     sw_ql: usize,
     pub target_acc: f64,
     pub converged_iterations: usize,
-    active_cost: f64,
-    idle_cost: f64,
+
+    /// Iterations after which the model gives up, 0 for no limit
+    pub max_iterations: usize,
+
+    /// Weight of each new arrival rate in (0, 1], 0 for a cumulative moving average
+    pub relaxation: f64,
+
+    /// Queues of the switches and of the VNFs, M/M/1/K unless set otherwise
+    pub switch_queue: Arc<dyn QueueDiscipline>,
+    pub vnf_queue: Arc<dyn QueueDiscipline>,
+
+    /// Power drawn by each component, linear in its utilisation unless set otherwise
+    pub power: Arc<PowerModel>,
+}
+
+pub const DEFAULT_MAX_ITERATIONS: usize = 1000;
+
+// Iterations in a row the largest change in arrival rate has to grow to count as diverging
+const DIVERGING_ITERATIONS: usize = 50;
+
+/// Why the fixed-point iteration of the arrival rates stopped before converging
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConvergenceError {
+    MaxIterations { iterations: usize, max_diff: f64 },
+    Diverged { iterations: usize, max_diff: f64 },
+}
+
+impl Display for ConvergenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConvergenceError::MaxIterations {
+                iterations,
+                max_diff,
+            } => write!(
+                f,
+                "the queueing model had not converged after {} iterations (max diff {})",
+                iterations, max_diff
+            ),
+            ConvergenceError::Diverged {
+                iterations,
+                max_diff,
+            } => write!(
+                f,
+                "the queueing model diverged after {} iterations (max diff {})",
+                iterations, max_diff
+            ),
+        }
+    }
+}
+
+impl Error for ConvergenceError {}
+
+/**
+ * Everything the queueing model finds about a placement rather than only the objectives,
+ * to find its bottlenecks. The components are indexed by their id in the datacentre, so
+ * the servers come first, and only the VNFs that were placed are listed.
+ **/
+#[derive(Debug, Clone)]
+pub struct QueueingReport {
+    pub latencies: Vec<f64>,
+    pub packet_losses: Vec<f64>,
+    pub energy: f64,
+    pub components: Vec<ComponentReport>,
+    pub vnfs: Vec<VnfReport>,
+
+    /// Links that carry packets, in each direction
+    pub links: Vec<LinkReport>,
+
+    /// Fixed-point iterations until the arrival rates converged
+    pub iterations: usize,
+
+    /// Largest change of an arrival rate in the last iteration
+    pub max_diff: f64,
+
+    /// Set if the arrival rates did not converge, the rest is then the state it stopped in
+    pub error: Option<ConvergenceError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentReport {
+    pub id: NodeID,
+    pub is_server: bool,
+    pub arrival_rate: f64,
+
+    /// Probability the component is busy, for a server either its switch or a VNF on it
+    pub utilisation: f64,
+    pub packet_loss: f64,
+
+    /// Mean number of packets in the switch's queue
+    pub queue_occupancy: f64,
+    pub energy: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VnfReport {
+    pub server: NodeID,
+    pub service: ServiceID,
+    pub stage: usize,
+    pub arrival_rate: f64,
+    pub utilisation: f64,
+    pub packet_loss: f64,
+    pub queue_occupancy: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkReport {
+    pub from: NodeID,
+    pub to: NodeID,
+
+    /// Packets per unit of time sent over the link
+    pub load: f64,
+
+    /// Share of the link's bandwidth used, above 1 when it is overloaded
+    pub utilisation: f64,
+}
+
+// Mean arrival rates and packet losses once the fixed-point iteration has converged
+struct SteadyState {
+    servers: Vec<Server>,
+    sw_arr: Vec<f64>,
+    sw_pl: Vec<f64>,
+    iterations: usize,
+    max_diff: f64,
+    error: Option<ConvergenceError>,
 }
 
 impl<'a> QueueingModel<'a> {
@@ -35,8 +160,11 @@ impl<'a> QueueingModel<'a> {
             sw_ql,
             target_acc: accuracy,
             converged_iterations,
-            active_cost,
-            idle_cost,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            relaxation: 0.0,
+            switch_queue: Arc::new(MM1K),
+            vnf_queue: Arc::new(MM1K),
+            power: Arc::new(PowerModel::linear(dc, active_cost, idle_cost)),
         }
     }
 
@@ -44,7 +172,154 @@ impl<'a> QueueingModel<'a> {
         &self,
         services: &Vec<Service>,
         routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+    ) -> Result<(Vec<f64>, Vec<f64>, f64), ConvergenceError> {
+        let state = self.steady_state(services, routes);
+        if let Some(error) = state.error {
+            return Err(error);
+        }
+
+        Ok(self.objectives(services, routes, &state))
+    }
+
+    /**
+     * Evaluates the placement like `evaluate`, also giving the utilisation of each link in
+     * the order of `Datacentre::links`.
+     **/
+    pub fn evaluate_with_links(
+        &self,
+        services: &Vec<Service>,
+        routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+    ) -> Result<(Vec<f64>, Vec<f64>, f64, Vec<Vec<f64>>), ConvergenceError> {
+        let state = self.steady_state(services, routes);
+        if let Some(error) = state.error {
+            return Err(error);
+        }
+
+        let (latencies, packet_losses, energy) = self.objectives(services, routes, &state);
+        let utilisations = self.link_utilisations(&self.link_loads(services, routes, &state));
+
+        Ok((latencies, packet_losses, energy, utilisations))
+    }
+
+    // Latency and packet loss of each service and the total energy
+    fn objectives(
+        &self,
+        services: &Vec<Service>,
+        routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+        state: &SteadyState,
     ) -> (Vec<f64>, Vec<f64>, f64) {
+        let (service_latency, service_pl) = self.service_metrics(services, routes, &state);
+
+        // Calculate energy consumption
+        let energy = self.get_energy_consumption(
+            services,
+            &state.servers,
+            &state.sw_arr,
+            self.sw_sr,
+            self.sw_ql,
+        );
+
+        (service_latency, service_pl, energy)
+    }
+
+    /// Evaluates the placement like `evaluate`, also reporting each component and VNF
+    pub fn evaluate_detailed(
+        &self,
+        services: &Vec<Service>,
+        routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+    ) -> QueueingReport {
+        let state = self.steady_state(services, routes);
+        let (latencies, packet_losses) = self.service_metrics(services, routes, &state);
+
+        let components: Vec<ComponentReport> = (0..self.dc.num_components())
+            .map(|i| {
+                let utilisation = self.get_utilisation(
+                    i,
+                    services,
+                    &state.servers,
+                    &state.sw_arr,
+                    self.sw_sr,
+                    self.sw_ql,
+                );
+
+                ComponentReport {
+                    id: i,
+                    is_server: self.dc.is_server(i),
+                    arrival_rate: state.sw_arr[i],
+                    utilisation,
+                    packet_loss: state.sw_pl[i],
+                    queue_occupancy: self.switch_queue.num_in_system(
+                        state.sw_arr[i],
+                        self.sw_sr,
+                        self.sw_ql,
+                    ),
+                    energy: self.power.power(i, utilisation),
+                }
+            })
+            .collect();
+
+        let mut vnfs = Vec::new();
+        for (i, server) in state.servers.iter().enumerate() {
+            for (&(s_id, stage), metrics) in server {
+                let vnf = &services[s_id].vnfs[stage];
+
+                vnfs.push(VnfReport {
+                    server: i,
+                    service: s_id,
+                    stage,
+                    arrival_rate: metrics.arrival_rate,
+                    utilisation: self.vnf_queue.busy(
+                        metrics.arrival_rate,
+                        vnf.service_rate,
+                        vnf.queue_length,
+                    ),
+                    packet_loss: metrics.packet_losses,
+                    queue_occupancy: self.vnf_queue.num_in_system(
+                        metrics.arrival_rate,
+                        vnf.service_rate,
+                        vnf.queue_length,
+                    ),
+                });
+            }
+        }
+
+        // Summed in the same order as get_energy_consumption
+        let energy = components.iter().map(|component| component.energy).sum();
+
+        let loads = self.link_loads(services, routes, &state);
+        let utilisations = self.link_utilisations(&loads);
+        let mut links = Vec::new();
+        for (from, neighbours) in self.dc.graph.iter().enumerate() {
+            for (position, &to) in neighbours.iter().enumerate() {
+                if loads[from][position] > 0.0 {
+                    links.push(LinkReport {
+                        from,
+                        to,
+                        load: loads[from][position],
+                        utilisation: utilisations[from][position],
+                    });
+                }
+            }
+        }
+
+        QueueingReport {
+            latencies,
+            packet_losses,
+            energy,
+            components,
+            vnfs,
+            links,
+            iterations: state.iterations,
+            max_diff: state.max_diff,
+            error: state.error,
+        }
+    }
+
+    fn steady_state(
+        &self,
+        services: &Vec<Service>,
+        routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+    ) -> SteadyState {
         let mut servers_mean: Vec<Server> = vec![Server::new(); self.dc.num_servers];
         let mut servers_temp: Vec<Server> = vec![Server::new(); self.dc.num_servers];
 
@@ -67,9 +342,22 @@ impl<'a> QueueingModel<'a> {
         // Calculate arrival rate
         let mut num_iterations = 0;
         let mut num_below = 0;
-        let mut max_diff: f64;
+        let mut max_diff: f64 = 0.0;
+        let mut num_growing = 0;
+        let mut error = None;
 
         while num_below < self.converged_iterations {
+            if self.max_iterations > 0 && num_iterations >= self.max_iterations {
+                error = Some(ConvergenceError::MaxIterations {
+                    iterations: num_iterations,
+                    max_diff,
+                });
+                break;
+            }
+
+            let last_diff = max_diff;
+            let mut is_finite = true;
+
             // Add arrival rates
             set_all_arrival_rates(
                 &routes,
@@ -87,16 +375,19 @@ impl<'a> QueueingModel<'a> {
                 self.sw_sr,
                 self.sw_ql,
                 &mut servers_temp,
+                &*self.switch_queue,
+                &*self.vnf_queue,
             );
 
-            // Cumulative moving average of arrival rates
+            // Average of the arrival rates
             max_diff = 0.0;
 
             for i in 0..sw_arr_temp.len() {
-                let (new, diff) = calc_ma(sw_arr_mean[i], sw_arr_temp[i], num_iterations);
+                let (new, diff) = self.update(sw_arr_mean[i], sw_arr_temp[i], num_iterations);
                 sw_arr_mean[i] = new;
 
                 max_diff = max_diff.max(diff);
+                is_finite &= diff.is_finite();
             }
 
             for i in 0..servers_temp.len() {
@@ -108,13 +399,32 @@ impl<'a> QueueingModel<'a> {
                         packet_losses: 0.0,
                     });
 
-                    let (new, diff) = calc_ma(vnf_info.arrival_rate, temp, num_iterations);
+                    let (new, diff) = self.update(vnf_info.arrival_rate, temp, num_iterations);
                     vnf_info.arrival_rate = new;
 
                     max_diff = max_diff.max(diff);
+                    is_finite &= diff.is_finite();
                 }
             }
 
+            num_growing = if num_iterations > 0 && max_diff > last_diff {
+                num_growing + 1
+            } else {
+                0
+            };
+
+            if !is_finite || num_growing >= DIVERGING_ITERATIONS {
+                error = Some(ConvergenceError::Diverged {
+                    iterations: num_iterations + 1,
+                    max_diff: if is_finite {
+                        max_diff
+                    } else {
+                        std::f64::INFINITY
+                    },
+                });
+                break;
+            }
+
             if max_diff < self.target_acc {
                 num_below = num_below + 1;
             } else {
@@ -132,8 +442,39 @@ impl<'a> QueueingModel<'a> {
             self.sw_sr,
             self.sw_ql,
             &mut servers_mean,
+            &*self.switch_queue,
+            &*self.vnf_queue,
         );
 
+        SteadyState {
+            servers: servers_mean,
+            sw_arr: sw_arr_mean,
+            sw_pl,
+            iterations: num_iterations,
+            max_diff,
+            error,
+        }
+    }
+
+    // Moves the mean arrival rate towards the latest one, giving the new mean and the change
+    fn update(&self, mean: f64, value: f64, num_iterations: usize) -> (f64, f64) {
+        if self.relaxation > 0.0 {
+            let new = mean + self.relaxation * (value - mean);
+            (new, (new - mean).abs())
+        } else {
+            calc_ma(mean, value, num_iterations)
+        }
+    }
+
+    // Mean latency and packet loss of the instances of each service
+    fn service_metrics(
+        &self,
+        services: &Vec<Service>,
+        routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+        state: &SteadyState,
+    ) -> (Vec<f64>, Vec<f64>) {
+        let (sw_arr_mean, sw_pl, servers_mean) = (&state.sw_arr, &state.sw_pl, &state.servers);
+
         // Calculate service latency + pl
         let mut service_latency = vec![0.0; services.len()];
         let mut service_pl = vec![0.0; services.len()];
@@ -146,11 +487,11 @@ impl<'a> QueueingModel<'a> {
             let mut node_pv = vec![0.0; route.len()]; // Probability of visiting this node
             node_pv[0] = 1.0;
             node_pk[0] = 1.0;
+            let mut link_latency = 0.0;
 
             iterate_route(route, |curr| {
                 let (_, pl) =
-                    get_metrics(&route[curr], *s_id, &sw_arr_mean, &sw_pl, &mut servers_mean)
-                        .unwrap();
+                    get_metrics(&route[curr], *s_id, sw_arr_mean, sw_pl, servers_mean).unwrap();
 
                 node_pl[curr] = pl;
                 node_pk[curr] = node_pk[curr] * (1.0 - node_pl[curr]);
@@ -164,40 +505,101 @@ impl<'a> QueueingModel<'a> {
                 for node in &route[curr].next_nodes {
                     node_pk[*node] += node_pk[curr] / num_next as f64;
                     node_pv[*node] += node_pv[curr] / num_next as f64;
+
+                    // Propagation delay of the links the packets cross
+                    let (from, to) = (route[curr].dc_id(), route[*node].dc_id());
+                    if let Some(link) = self.dc.link(from, to) {
+                        link_latency += link.latency * node_pv[curr] / num_next as f64;
+                    }
                 }
             });
 
-            let mut latency = 0.0;
+            let mut latency = link_latency;
             for i in 1..route.len() {
                 let rn = &route[i];
-                let (arr, _) =
-                    get_metrics(rn, *s_id, &sw_arr_mean, &sw_pl, &mut servers_mean).unwrap();
+                let (arr, _) = get_metrics(rn, *s_id, sw_arr_mean, sw_pl, servers_mean).unwrap();
 
-                let (srv, ql) = match rn.node_type {
-                    NodeType::Component(_) => (self.sw_sr, self.sw_ql),
+                let (queue, srv, ql) = match rn.node_type {
+                    NodeType::Component(_) => (&self.switch_queue, self.sw_sr, self.sw_ql),
                     NodeType::VNF(_, stage) => {
                         let vnf = &services[*s_id].vnfs[stage];
-                        (vnf.service_rate, vnf.queue_length)
+                        (&self.vnf_queue, vnf.service_rate, vnf.queue_length)
                     }
                 };
 
-                latency = latency + (calc_wt(arr, srv, ql, node_pl[i]) * node_pv[i]);
+                let wt = queue.waiting_time(arr, srv, ql, node_pl[i]);
+                latency = latency + (wt * node_pv[i]);
             }
 
             service_latency[*s_id] = calc_ma(service_latency[*s_id], latency, s_count[*s_id]).0;
             s_count[*s_id] += 1;
         }
 
-        // Calculate energy consumption
-        let energy = self.get_energy_consumption(
-            services,
-            &servers_mean,
-            &sw_arr_mean,
-            self.sw_sr,
-            self.sw_ql,
-        );
+        (service_latency, service_pl)
+    }
 
-        (service_latency, service_pl, energy)
+    // Packets per unit of time sent over each link, what leaves a node after its losses
+    fn link_loads(
+        &self,
+        services: &Vec<Service>,
+        routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+        state: &SteadyState,
+    ) -> Vec<Vec<f64>> {
+        let mut loads: Vec<Vec<f64>> = self
+            .dc
+            .graph
+            .iter()
+            .map(|neighbours| vec![0.0; neighbours.len()])
+            .collect();
+
+        let mut num_instances = vec![0; services.len()];
+        for (s_id, _) in routes {
+            num_instances[*s_id] += 1;
+        }
+
+        for (s_id, route) in routes {
+            let mut arrs = vec![0.0; route.len()];
+            arrs[0] = services[*s_id].prod_rate / num_instances[*s_id] as f64;
+
+            iterate_route(route, |curr| {
+                let pl = get_metrics(
+                    &route[curr],
+                    *s_id,
+                    &state.sw_arr,
+                    &state.sw_pl,
+                    &state.servers,
+                )
+                .map_or(0.0, |(_, pl)| pl);
+
+                let from = route[curr].dc_id();
+                let distr_out = arrs[curr] * (1.0 - pl) / route[curr].next_nodes.len() as f64;
+
+                for &next in &route[curr].next_nodes {
+                    arrs[next] += distr_out;
+
+                    let to = route[next].dc_id();
+                    if let Some(position) = self.dc.graph[from].iter().position(|&n| n == to) {
+                        loads[from][position] += distr_out;
+                    }
+                }
+            });
+        }
+
+        loads
+    }
+
+    fn link_utilisations(&self, loads: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+        loads
+            .iter()
+            .zip(&self.dc.links)
+            .map(|(loads, links)| {
+                loads
+                    .iter()
+                    .zip(links)
+                    .map(|(load, link)| load / link.bandwidth)
+                    .collect()
+            })
+            .collect()
     }
 
     pub fn get_energy_consumption(
@@ -211,40 +613,195 @@ impl<'a> QueueingModel<'a> {
         let mut sum_energy = 0.0;
 
         for i in 0..self.dc.num_components() {
-            let utilisation;
-            if self.dc.is_server(i) {
-                let server_busy = calc_busy(sw_arr_mean[i], sw_sr, sw_ql);
-                let mut p_none_busy = 1.0;
-
-                for (&(s_id, pos), vnf) in &servers_mean[i] {
-                    // Producing VNFs don't go towards energy consumption
-                    if pos == 0 {
-                        continue;
-                    }
+            let utilisation =
+                self.get_utilisation(i, services, servers_mean, sw_arr_mean, sw_sr, sw_ql);
 
-                    let vnf_info = &services[s_id].vnfs[pos];
+            sum_energy += self.power.power(i, utilisation);
+        }
 
-                    let vm_not_busy = 1.0
-                        - calc_busy(
-                            vnf.arrival_rate,
-                            vnf_info.service_rate,
-                            vnf_info.queue_length,
-                        );
-                    p_none_busy = p_none_busy * vm_not_busy;
-                }
+        sum_energy
+    }
 
-                utilisation = 1.0 - ((1.0 - server_busy) * p_none_busy)
-            } else {
-                utilisation = calc_busy(sw_arr_mean[i], self.sw_sr, self.sw_ql)
-            };
+    // Probability component i is busy
+    fn get_utilisation(
+        &self,
+        i: usize,
+        services: &Vec<Service>,
+        servers_mean: &Vec<Server>,
+        sw_arr_mean: &Vec<f64>,
+        sw_sr: f64,
+        sw_ql: usize,
+    ) -> f64 {
+        if !self.dc.is_server(i) {
+            return self
+                .switch_queue
+                .busy(sw_arr_mean[i], self.sw_sr, self.sw_ql);
+        }
 
-            if utilisation == 0.0 {
+        let server_busy = self.switch_queue.busy(sw_arr_mean[i], sw_sr, sw_ql);
+        let mut p_none_busy = 1.0;
+
+        for (&(s_id, pos), vnf) in &servers_mean[i] {
+            // Producing VNFs don't go towards energy consumption
+            if pos == 0 {
                 continue;
             }
 
-            sum_energy += (self.active_cost * utilisation) + (self.idle_cost * (1.0 - utilisation));
+            let vnf_info = &services[s_id].vnfs[pos];
+
+            let vm_not_busy = 1.0
+                - self.vnf_queue.busy(
+                    vnf.arrival_rate,
+                    vnf_info.service_rate,
+                    vnf_info.queue_length,
+                );
+            p_none_busy = p_none_busy * vm_not_busy;
         }
 
-        sum_energy
+        1.0 - ((1.0 - server_busy) * p_none_busy)
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            datacentre::{FatTree, Link, Tier},
+            resources::Resources,
+            routing::get_tables,
+            service::{Sla, VNF},
+        },
+        operators::mapping::find_routes,
+    };
+
+    #[test]
+    fn test_evaluate_detailed() {
+        let dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+        let qm = QueueingModel::new(&dc, 80.0, 80, 0.01, 10, 30.0, 10.0);
+
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(40),
+        };
+        let services = vec![Service {
+            id: 0,
+            prod_rate: 5.0,
+            vnfs: vec![vnf; 3],
+            sla: Sla::default(),
+        }];
+        let routes = vec![(0, find_routes(vec![0, 2, 15], &rt))];
+
+        let (latencies, pls, energy) = qm.evaluate(&services, &routes).unwrap();
+        let report = qm.evaluate_detailed(&services, &routes);
+
+        assert_eq!(report.latencies, latencies);
+        assert_eq!(report.packet_losses, pls);
+        assert_eq!(report.energy, energy);
+        assert!(report.iterations >= 10);
+        assert!(report.max_diff < 0.01);
+        assert!(report.error.is_none());
+
+        assert_eq!(report.components.len(), dc.num_components());
+        assert_eq!(report.vnfs.len(), 3);
+
+        // Traffic from server 0 to 2 passes through their edge switch
+        let edge = &report.components[16];
+        assert!(!edge.is_server);
+        assert!(edge.arrival_rate > 0.0 && edge.utilisation > 0.0);
+        assert!(edge.queue_occupancy > 0.0 && edge.energy > 0.0);
+
+        // Unused components are off
+        let unused = &report.components[5];
+        assert_eq!((unused.utilisation, unused.energy), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_links() {
+        let mut dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(40),
+        };
+        let services = vec![Service {
+            id: 0,
+            prod_rate: 5.0,
+            vnfs: vec![vnf; 2],
+            sla: Sla::default(),
+        }];
+        let routes = vec![(0, find_routes(vec![0, 1], &rt))];
+
+        let qm = QueueingModel::new(&dc, 80.0, 80, 0.01, 10, 30.0, 10.0);
+        let (latencies, _, _, utilisations) = qm.evaluate_with_links(&services, &routes).unwrap();
+        assert!(utilisations.iter().flatten().all(|&u| u == 0.0));
+
+        // The links from the servers to their edge switches are slow and too narrow
+        let slow = Link {
+            bandwidth: 4.0,
+            latency: 0.5,
+        };
+        dc.set_links(|from, _| match from {
+            Tier::Server => slow,
+            _ => Link::default(),
+        });
+
+        let qm = QueueingModel::new(&dc, 80.0, 80, 0.01, 10, 30.0, 10.0);
+        let (slow_latencies, _, _, utilisations) =
+            qm.evaluate_with_links(&services, &routes).unwrap();
+        assert!((slow_latencies[0] - latencies[0] - 0.5).abs() < 1e-9);
+        assert!(utilisations[0][0] > 1.0 && utilisations[0][0] <= 1.25);
+
+        let report = qm.evaluate_detailed(&services, &routes);
+        let links: Vec<_> = report.links.iter().map(|l| (l.from, l.to)).collect();
+        assert_eq!(links, vec![(0, 16), (16, 1)]);
+        assert!((report.links[0].load - 5.0).abs() < 0.1);
+        assert_eq!(report.links[0].utilisation, utilisations[0][0]);
+    }
+
+    #[test]
+    fn test_convergence() {
+        let dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+        let mut qm = QueueingModel::new(&dc, 80.0, 80, 0.01, 10, 30.0, 10.0);
+
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(40),
+        };
+        let mut services = vec![Service {
+            id: 0,
+            prod_rate: 9.0,
+            vnfs: vec![vnf; 3],
+            sla: Sla::default(),
+        }];
+        let routes = vec![(0, find_routes(vec![0, 2, 15], &rt))];
+
+        let (latencies, _, _) = qm.evaluate(&services, &routes).unwrap();
+
+        // Relaxed updates settle on the same arrival rates
+        qm.relaxation = 0.5;
+        let (relaxed, _, _) = qm.evaluate(&services, &routes).unwrap();
+        assert!((relaxed[0] - latencies[0]).abs() < 0.01 * latencies[0]);
+
+        qm.max_iterations = 5;
+        match qm.evaluate(&services, &routes) {
+            Err(ConvergenceError::MaxIterations { iterations, .. }) => assert_eq!(iterations, 5),
+            result => panic!("Expected the iteration limit, got {:?}", result),
+        }
+
+        // A VNF that never serves packets makes the packet loss undefined
+        qm.max_iterations = 0;
+        services[0].vnfs[1].service_rate = 0.0;
+        match qm.evaluate_detailed(&services, &routes).error {
+            Some(ConvergenceError::Diverged { .. }) => {}
+            error => panic!("Expected divergence, got {:?}", error),
+        }
     }
 }
@@ -0,0 +1,374 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+/**
+ * Steady state of a queue with Poisson arrivals at `arrival_rate` and room for `queue_length`
+ * packets, including those in service, served at `service_rate` by each server. The
+ * queueing model uses one discipline for the switches and one for the VNFs.
+ **/
+pub trait QueueDiscipline: Send + Sync + std::fmt::Debug {
+    /// Probability an arriving packet finds the queue full and is dropped
+    fn packet_loss(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64;
+
+    /// Mean number of packets in the queue and in service
+    fn num_in_system(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64;
+
+    /// Probability the queue is not empty
+    fn busy(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64;
+
+    /// Mean time an accepted packet spends in the queue and in service, by Little's law
+    fn waiting_time(
+        &self,
+        arrival_rate: f64,
+        service_rate: f64,
+        queue_length: usize,
+        packet_loss: f64,
+    ) -> f64 {
+        if arrival_rate == 0. {
+            return 0.;
+        }
+
+        let num_in_system = self.num_in_system(arrival_rate, service_rate, queue_length);
+        let ar = arrival_rate * (1.0 - packet_loss);
+
+        num_in_system / ar
+    }
+}
+
+/// Exponential service times with a single server
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MM1K;
+
+impl QueueDiscipline for MM1K {
+    fn packet_loss(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        let queue_length = queue_length as f64;
+        let rho = arrival_rate / service_rate;
+
+        if rho == 1. {
+            1. / (queue_length + 1.)
+        } else {
+            ((1. - rho) * rho.powf(queue_length)) / (1. - rho.powf(queue_length + 1.))
+        }
+    }
+
+    fn num_in_system(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        let queue_length = queue_length as f64;
+
+        let rho = arrival_rate / service_rate;
+
+        if arrival_rate == 0. {
+            return 0.;
+        }
+
+        if rho != 1.0 {
+            let a = rho
+                * (1.0 - (queue_length + 1.0) * rho.powf(queue_length)
+                    + queue_length * rho.powf(queue_length + 1.0));
+            let b = (1.0 - rho) * (1.0 - rho.powf(queue_length + 1.0));
+
+            a / b
+        } else {
+            queue_length / 2.0
+        }
+    }
+
+    fn busy(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        if arrival_rate > 0.0 && service_rate == 0.0 {
+            return std::f64::INFINITY;
+        }
+
+        let rho = arrival_rate / service_rate;
+        let k = queue_length as f64;
+
+        let p_empty = if arrival_rate != service_rate {
+            (1.0 - rho) / (1.0 - rho.powf(k + 1.0))
+        } else {
+            1.0 / (k + 1.0)
+        };
+
+        1.0 - p_empty
+    }
+}
+
+/**
+ * General service times with coefficient of variation `cv` and a single server. The loss is
+ * Smith's two-moment approximation (2003), which is exact for exponential service times,
+ * and the number waiting scales that of M/M/1/K by (1 + cv^2) / 2 as in the
+ * Pollaczek-Khinchine formula.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MG1K {
+    pub cv: f64,
+}
+
+impl QueueDiscipline for MG1K {
+    fn packet_loss(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        if arrival_rate == 0.0 {
+            return 0.0;
+        }
+
+        let rho = arrival_rate / service_rate;
+        let k = queue_length as f64;
+        let root = rho.sqrt();
+        let scv = self.cv * self.cv;
+
+        let denominator = 2.0 + root * scv - root;
+        let a = (root * scv - root + 2.0 * k) / denominator;
+        let b = 2.0 * (root * scv - root + k + 1.0) / denominator;
+
+        if (rho - 1.0).abs() < 1e-12 {
+            1.0 / b
+        } else {
+            rho.powf(a) * (rho - 1.0) / (rho.powf(b) - 1.0)
+        }
+    }
+
+    fn num_in_system(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        if arrival_rate == 0.0 {
+            return 0.0;
+        }
+
+        let in_service = self.busy(arrival_rate, service_rate, queue_length);
+
+        let mm1k_waiting = MM1K.num_in_system(arrival_rate, service_rate, queue_length)
+            - MM1K.busy(arrival_rate, service_rate, queue_length);
+        let waiting = mm1k_waiting.max(0.0) * (1.0 + self.cv * self.cv) / 2.0;
+
+        (in_service + waiting).min(queue_length as f64)
+    }
+
+    fn busy(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        let throughput =
+            arrival_rate * (1.0 - self.packet_loss(arrival_rate, service_rate, queue_length));
+
+        (throughput / service_rate).min(1.0)
+    }
+}
+
+/// Deterministic service times with a single server, such as fixed size packets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MD1K;
+
+impl QueueDiscipline for MD1K {
+    fn packet_loss(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        MG1K { cv: 0.0 }.packet_loss(arrival_rate, service_rate, queue_length)
+    }
+
+    fn num_in_system(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        MG1K { cv: 0.0 }.num_in_system(arrival_rate, service_rate, queue_length)
+    }
+
+    fn busy(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        MG1K { cv: 0.0 }.busy(arrival_rate, service_rate, queue_length)
+    }
+}
+
+/**
+ * Exponential service times with `servers` parallel servers, such as a VNF on several
+ * cores, each serving at the service rate. The queue holds at least one packet per server.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MMcK {
+    pub servers: usize,
+}
+
+impl MMcK {
+    // Probability of each number of packets in the system
+    fn distribution(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> Vec<f64> {
+        let capacity = queue_length.max(self.servers);
+
+        if arrival_rate == 0.0 {
+            let mut probabilities = vec![0.0; capacity + 1];
+            probabilities[0] = 1.0;
+            return probabilities;
+        }
+
+        // Unnormalised in logs, as the terms overflow for long queues
+        let offered = (arrival_rate / service_rate).ln();
+        let mut log_terms = Vec::with_capacity(capacity + 1);
+        log_terms.push(0.0);
+
+        for n in 1..=capacity {
+            let log_term = log_terms[n - 1] + offered - (n.min(self.servers) as f64).ln();
+            log_terms.push(log_term);
+        }
+
+        let max = log_terms
+            .iter()
+            .cloned()
+            .fold(std::f64::NEG_INFINITY, f64::max);
+        let terms: Vec<f64> = log_terms.iter().map(|term| (term - max).exp()).collect();
+        let total: f64 = terms.iter().sum();
+
+        terms.iter().map(|term| term / total).collect()
+    }
+}
+
+impl QueueDiscipline for MMcK {
+    fn packet_loss(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        let probabilities = self.distribution(arrival_rate, service_rate, queue_length);
+        probabilities[probabilities.len() - 1]
+    }
+
+    fn num_in_system(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        self.distribution(arrival_rate, service_rate, queue_length)
+            .iter()
+            .enumerate()
+            .map(|(n, probability)| n as f64 * probability)
+            .sum()
+    }
+
+    fn busy(&self, arrival_rate: f64, service_rate: f64, queue_length: usize) -> f64 {
+        1.0 - self.distribution(arrival_rate, service_rate, queue_length)[0]
+    }
+}
+
+/**
+ * The queue disciplines that can be chosen in the config, written as `mm1k`, `md1k`,
+ * `mg1k:CV` with the coefficient of variation of the service times, or `mmck:C` with the
+ * number of servers.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Discipline {
+    MM1K,
+    MD1K,
+    MG1K(f64),
+    MMcK(usize),
+}
+
+impl Discipline {
+    pub fn build(&self) -> Arc<dyn QueueDiscipline> {
+        match *self {
+            Discipline::MM1K => Arc::new(MM1K),
+            Discipline::MD1K => Arc::new(MD1K),
+            Discipline::MG1K(cv) => Arc::new(MG1K { cv }),
+            Discipline::MMcK(servers) => Arc::new(MMcK { servers }),
+        }
+    }
+}
+
+impl Default for Discipline {
+    fn default() -> Self {
+        Discipline::MM1K
+    }
+}
+
+impl Display for Discipline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Discipline::MM1K => write!(f, "mm1k"),
+            Discipline::MD1K => write!(f, "md1k"),
+            Discipline::MG1K(cv) => write!(f, "mg1k:{}", cv),
+            Discipline::MMcK(servers) => write!(f, "mmck:{}", servers),
+        }
+    }
+}
+
+impl FromStr for Discipline {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase().replace("/", "");
+        let mut parts = name.splitn(2, ':');
+        let kind = parts.next().unwrap().trim();
+        let value = parts.next().map(|value| value.trim());
+
+        let invalid = || {
+            format!(
+                "Unknown queue discipline '{}', expected mm1k, md1k, mg1k:CV or mmck:SERVERS",
+                s
+            )
+        };
+
+        match (kind, value) {
+            ("mm1k", None) => Ok(Discipline::MM1K),
+            ("md1k", None) => Ok(Discipline::MD1K),
+            ("mg1k", Some(cv)) => match cv.parse::<f64>() {
+                Ok(cv) if cv >= 0.0 => Ok(Discipline::MG1K(cv)),
+                _ => Err(invalid()),
+            },
+            ("mmck", Some(servers)) => match servers.parse::<usize>() {
+                Ok(servers) if servers > 0 => Ok(Discipline::MMcK(servers)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
+}
+
+serialize_display!(Discipline);
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_mm1k() {
+        // rho = 0.5 and K = 2, so the states have weights 1, 0.5 and 0.25
+        assert_close(MM1K.packet_loss(5.0, 10.0, 2), 0.25 / 1.75);
+        assert_close(MM1K.num_in_system(5.0, 10.0, 2), 1.0 / 1.75);
+        assert_close(MM1K.busy(5.0, 10.0, 2), 0.75 / 1.75);
+        assert_close(MM1K.packet_loss(10.0, 10.0, 3), 0.25);
+        assert_eq!(MM1K.waiting_time(0.0, 10.0, 3, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_mg1k() {
+        // Exponential service times are M/M/1/K
+        let exponential = MG1K { cv: 1.0 };
+        for &arrival_rate in &[2.0, 10.0, 15.0] {
+            let expected = MM1K.packet_loss(arrival_rate, 10.0, 5);
+            assert_close(exponential.packet_loss(arrival_rate, 10.0, 5), expected);
+
+            let expected = MM1K.num_in_system(arrival_rate, 10.0, 5);
+            assert_close(exponential.num_in_system(arrival_rate, 10.0, 5), expected);
+        }
+
+        // Less variable service times lose fewer packets and queue less
+        assert!(MD1K.packet_loss(8.0, 10.0, 5) < MM1K.packet_loss(8.0, 10.0, 5));
+        assert!(MD1K.num_in_system(8.0, 10.0, 5) < MM1K.num_in_system(8.0, 10.0, 5));
+        assert!(MG1K { cv: 2.0 }.packet_loss(8.0, 10.0, 5) > MM1K.packet_loss(8.0, 10.0, 5));
+        assert_eq!(MD1K.packet_loss(0.0, 10.0, 5), 0.0);
+    }
+
+    #[test]
+    fn test_mmck() {
+        // A single server is M/M/1/K
+        let single = MMcK { servers: 1 };
+        assert_close(
+            single.packet_loss(5.0, 10.0, 2),
+            MM1K.packet_loss(5.0, 10.0, 2),
+        );
+        assert_close(
+            single.num_in_system(5.0, 10.0, 2),
+            MM1K.num_in_system(5.0, 10.0, 2),
+        );
+        assert_close(single.busy(5.0, 10.0, 2), MM1K.busy(5.0, 10.0, 2));
+
+        // M/M/2/2 is Erlang's loss system, B(2, 1) = 0.2
+        assert_close(MMcK { servers: 2 }.packet_loss(10.0, 10.0, 2), 0.2);
+
+        // More servers lose fewer packets and long queues do not overflow
+        assert!(MMcK { servers: 4 }.packet_loss(30.0, 10.0, 8) < MM1K.packet_loss(30.0, 10.0, 8));
+        assert!(MMcK { servers: 2 }
+            .packet_loss(100.0, 10.0, 2000)
+            .is_finite());
+    }
+
+    #[test]
+    fn test_discipline() {
+        for name in &["mm1k", "md1k", "mg1k:0.5", "mmck:4"] {
+            let discipline: Discipline = name.parse().unwrap();
+            assert_eq!(discipline.to_string(), *name);
+        }
+
+        assert_eq!("M/M/1/K".parse(), Ok(Discipline::MM1K));
+        assert!("mmck:0".parse::<Discipline>().is_err());
+        assert!("mg1k".parse::<Discipline>().is_err());
+        assert!("gg1".parse::<Discipline>().is_err());
+    }
+}
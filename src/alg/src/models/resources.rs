@@ -0,0 +1,302 @@
+use std::{
+    fmt::{self, Display},
+    ops::{Add, AddAssign, Index, Sub, SubAssign},
+    str::FromStr,
+};
+
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// The most resource types a server or VNF can have, e.g. CPU, memory, NIC bandwidth, storage
+pub const MAX_RESOURCES: usize = 4;
+
+/**
+ * An amount of each type of resource, the capacity of a server or the size of a VNF. Amounts
+ * of the dimensions that are not given are 0, so a VNF with fewer dimensions than the servers
+ * does not use the others. Written as a single number when there is one dimension, e.g. `100`,
+ * and otherwise as a list, e.g. `[32, 64, 10]` in config and JSON files or `32,64,10` on the
+ * command line.
+ **/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Resources {
+    amounts: [usize; MAX_RESOURCES],
+    dimensions: usize,
+}
+
+impl Resources {
+    pub fn new(amounts: &[usize]) -> Result<Resources, String> {
+        if amounts.is_empty() || amounts.len() > MAX_RESOURCES {
+            return Err(format!(
+                "Expected between 1 and {} resource amounts, found {}",
+                MAX_RESOURCES,
+                amounts.len()
+            ));
+        }
+
+        let mut resources = Resources {
+            amounts: [0; MAX_RESOURCES],
+            dimensions: amounts.len(),
+        };
+        resources.amounts[..amounts.len()].copy_from_slice(amounts);
+
+        Ok(resources)
+    }
+
+    pub fn scalar(amount: usize) -> Resources {
+        let mut amounts = [0; MAX_RESOURCES];
+        amounts[0] = amount;
+
+        Resources {
+            amounts,
+            dimensions: 1,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[usize] {
+        &self.amounts[..self.dimensions]
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amounts.iter().all(|&amount| amount == 0)
+    }
+
+    /// Whether every dimension has at least 1
+    pub fn all_positive(&self) -> bool {
+        self.as_slice().iter().all(|&amount| amount > 0)
+    }
+
+    /// Whether `required` fits in every dimension
+    pub fn fits(&self, required: &Resources) -> bool {
+        self.amounts
+            .iter()
+            .zip(&required.amounts)
+            .all(|(available, required)| available >= required)
+    }
+
+    /**
+     * How many times `required` fits in the dimension it is most limited by, used to rank the
+     * servers it fits in. At least 1 if it fits. When nothing is required it is the amount
+     * left of the scarcest dimension, so the servers are still ranked by how full they are.
+     **/
+    pub fn fit(&self, required: &Resources) -> f64 {
+        if required.is_zero() {
+            return self.as_slice().iter().min().map_or(0, |&amount| amount) as f64;
+        }
+
+        self.amounts
+            .iter()
+            .zip(&required.amounts)
+            .filter(|(_, required)| **required > 0)
+            .map(|(&available, &required)| available as f64 / required as f64)
+            .fold(std::f64::INFINITY, f64::min)
+    }
+
+    /// The largest amount of each dimension
+    pub fn max(&self, other: &Resources) -> Resources {
+        self.combine(other, usize::max)
+    }
+
+    fn combine(&self, other: &Resources, f: impl Fn(usize, usize) -> usize) -> Resources {
+        let mut amounts = [0; MAX_RESOURCES];
+        for d in 0..MAX_RESOURCES {
+            amounts[d] = f(self.amounts[d], other.amounts[d]);
+        }
+
+        Resources {
+            amounts,
+            dimensions: self.dimensions.max(other.dimensions),
+        }
+    }
+}
+
+impl From<usize> for Resources {
+    fn from(amount: usize) -> Self {
+        Resources::scalar(amount)
+    }
+}
+
+// Missing dimensions are 0, so 100 and [100, 0] are the same
+impl PartialEq for Resources {
+    fn eq(&self, other: &Resources) -> bool {
+        self.amounts == other.amounts
+    }
+}
+
+impl Eq for Resources {}
+
+impl Index<usize> for Resources {
+    type Output = usize;
+
+    fn index(&self, dimension: usize) -> &usize {
+        &self.amounts[dimension]
+    }
+}
+
+impl Add for Resources {
+    type Output = Resources;
+
+    fn add(self, other: Resources) -> Resources {
+        self.combine(&other, |a, b| a + b)
+    }
+}
+
+impl AddAssign for Resources {
+    fn add_assign(&mut self, other: Resources) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Resources {
+    type Output = Resources;
+
+    fn sub(self, other: Resources) -> Resources {
+        self.combine(&other, |a, b| a - b)
+    }
+}
+
+impl SubAssign for Resources {
+    fn sub_assign(&mut self, other: Resources) {
+        *self = *self - other;
+    }
+}
+
+impl Display for Resources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let amounts: Vec<String> = self.as_slice().iter().map(|a| a.to_string()).collect();
+
+        write!(f, "{}", amounts.join(","))
+    }
+}
+
+impl FromStr for Resources {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().trim_start_matches('[').trim_end_matches(']');
+
+        let amounts = s
+            .split(',')
+            .map(|amount| amount.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("Invalid resources '{}', expected e.g. 100 or 32,64,10", s))?;
+
+        Resources::new(&amounts)
+    }
+}
+
+impl Serialize for Resources {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && self.dimensions == 1 {
+            return serializer.serialize_u64(self.amounts[0] as u64);
+        }
+
+        let mut seq = serializer.serialize_seq(Some(self.dimensions))?;
+        for amount in self.as_slice() {
+            seq.serialize_element(amount)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Resources {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ResourcesVisitor)
+        } else {
+            deserializer.deserialize_seq(ResourcesVisitor)
+        }
+    }
+}
+
+struct ResourcesVisitor;
+
+impl<'de> Visitor<'de> for ResourcesVisitor {
+    type Value = Resources;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an amount or a list of up to {} amounts", MAX_RESOURCES)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Resources, E> {
+        Ok(Resources::scalar(value as usize))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Resources, E> {
+        if value < 0 {
+            return Err(E::invalid_value(de::Unexpected::Signed(value), &self));
+        }
+
+        self.visit_u64(value as u64)
+    }
+
+    // Values overridden from the environment arrive as strings
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Resources, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Resources, A::Error> {
+        let mut amounts = Vec::new();
+        while let Some(amount) = seq.next_element::<usize>()? {
+            amounts.push(amount);
+        }
+
+        Resources::new(&amounts).map_err(de::Error::custom)
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        let server = Resources::new(&[32, 64, 10]).unwrap();
+
+        assert!(server.fits(&Resources::new(&[32, 16]).unwrap()));
+        assert!(!server.fits(&Resources::new(&[8, 16, 11]).unwrap()));
+        assert_eq!(server.fit(&Resources::new(&[8, 32, 1]).unwrap()), 2.0);
+        assert_eq!(server.fit(&Resources::new(&[0, 0]).unwrap()), 10.0);
+
+        let used = server - Resources::new(&[8, 32]).unwrap();
+        assert_eq!(used, Resources::new(&[24, 32, 10]).unwrap());
+        assert_eq!(used + Resources::new(&[8, 32]).unwrap(), server);
+
+        assert_eq!(Resources::scalar(100), Resources::new(&[100, 0]).unwrap());
+        assert_eq!(Resources::scalar(30).fit(&Resources::scalar(20)), 1.5);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("100".parse(), Ok(Resources::scalar(100)));
+        assert_eq!(
+            "32, 64,10".parse(),
+            Ok(Resources::new(&[32, 64, 10]).unwrap())
+        );
+        assert!("".parse::<Resources>().is_err());
+        assert!("1,2,3,4,5".parse::<Resources>().is_err());
+        assert!("-1".parse::<Resources>().is_err());
+        assert_eq!(Resources::new(&[32, 64]).unwrap().to_string(), "32,64");
+    }
+
+    #[test]
+    fn test_serialize() {
+        let scalar = Resources::scalar(100);
+        let vector = Resources::new(&[32, 64, 10]).unwrap();
+
+        assert_eq!(serde_json::to_string(&scalar).unwrap(), "100");
+        assert_eq!(serde_json::to_string(&vector).unwrap(), "[32,64,10]");
+        assert_eq!(serde_json::from_str::<Resources>("100").unwrap(), scalar);
+        assert_eq!(serde_json::from_str::<Resources>("[100]").unwrap(), scalar);
+        assert!(serde_json::from_str::<Resources>("[]").is_err());
+
+        for resources in &[scalar, vector] {
+            let bytes = bincode::serialize(resources).unwrap();
+            let read: Resources = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(read.as_slice(), resources.as_slice());
+        }
+    }
+}
@@ -1,20 +1,60 @@
+use crate::models::resources::Resources;
 use crate::operators::solution::Solution;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 pub type ServiceID = usize;
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Service {
     pub id: ServiceID,
     pub prod_rate: f64,
     pub vnfs: Vec<VNF>,
+    #[serde(default)]
+    pub sla: Sla,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The most latency and packet loss a service may see, unbounded if not given
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sla {
+    #[serde(default)]
+    pub latency: Option<f64>,
+    #[serde(default)]
+    pub packet_loss: Option<f64>,
+}
+
+impl Sla {
+    pub fn is_empty(&self) -> bool {
+        self.latency.is_none() && self.packet_loss.is_none()
+    }
+
+    /// Whether the bounds that are given are positive
+    pub fn is_valid(&self) -> bool {
+        self.latency
+            .iter()
+            .chain(&self.packet_loss)
+            .all(|&bound| bound > 0.0)
+    }
+
+    /**
+     * How far `latency` and `packet_loss` exceed their bounds, relative to the bounds so the
+     * two can be added up, e.g. 0.5 for a latency 50% over its bound. 0 if the SLA is met.
+     **/
+    pub fn violation(&self, latency: f64, packet_loss: f64) -> f64 {
+        let excess = |value: f64, bound: Option<f64>| match bound {
+            Some(bound) if value > bound => (value - bound) / bound,
+            _ => 0.0,
+        };
+
+        excess(latency, self.latency) + excess(packet_loss, self.packet_loss)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct VNF {
     pub service_rate: f64,
     pub queue_length: usize,
-    pub size: usize,
+    pub size: Resources,
 }
 
 impl Display for Solution<Option<&Service>> {
@@ -29,3 +69,29 @@ impl Display for Solution<Option<&Service>> {
         Ok(())
     }
 }
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sla_violation() {
+        let sla = Sla {
+            latency: Some(2.0),
+            packet_loss: Some(0.01),
+        };
+
+        assert_eq!(sla.violation(1.5, 0.01), 0.0);
+        assert_eq!(sla.violation(3.0, 0.0), 0.5);
+        assert!((sla.violation(3.0, 0.02) - 1.5).abs() < 1e-9);
+        assert_eq!(Sla::default().violation(100.0, 1.0), 0.0);
+
+        assert!(sla.is_valid() && !sla.is_empty());
+        assert!(!Sla {
+            latency: Some(0.0),
+            packet_loss: None
+        }
+        .is_valid());
+    }
+}
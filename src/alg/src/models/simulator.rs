@@ -0,0 +1,574 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use rand::Rng;
+use rand_distr::{Exp1, StandardNormal};
+
+use crate::models::datacentre::{Datacentre, NodeID};
+use crate::models::queues::Discipline;
+use crate::models::service::{Service, ServiceID};
+use crate::operators::mapping::{NodeType, RouteNode};
+use crate::utilities::rng::thread_rng;
+
+pub const DEFAULT_DURATION: f64 = 1000.0;
+pub const DEFAULT_WARM_UP: f64 = 100.0;
+
+/**
+ * Packet level discrete event simulation of the routes of a placement, to validate the
+ * queueing model against. Every instance of a service emits packets from its first VNF as
+ * a Poisson process, sharing the production rate of the service between its instances like
+ * the queueing model does. Each switch and each VNF after the first is a finite FIFO queue
+ * with service times drawn for its discipline, packets that find it full are dropped. Where
+ * a route branches a packet takes one of the next nodes at random, as ECMP would.
+ **/
+#[derive(Clone)]
+pub struct Simulator<'a> {
+    dc: &'a Datacentre,
+    sw_sr: f64,
+    sw_ql: usize,
+
+    /// Simulated time over which packets are measured
+    pub duration: f64,
+
+    /// Simulated time before measuring, for the queues to fill
+    pub warm_up: f64,
+
+    pub switch_queue: Discipline,
+    pub vnf_queue: Discipline,
+}
+
+/// What the simulation measured, for packets emitted after the warm up
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    /// Mean time from being emitted to leaving the last VNF of each service's delivered packets
+    pub latencies: Vec<f64>,
+
+    /// Proportion of each service's packets dropped on the way
+    pub packet_losses: Vec<f64>,
+
+    /// Packets each service emitted
+    pub packets: Vec<usize>,
+
+    /// Switch queue of every component, by id in the datacentre
+    pub components: Vec<QueueStats>,
+
+    /// Queues of the VNFs after the first of each service
+    pub vnfs: Vec<VnfStats>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QueueStats {
+    /// Packets offered per unit of time, including those dropped
+    pub arrival_rate: f64,
+
+    /// Proportion of the time the queue was not empty
+    pub utilisation: f64,
+
+    pub packet_loss: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct VnfStats {
+    pub server: NodeID,
+    pub service: ServiceID,
+    pub stage: usize,
+    pub stats: QueueStats,
+}
+
+/// A service as simulated and as found by the queueing model
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceComparison {
+    pub service: ServiceID,
+    pub packets: usize,
+    pub model_latency: f64,
+    pub simulated_latency: f64,
+    pub model_packet_loss: f64,
+    pub simulated_packet_loss: f64,
+}
+
+impl ServiceComparison {
+    /// Difference of the model's latency from the simulated one, relative to the simulated one
+    pub fn latency_error(&self) -> f64 {
+        relative_error(self.model_latency, self.simulated_latency)
+    }
+
+    pub fn packet_loss_error(&self) -> f64 {
+        self.model_packet_loss - self.simulated_packet_loss
+    }
+}
+
+impl SimulationReport {
+    /**
+     * Pairs the simulated latency and packet loss of each service with those of the
+     * queueing model, as returned by `QueueingModel::evaluate`. Services that emitted no
+     * packets, as they were not placed, are left out.
+     **/
+    pub fn compare(&self, latencies: &[f64], packet_losses: &[f64]) -> Vec<ServiceComparison> {
+        (0..self.packets.len())
+            .filter(|&i| self.packets[i] > 0)
+            .map(|i| ServiceComparison {
+                service: i,
+                packets: self.packets[i],
+                model_latency: latencies[i],
+                simulated_latency: self.latencies[i],
+                model_packet_loss: packet_losses[i],
+                simulated_packet_loss: self.packet_losses[i],
+            })
+            .collect()
+    }
+}
+
+pub fn relative_error(model: f64, simulated: f64) -> f64 {
+    if simulated == 0.0 {
+        if model == 0.0 {
+            0.0
+        } else {
+            std::f64::INFINITY
+        }
+    } else {
+        (model - simulated) / simulated
+    }
+}
+
+// A packet arriving at a node of a route, or the next packet of a route being emitted
+#[derive(Debug, Clone, Copy)]
+struct Event {
+    time: f64,
+    seq: usize,
+    route: usize,
+    node: Option<usize>,
+    emitted: f64,
+}
+
+// Earliest first in a max heap, in order of scheduling when at the same time
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .time
+            .partial_cmp(&self.time)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Event {}
+
+// A FIFO queue served by one or more servers
+struct Queue {
+    discipline: Discipline,
+    service_rate: f64,
+    capacity: usize,
+
+    // Departure times of the packets in the queue, and when each server is next free
+    departures: Vec<f64>,
+    free_at: Vec<f64>,
+
+    // Measured over the window, busy_until is the end of the current busy period
+    arrivals: usize,
+    losses: usize,
+    busy_time: f64,
+    busy_until: f64,
+}
+
+impl Queue {
+    fn new(discipline: Discipline, service_rate: f64, queue_length: usize) -> Queue {
+        let servers = match discipline {
+            Discipline::MMcK(servers) => servers,
+            _ => 1,
+        };
+
+        Queue {
+            discipline,
+            service_rate,
+            capacity: queue_length.max(servers),
+            departures: Vec::new(),
+            free_at: vec![0.0; servers],
+            arrivals: 0,
+            losses: 0,
+            busy_time: 0.0,
+            busy_until: 0.0,
+        }
+    }
+
+    // Departure time of a packet arriving at `time`, or None if it is dropped
+    fn arrive(&mut self, time: f64, measured: bool, rng: &mut impl Rng) -> Option<f64> {
+        self.departures.retain(|&departure| departure > time);
+
+        if measured {
+            self.arrivals = self.arrivals + 1;
+        }
+
+        if self.departures.len() >= self.capacity {
+            if measured {
+                self.losses = self.losses + 1;
+            }
+
+            return None;
+        }
+
+        let (server, free_at) = self
+            .free_at
+            .iter()
+            .cloned()
+            .enumerate()
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            .unwrap();
+
+        let departure = time.max(free_at) + self.service_time(rng);
+        self.free_at[server] = departure;
+        self.departures.push(departure);
+
+        // The queue is busy from the arrival of a packet to its departure
+        if measured && departure > self.busy_until {
+            self.busy_time = self.busy_time + departure - time.max(self.busy_until);
+        }
+        self.busy_until = self.busy_until.max(departure);
+
+        Some(departure)
+    }
+
+    fn service_time(&self, rng: &mut impl Rng) -> f64 {
+        let mean = 1.0 / self.service_rate;
+
+        match self.discipline {
+            Discipline::MM1K | Discipline::MMcK(_) => rng.sample::<f64, _>(Exp1) * mean,
+            Discipline::MD1K => mean,
+            Discipline::MG1K(cv) if cv == 0.0 => mean,
+            Discipline::MG1K(cv) => {
+                // Log-normal with the mean and coefficient of variation
+                let sigma = (1.0 + cv * cv).ln().sqrt();
+                let mu = mean.ln() - sigma * sigma / 2.0;
+
+                (mu + sigma * rng.sample::<f64, _>(StandardNormal)).exp()
+            }
+        }
+    }
+
+    fn stats(&self, duration: f64) -> QueueStats {
+        QueueStats {
+            arrival_rate: self.arrivals as f64 / duration,
+            utilisation: (self.busy_time / duration).min(1.0),
+            packet_loss: if self.arrivals > 0 {
+                self.losses as f64 / self.arrivals as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(dc: &Datacentre, sw_sr: f64, sw_ql: usize) -> Simulator {
+        Simulator {
+            dc,
+            sw_sr,
+            sw_ql,
+            duration: DEFAULT_DURATION,
+            warm_up: DEFAULT_WARM_UP,
+            switch_queue: Discipline::MM1K,
+            vnf_queue: Discipline::MM1K,
+        }
+    }
+
+    pub fn simulate(
+        &self,
+        services: &Vec<Service>,
+        routes: &Vec<(ServiceID, Vec<RouteNode>)>,
+    ) -> SimulationReport {
+        let mut rng = thread_rng();
+        let end = self.warm_up + self.duration;
+
+        // The switch queues are the first components of the list, then the VNFs
+        let mut queues: Vec<Queue> = (0..self.dc.num_components())
+            .map(|_| Queue::new(self.switch_queue, self.sw_sr, self.sw_ql))
+            .collect();
+        let mut vnf_ids: HashMap<(NodeID, ServiceID, usize), usize> = HashMap::new();
+
+        // Queue of each node of each route, the first VNF emits the packets
+        let route_queues: Vec<Vec<Option<usize>>> = routes
+            .iter()
+            .map(|(s_id, route)| {
+                route
+                    .iter()
+                    .map(|node| match node.node_type {
+                        NodeType::Component(id) => Some(id),
+                        NodeType::VNF(_, 0) => None,
+                        NodeType::VNF(server, stage) => {
+                            let id = vnf_ids.entry((server, *s_id, stage)).or_insert_with(|| {
+                                let vnf = &services[*s_id].vnfs[stage];
+                                queues.push(Queue::new(
+                                    self.vnf_queue,
+                                    vnf.service_rate,
+                                    vnf.queue_length,
+                                ));
+
+                                queues.len() - 1
+                            });
+
+                            Some(*id)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut num_instances = vec![0; services.len()];
+        for (s_id, _) in routes {
+            num_instances[*s_id] += 1;
+        }
+
+        let mut events = BinaryHeap::new();
+        let mut seq = 0;
+        let mut schedule = |events: &mut BinaryHeap<Event>, time, route, node, emitted| {
+            events.push(Event {
+                time,
+                seq,
+                route,
+                node,
+                emitted,
+            });
+            seq = seq + 1;
+        };
+
+        let rates: Vec<f64> = routes
+            .iter()
+            .map(|(s_id, _)| services[*s_id].prod_rate / num_instances[*s_id] as f64)
+            .collect();
+
+        for (i, &rate) in rates.iter().enumerate() {
+            if rate > 0.0 {
+                let time = rng.sample::<f64, _>(Exp1) / rate;
+                schedule(&mut events, time, i, None, time);
+            }
+        }
+
+        let mut packets = vec![0; services.len()];
+        let mut lost = vec![0; services.len()];
+        let mut delivered = vec![0; services.len()];
+        let mut total_latency = vec![0.0; services.len()];
+
+        while let Some(event) = events.pop() {
+            let (s_id, route) = &routes[event.route];
+            let measured = event.emitted >= self.warm_up && event.emitted < end;
+
+            let (node, departure) = match event.node {
+                // Emit a packet and schedule the next, the first VNF does not queue them
+                None => {
+                    let next = event.time + rng.sample::<f64, _>(Exp1) / rates[event.route];
+                    if next < end {
+                        schedule(&mut events, next, event.route, None, next);
+                    }
+
+                    if measured {
+                        packets[*s_id] += 1;
+                    }
+
+                    (0, event.time)
+                }
+                Some(node) => match route_queues[event.route][node] {
+                    Some(queue) => match queues[queue].arrive(event.time, measured, &mut rng) {
+                        Some(departure) => (node, departure),
+                        None => {
+                            if measured {
+                                lost[*s_id] += 1;
+                            }
+                            continue;
+                        }
+                    },
+                    None => (node, event.time),
+                },
+            };
+
+            let next_nodes = &route[node].next_nodes;
+            if next_nodes.is_empty() {
+                if measured {
+                    delivered[*s_id] += 1;
+                    total_latency[*s_id] += departure - event.emitted;
+                }
+            } else {
+                let next = next_nodes[rng.gen_range(0, next_nodes.len())];
+                schedule(
+                    &mut events,
+                    departure,
+                    event.route,
+                    Some(next),
+                    event.emitted,
+                );
+            }
+        }
+
+        let latencies = (0..services.len())
+            .map(|i| {
+                if delivered[i] > 0 {
+                    total_latency[i] / delivered[i] as f64
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let packet_losses = (0..services.len())
+            .map(|i| {
+                if packets[i] > 0 {
+                    lost[i] as f64 / packets[i] as f64
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let components = queues[..self.dc.num_components()]
+            .iter()
+            .map(|queue| queue.stats(self.duration))
+            .collect();
+
+        let mut vnfs: Vec<VnfStats> = vnf_ids
+            .iter()
+            .map(|(&(server, service, stage), &id)| VnfStats {
+                server,
+                service,
+                stage,
+                stats: queues[id].stats(self.duration),
+            })
+            .collect();
+        vnfs.sort_by_key(|vnf| (vnf.server, vnf.service, vnf.stage));
+
+        SimulationReport {
+            latencies,
+            packet_losses,
+            packets,
+            components,
+            vnfs,
+        }
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::datacentre::FatTree;
+    use crate::models::queueing_model::QueueingModel;
+    use crate::models::queues::{QueueDiscipline, MM1K};
+    use crate::models::resources::Resources;
+    use crate::models::service::{Sla, VNF};
+    use crate::utilities::rng;
+
+    // Two VNFs of a service on one server, with the server between them
+    fn get_route() -> Vec<RouteNode> {
+        vec![
+            RouteNode {
+                node_type: NodeType::VNF(0, 0),
+                route_count: 1,
+                next_nodes: vec![1],
+            },
+            RouteNode {
+                node_type: NodeType::Component(0),
+                route_count: 1,
+                next_nodes: vec![2],
+            },
+            RouteNode {
+                node_type: NodeType::VNF(0, 1),
+                route_count: 1,
+                next_nodes: vec![],
+            },
+        ]
+    }
+
+    fn get_services(queue_length: usize) -> Vec<Service> {
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length,
+            size: Resources::scalar(50),
+        };
+
+        vec![Service {
+            id: 0,
+            prod_rate: 8.0,
+            vnfs: vec![vnf; 2],
+            sla: Sla::default(),
+        }]
+    }
+
+    #[test]
+    fn test_simulate() {
+        rng::seed(1);
+
+        let dc = FatTree::new(4);
+        let routes = vec![(0, get_route())];
+
+        let mut sim = Simulator::new(&dc, 40.0, 20);
+        sim.duration = 5000.0;
+
+        // Two queues in tandem with Poisson arrivals, like the queueing model assumes
+        let services = get_services(20);
+        let report = sim.simulate(&services, &routes);
+        let qm = QueueingModel::new(&dc, 40.0, 20, 0.001, 10, 30.0, 10.0);
+        let (latencies, packet_losses, _) = qm.evaluate(&services, &routes).unwrap();
+
+        let comparison = report.compare(&latencies, &packet_losses);
+        assert_eq!(comparison.len(), 1);
+        assert!((comparison[0].packets as f64 - 8.0 * 5000.0).abs() < 2000.0);
+        assert!(comparison[0].latency_error().abs() < 0.1);
+        assert!(comparison[0].simulated_packet_loss < 0.01);
+
+        let switch = &report.components[0];
+        assert!((switch.arrival_rate - 8.0).abs() < 0.4);
+        assert!((switch.utilisation - MM1K.busy(8.0, 40.0, 20)).abs() < 0.02);
+        assert_eq!(report.vnfs.len(), 1);
+        assert!((report.vnfs[0].stats.utilisation - 0.8).abs() < 0.05);
+        let latency = report.latencies[0];
+
+        // Short queues lose packets
+        let services = get_services(2);
+        let report = sim.simulate(&services, &routes);
+        let (latencies, packet_losses, _) = qm.evaluate(&services, &routes).unwrap();
+
+        let comparison = report.compare(&latencies, &packet_losses);
+        assert!(comparison[0].simulated_packet_loss > 0.2);
+        assert!(comparison[0].packet_loss_error().abs() < 0.03);
+
+        // Deterministic service times queue less
+        sim.vnf_queue = Discipline::MD1K;
+        let deterministic = sim.simulate(&get_services(20), &routes);
+        assert!(deterministic.latencies[0] < latency);
+    }
+
+    #[test]
+    fn test_branching() {
+        rng::seed(2);
+
+        let dc = FatTree::new(4);
+        let mut route = get_route();
+        route[0].next_nodes = vec![1, 3];
+        route[2].route_count = 2;
+        route.push(RouteNode {
+            node_type: NodeType::Component(1),
+            route_count: 1,
+            next_nodes: vec![2],
+        });
+
+        let mut sim = Simulator::new(&dc, 40.0, 20);
+        sim.duration = 2000.0;
+        let report = sim.simulate(&get_services(20), &vec![(0, route)]);
+
+        // ECMP splits the packets evenly
+        let (a, b) = (&report.components[0], &report.components[1]);
+        assert!((a.arrival_rate - 4.0).abs() < 0.3 && (b.arrival_rate - 4.0).abs() < 0.3);
+        assert!((report.vnfs[0].stats.arrival_rate - 8.0).abs() < 0.4);
+    }
+}
@@ -100,7 +100,12 @@ impl<'a> UtilisationModel<'a> {
 mod tests {
     use super::*;
     use crate::{
-        models::{datacentre::FatTree, routing, service::VNF},
+        models::{
+            datacentre::FatTree,
+            resources::Resources,
+            routing,
+            service::{Sla, VNF},
+        },
         operators::mapping::find_routes,
     };
 
@@ -123,19 +128,20 @@ mod tests {
                 VNF {
                     queue_length: sw_ql,
                     service_rate: 1.0,
-                    size: 100,
+                    size: Resources::scalar(100),
                 },
                 VNF {
                     queue_length: sw_ql,
                     service_rate: 1.0,
-                    size: 100,
+                    size: Resources::scalar(100),
                 },
                 VNF {
                     queue_length: sw_ql,
                     service_rate: 1.0,
-                    size: 100,
+                    size: Resources::scalar(100),
                 },
             ],
+            sla: Sla::default(),
         };
 
         let simple_seq = vec![0, 1, 2];
@@ -0,0 +1,122 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::solution::Solution;
+use crate::models::service::{Service, ServiceID};
+
+/**
+ * Converts the elements of solutions to something that can be written to a checkpoint and
+ * back, for solutions that borrow data such as the services of the problem instance.
+ **/
+pub trait Codec<X> {
+    type Encoded: Serialize + DeserializeOwned + Clone + Send;
+
+    fn encode(&self, x: &X) -> Self::Encoded;
+    fn decode(&self, encoded: &Self::Encoded) -> X;
+
+    /// Whether `encoded` can be decoded, as a checkpoint may not belong to this problem
+    fn check(&self, _encoded: &Self::Encoded) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn check_all(&self, solutions: &[Solution<Self::Encoded>]) -> Result<(), String> {
+        solutions
+            .iter()
+            .flat_map(|solution| &solution.point)
+            .map(|encoded| self.check(encoded))
+            .collect()
+    }
+
+    fn encode_all(&self, solutions: &[Solution<X>]) -> Vec<Solution<Self::Encoded>> {
+        solutions
+            .iter()
+            .map(|solution| Solution {
+                point: solution.point.iter().map(|x| self.encode(x)).collect(),
+                objectives: solution.objectives.clone(),
+            })
+            .collect()
+    }
+
+    fn decode_all(&self, solutions: &[Solution<Self::Encoded>]) -> Vec<Solution<X>> {
+        solutions
+            .iter()
+            .map(|solution| Solution {
+                point: solution.point.iter().map(|x| self.decode(x)).collect(),
+                objectives: solution.objectives.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Solutions of service placements are stored by the ids of the services they place
+pub struct ServiceCodec<'a> {
+    services: &'a [Service],
+}
+
+impl<'a> ServiceCodec<'a> {
+    pub fn new(services: &'a [Service]) -> ServiceCodec<'a> {
+        ServiceCodec { services }
+    }
+}
+
+impl<'a> Codec<Vec<&'a Service>> for ServiceCodec<'a> {
+    type Encoded = Vec<ServiceID>;
+
+    fn encode(&self, services: &Vec<&'a Service>) -> Vec<ServiceID> {
+        services.iter().map(|service| service.id).collect()
+    }
+
+    fn decode(&self, ids: &Vec<ServiceID>) -> Vec<&'a Service> {
+        ids.iter().map(|&id| &self.services[id]).collect()
+    }
+
+    fn check(&self, ids: &Vec<ServiceID>) -> Result<(), String> {
+        match ids.iter().find(|&&id| id >= self.services.len()) {
+            Some(id) => Err(format!(
+                "service {} is placed, but there are only {} services",
+                id,
+                self.services.len()
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        resources::Resources,
+        service::{Sla, VNF},
+    };
+
+    #[test]
+    fn test_service_codec() {
+        let services: Vec<Service> = (0..3)
+            .map(|id| Service {
+                id,
+                prod_rate: 10.0,
+                vnfs: vec![VNF {
+                    service_rate: 10.0,
+                    queue_length: 20,
+                    size: Resources::scalar(10),
+                }],
+                sla: Sla::default(),
+            })
+            .collect();
+
+        let codec = ServiceCodec::new(&services);
+        let solution = Solution::new(vec![
+            vec![&services[2], &services[0]],
+            vec![],
+            vec![&services[1]],
+        ]);
+
+        let encoded = codec.encode_all(&[solution.clone()]);
+        assert_eq!(encoded[0].point, vec![vec![2, 0], vec![], vec![1]]);
+        assert_eq!(codec.decode_all(&encoded), vec![solution]);
+
+        assert!(codec.check_all(&encoded).is_ok());
+        assert!(codec.check(&vec![1, 3]).is_err());
+    }
+}
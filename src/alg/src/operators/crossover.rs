@@ -2,6 +2,7 @@ use rand::prelude::*;
 
 use crate::operators::distance_matrix::DistanceMatrix;
 use crate::operators::solution::Solution;
+use crate::utilities::rng::thread_rng;
 
 pub trait Crossover<X> {
     fn apply(&self, parent_one: &Solution<X>, parent_two: &Solution<X>) -> Vec<Solution<X>>;
@@ -20,7 +21,7 @@ impl UniformCrossover {
 
 impl<X: Clone> Crossover<X> for UniformCrossover {
     fn apply(&self, parent_one: &Solution<X>, parent_two: &Solution<X>) -> Vec<Solution<X>> {
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
 
         if rng.gen::<f64>() > self.pc {
             return vec![parent_one.clone(), parent_two.clone()];
@@ -32,7 +33,7 @@ impl<X: Clone> Crossover<X> for UniformCrossover {
         let mut child_b = Vec::with_capacity(length);
 
         for i in 0..length {
-            if random() {
+            if rng.gen() {
                 child_a.push(parent_one[i].clone());
                 child_b.push(parent_two[i].clone());
             } else {
@@ -68,7 +69,7 @@ impl<X: Clone> Crossover<X> for NPointCrossover {
 
         let solution_len = parent_one.len();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
 
         if rng.gen::<f64>() > self.pc {
             return vec![parent_one.clone(), parent_two.clone()];
@@ -148,13 +149,13 @@ impl LocalExchange<'_> {
 
 impl<X: Clone> Crossover<X> for LocalExchange<'_> {
     fn apply(&self, parent_one: &Solution<X>, parent_two: &Solution<X>) -> Vec<Solution<X>> {
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
 
         if rng.gen::<f64>() > self.pc {
             return vec![parent_one.clone(), parent_two.clone()];
         }
 
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
         if rng.gen::<f64>() > self.pc {
             return vec![parent_one.clone(), parent_two.clone()];
         }
@@ -202,6 +203,7 @@ mod tests {
 
     use crate::models::datacentre::FatTree;
     use crate::operators::distance_matrix::build_cache;
+    use crate::utilities::rng;
 
     #[test]
     fn test_uniform_crossover() {
@@ -209,6 +211,19 @@ mod tests {
         basic_tests(crossover_func, 50);
     }
 
+    #[test]
+    fn test_uniform_crossover_seeded() {
+        let crossover_func = UniformCrossover::new(1.0);
+        let parent_one = Solution::new((0..50).map(Some).collect());
+        let parent_two = Solution::new(vec![None; 50]);
+
+        rng::seed(7);
+        let children = crossover_func.apply(&parent_one, &parent_two);
+
+        rng::seed(7);
+        assert_eq!(crossover_func.apply(&parent_one, &parent_two), children);
+    }
+
     #[test]
     fn test_n_point_crossover() {
         let crossover_func_1 = NPointCrossover::new(1.0, 1);
@@ -1,4 +1,5 @@
 use crate::models::datacentre::{Datacentre, NodeID};
+use crate::utilities::rng::thread_rng;
 use rand::prelude::*;
 use std::collections::HashSet;
 
@@ -1,29 +1,185 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
 use super::{
-    distance_matrix::DistanceMatrix, mapping::RouteNode, placement_strategies::NodeSelection,
-    solution::Constraint,
+    distance_matrix::DistanceMatrix,
+    mapping::{NodeType, RouteNode},
+    placement_strategies::NodeSelection,
+    solution::{Aggregation, Constraint, Violation},
 };
 use crate::{
     models::{
-        datacentre::Datacentre, heuristic_model::HeuristicModel, queueing_model::QueueingModel,
-        routing::RoutingTable, service::Service, utilisation_model::UtilisationModel,
+        datacentre::{Datacentre, NodeID},
+        heuristic_model::HeuristicModel,
+        queueing_model::QueueingModel,
+        resources::Resources,
+        routing::RoutingTable,
+        service::Service,
+        utilisation_model::UtilisationModel,
     },
-    utilities::metrics::mean,
+    utilities::metrics::{mean, percentile},
 };
 
 pub trait Evaluation {
-    fn evaluate_ind(&self, routes: &Vec<(usize, Vec<RouteNode>)>) -> Constraint<Vec<f64>, usize>;
+    fn evaluate_ind(
+        &self,
+        routes: &Vec<(usize, Vec<RouteNode>)>,
+    ) -> Constraint<Vec<f64>, Violation>;
+
+    /// Number of evaluations so far where the model failed, such as not converging
+    fn num_failures(&self) -> usize {
+        0
+    }
+}
+
+/**
+ * How links carrying more traffic than their bandwidth count against a placement: `ignore`
+ * them, treat each overloaded link as a violated `constraint`, or add the largest link
+ * utilisation to the objectives, `objective`.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkOverload {
+    Ignore,
+    Constraint,
+    Objective,
+}
+
+impl Default for LinkOverload {
+    fn default() -> Self {
+        LinkOverload::Ignore
+    }
+}
+
+impl Display for LinkOverload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkOverload::Ignore => write!(f, "ignore"),
+            LinkOverload::Constraint => write!(f, "constraint"),
+            LinkOverload::Objective => write!(f, "objective"),
+        }
+    }
+}
+
+impl FromStr for LinkOverload {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ignore" => Ok(LinkOverload::Ignore),
+            "constraint" => Ok(LinkOverload::Constraint),
+            "objective" => Ok(LinkOverload::Objective),
+            _ => Err(format!(
+                "Unknown link overload '{}', expected ignore, constraint or objective",
+                s
+            )),
+        }
+    }
+}
+
+serialize_display!(LinkOverload);
+
+/**
+ * A quantity of a placement found by the queueing model, to be minimised. Written as
+ * `mean_latency`, `max_latency`, `latency_pN` for the N-th percentile of the latencies of
+ * the services, e.g. `latency_p95`, `mean_packet_loss`, `max_packet_loss`, `energy`,
+ * `active_servers`, `vnf_instances` or `max_link_utilisation`.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    MeanLatency,
+    MaxLatency,
+    LatencyPercentile(f64),
+    MeanPacketLoss,
+    MaxPacketLoss,
+    Energy,
+    ActiveServers,
+    VnfInstances,
+    MaxLinkUtilisation,
+}
+
+/// The objectives the experiments were originally run with
+pub const DEFAULT_OBJECTIVES: [Objective; 3] = [
+    Objective::MeanLatency,
+    Objective::MeanPacketLoss,
+    Objective::Energy,
+];
+
+impl Display for Objective {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Objective::MeanLatency => write!(f, "mean_latency"),
+            Objective::MaxLatency => write!(f, "max_latency"),
+            Objective::LatencyPercentile(p) => write!(f, "latency_p{}", p),
+            Objective::MeanPacketLoss => write!(f, "mean_packet_loss"),
+            Objective::MaxPacketLoss => write!(f, "max_packet_loss"),
+            Objective::Energy => write!(f, "energy"),
+            Objective::ActiveServers => write!(f, "active_servers"),
+            Objective::VnfInstances => write!(f, "vnf_instances"),
+            Objective::MaxLinkUtilisation => write!(f, "max_link_utilisation"),
+        }
+    }
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+
+        if lower.starts_with("latency_p") {
+            return match lower["latency_p".len()..].parse::<f64>() {
+                Ok(p) if p > 0.0 && p <= 100.0 => Ok(Objective::LatencyPercentile(p)),
+                _ => Err(format!(
+                    "Invalid latency percentile in '{}', expected e.g. latency_p95",
+                    s
+                )),
+            };
+        }
+
+        match lower.as_str() {
+            "mean_latency" => Ok(Objective::MeanLatency),
+            "max_latency" => Ok(Objective::MaxLatency),
+            "mean_packet_loss" => Ok(Objective::MeanPacketLoss),
+            "max_packet_loss" => Ok(Objective::MaxPacketLoss),
+            "energy" => Ok(Objective::Energy),
+            "active_servers" => Ok(Objective::ActiveServers),
+            "vnf_instances" => Ok(Objective::VnfInstances),
+            "max_link_utilisation" => Ok(Objective::MaxLinkUtilisation),
+            _ => Err(format!(
+                "Unknown objective '{}', expected mean_latency, max_latency, latency_pN, mean_packet_loss, max_packet_loss, energy, active_servers, vnf_instances or max_link_utilisation",
+                s
+            )),
+        }
+    }
 }
 
+serialize_display!(Objective);
+
 // --- Queueing Model
 #[derive(Clone)]
 pub struct QueueingEval<'a, N: NodeSelection> {
-    capacities: &'a Vec<usize>,
+    capacities: &'a Vec<Resources>,
     distance_matrix: &'a DistanceMatrix,
     pub queueing_model: QueueingModel<'a>,
     routing_tables: &'a Vec<RoutingTable>,
     services: &'a Vec<Service>,
     node_selection: N,
     pub use_hf_cnstr: bool,
+    pub link_overload: LinkOverload,
+    pub aggregation: Aggregation,
+
+    // Minimised in this order
+    pub objectives: Vec<Objective>,
+
+    // Shared by the clones used by each thread
+    failures: Arc<AtomicUsize>,
 }
 
 impl<'a, N: NodeSelection> QueueingEval<'a, N> {
@@ -31,7 +187,7 @@ impl<'a, N: NodeSelection> QueueingEval<'a, N> {
         queueing_model: QueueingModel<'a>,
         routing_tables: &'a Vec<RoutingTable>,
         distance_matrix: &'a DistanceMatrix,
-        capacities: &'a Vec<usize>,
+        capacities: &'a Vec<Resources>,
         services: &'a Vec<Service>,
         node_selection: N,
     ) -> QueueingEval<'a, N> {
@@ -43,33 +199,111 @@ impl<'a, N: NodeSelection> QueueingEval<'a, N> {
             services,
             node_selection,
             use_hf_cnstr: true,
+            link_overload: LinkOverload::Ignore,
+            aggregation: Aggregation::default(),
+            objectives: DEFAULT_OBJECTIVES.to_vec(),
+            failures: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn num_objectives(&self) -> usize {
+        self.objectives.len()
+    }
+
+    fn infeasible(&self, violation: Violation) -> Constraint<Vec<f64>, Violation> {
+        if self.use_hf_cnstr {
+            Constraint::Infeasible(self.aggregation.aggregate(violation))
+        } else {
+            Constraint::Infeasible(Violation::default())
         }
     }
 }
 
 impl<NS: NodeSelection> Evaluation for QueueingEval<'_, NS> {
-    fn evaluate_ind(&self, routes: &Vec<(usize, Vec<RouteNode>)>) -> Constraint<Vec<f64>, usize> {
+    fn evaluate_ind(
+        &self,
+        routes: &Vec<(usize, Vec<RouteNode>)>,
+    ) -> Constraint<Vec<f64>, Violation> {
         let num_unplaced = num_unplaced(&routes, &self.services);
         if num_unplaced > 0 {
-            return if self.use_hf_cnstr {
-                Constraint::Infeasible(num_unplaced)
-            } else {
-                Constraint::Infeasible(0)
-            };
+            return self.infeasible(Violation {
+                unplaced: num_unplaced as f64,
+                ..Violation::default()
+            });
+        }
+
+        let needs_links = self.link_overload == LinkOverload::Constraint
+            || self.objectives.contains(&Objective::MaxLinkUtilisation);
+
+        let result = if needs_links {
+            self.queueing_model
+                .evaluate_with_links(&self.services, &routes)
+        } else {
+            self.queueing_model
+                .evaluate(&self.services, &routes)
+                .map(|(latencies, pls, energy)| (latencies, pls, energy, Vec::new()))
+        };
+
+        let (latencies, pls, energy, link_utilisations) = match result {
+            Ok(result) => result,
+            Err(_) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+
+                return self.infeasible(Violation {
+                    failed: 1.0,
+                    ..Violation::default()
+                });
+            }
+        };
+
+        let mut violation = Violation {
+            sla: sla_violations(&self.services, &latencies, &pls)
+                .iter()
+                .sum(),
+            ..Violation::default()
+        };
+
+        // Each direction of a link is counted separately
+        let link_utilisations = link_utilisations.iter().flatten();
+        if self.link_overload == LinkOverload::Constraint {
+            violation.links = link_utilisations.clone().map(|&u| (u - 1.0).max(0.0)).sum();
         }
 
-        let (latencies, pls, energy) = self.queueing_model.evaluate(&self.services, &routes);
+        if violation.is_violated() {
+            return self.infeasible(violation);
+        }
+
+        let max = |values: &Vec<f64>| values.iter().cloned().fold(0.0, f64::max);
+
+        let objectives = self
+            .objectives
+            .iter()
+            .map(|objective| match objective {
+                Objective::MeanLatency => mean(&latencies),
+                Objective::MaxLatency => max(&latencies),
+                Objective::LatencyPercentile(p) => percentile(&latencies, *p),
+                Objective::MeanPacketLoss => mean(&pls),
+                Objective::MaxPacketLoss => max(&pls),
+                Objective::Energy => energy,
+                Objective::ActiveServers => active_servers(routes) as f64,
+                Objective::VnfInstances => vnf_instances(routes) as f64,
+                Objective::MaxLinkUtilisation => {
+                    link_utilisations.clone().cloned().fold(0.0, f64::max)
+                }
+            })
+            .collect();
 
-        let avg_latency = mean(&latencies);
-        let avg_pl = mean(&pls);
+        Constraint::Feasible(objectives)
+    }
 
-        Constraint::Feasible(vec![avg_latency, avg_pl, energy])
+    fn num_failures(&self) -> usize {
+        self.failures.load(Ordering::Relaxed)
     }
 }
 
 // --- Utilisation Model
 pub struct UtilisationEval<'a, N: NodeSelection> {
-    capacities: Vec<usize>,
+    capacities: Vec<Resources>,
     distance_matrix: &'a DistanceMatrix,
     util_model: UtilisationModel<'a>,
     routing_tables: &'a Vec<RoutingTable>,
@@ -82,7 +316,7 @@ impl<'a, N: NodeSelection> UtilisationEval<'a, N> {
         dc: &Datacentre,
         routing_tables: &'a Vec<RoutingTable>,
         distance_matrix: &'a DistanceMatrix,
-        capacities: Vec<usize>,
+        capacities: Vec<Resources>,
         services: &'a Vec<Service>,
         sw_sr: f64,
         sw_ql: usize,
@@ -103,11 +337,17 @@ impl<'a, N: NodeSelection> UtilisationEval<'a, N> {
 }
 
 impl<NS: NodeSelection> Evaluation for UtilisationEval<'_, NS> {
-    fn evaluate_ind(&self, routes: &Vec<(usize, Vec<RouteNode>)>) -> Constraint<Vec<f64>, usize> {
+    fn evaluate_ind(
+        &self,
+        routes: &Vec<(usize, Vec<RouteNode>)>,
+    ) -> Constraint<Vec<f64>, Violation> {
         // -- Evaluate solution and check feasibility
         let num_unplaced = num_unplaced(&routes, &self.services);
         if num_unplaced > 0 {
-            return Constraint::Infeasible(num_unplaced);
+            return Constraint::Infeasible(Aggregation::default().aggregate(Violation {
+                unplaced: num_unplaced as f64,
+                ..Violation::default()
+            }));
         }
 
         let (service_utilisation, energy) =
@@ -124,7 +364,7 @@ impl<NS: NodeSelection> Evaluation for UtilisationEval<'_, NS> {
 pub struct HeuristicEval<'a, N: NodeSelection> {
     routing_tables: &'a Vec<RoutingTable>,
     distance_matrix: &'a DistanceMatrix,
-    capacities: Vec<usize>,
+    capacities: Vec<Resources>,
     heuristic_model: HeuristicModel<'a>,
     services: &'a Vec<Service>,
     node_selection: &'a N,
@@ -135,7 +375,7 @@ impl<'a, N: NodeSelection> HeuristicEval<'a, N> {
         dc: &'a Datacentre,
         routing_tables: &'a Vec<RoutingTable>,
         distance_matrix: &'a DistanceMatrix,
-        capacities: Vec<usize>,
+        capacities: Vec<Resources>,
         services: &'a Vec<Service>,
         node_selection: &'a N,
     ) -> HeuristicEval<'a, N> {
@@ -153,11 +393,17 @@ impl<'a, N: NodeSelection> HeuristicEval<'a, N> {
 }
 
 impl<NS: NodeSelection> Evaluation for HeuristicEval<'_, NS> {
-    fn evaluate_ind(&self, routes: &Vec<(usize, Vec<RouteNode>)>) -> Constraint<Vec<f64>, usize> {
+    fn evaluate_ind(
+        &self,
+        routes: &Vec<(usize, Vec<RouteNode>)>,
+    ) -> Constraint<Vec<f64>, Violation> {
         // -- Evaluate solution and check feasibility
         let num_unplaced = num_unplaced(&routes, &self.services);
         if num_unplaced > 0 {
-            return Constraint::Infeasible(num_unplaced);
+            return Constraint::Infeasible(Aggregation::default().aggregate(Violation {
+                unplaced: num_unplaced as f64,
+                ..Violation::default()
+            }));
         }
 
         let (perc_used, len) = self.heuristic_model.evaluate(&routes);
@@ -175,3 +421,142 @@ pub fn num_unplaced(routes: &Vec<(usize, Vec<RouteNode>)>, services: &Vec<Servic
 
     counts.iter().filter(|&&count| count == 0).count()
 }
+
+/// Servers hosting at least one VNF
+pub fn active_servers(routes: &Vec<(usize, Vec<RouteNode>)>) -> usize {
+    let servers: HashSet<NodeID> = routes
+        .iter()
+        .flat_map(|(_, route)| route)
+        .filter_map(|node| match node.node_type {
+            NodeType::VNF(server, _) => Some(server),
+            NodeType::Component(_) => None,
+        })
+        .collect();
+
+    servers.len()
+}
+
+/// VNFs placed, a VNF shared by several instances of a service counts once
+pub fn vnf_instances(routes: &Vec<(usize, Vec<RouteNode>)>) -> usize {
+    let vnfs: HashSet<(usize, NodeID, usize)> = routes
+        .iter()
+        .flat_map(|(service, route)| route.iter().map(move |node| (*service, node)))
+        .filter_map(|(service, node)| match node.node_type {
+            NodeType::VNF(server, stage) => Some((service, server, stage)),
+            NodeType::Component(_) => None,
+        })
+        .collect();
+
+    vnfs.len()
+}
+
+/// How far each service misses its SLA with the given latencies and packet losses
+pub fn sla_violations(
+    services: &Vec<Service>,
+    latencies: &[f64],
+    packet_losses: &[f64],
+) -> Vec<f64> {
+    services
+        .iter()
+        .map(|service| {
+            service
+                .sla
+                .violation(latencies[service.id], packet_losses[service.id])
+        })
+        .collect()
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            datacentre::FatTree,
+            routing::get_tables,
+            service::{Sla, VNF},
+        },
+        operators::{
+            distance_matrix::build_cache, mapping::find_routes, placement_strategies::FirstFit,
+        },
+    };
+
+    #[test]
+    fn test_route_counts() {
+        let dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+
+        // Both instances of service 0 share its first VNF on server 0
+        let routes = vec![
+            (0, find_routes(vec![0, 1], &rt)),
+            (0, find_routes(vec![0, 2], &rt)),
+            (1, find_routes(vec![3], &rt)),
+        ];
+
+        assert_eq!(active_servers(&routes), 4);
+        assert_eq!(vnf_instances(&routes), 4);
+    }
+
+    #[test]
+    fn test_sla_violation() {
+        let dc = FatTree::new(4);
+        let rt = get_tables(&dc);
+        let dm = build_cache(&dc, dc.num_servers);
+        let capacities = vec![Resources::scalar(100); dc.num_servers];
+
+        let vnf = VNF {
+            service_rate: 10.0,
+            queue_length: 20,
+            size: Resources::scalar(40),
+        };
+        let mut services: Vec<Service> = (0..2)
+            .map(|id| Service {
+                id,
+                prod_rate: 5.0,
+                vnfs: vec![vnf; 2],
+                sla: Sla::default(),
+            })
+            .collect();
+        let routes = vec![
+            (0, find_routes(vec![0, 1], &rt)),
+            (1, find_routes(vec![2, 3], &rt)),
+        ];
+
+        let qm = QueueingModel::new(&dc, 80.0, 80, 0.01, 10, 30.0, 10.0);
+        let (latencies, _, _) = qm.evaluate(&services, &routes).unwrap();
+
+        // 100% and 25% over their bounds, the excess is added up rather than the services
+        services[0].sla.latency = Some(latencies[0] / 2.0);
+        services[1].sla.latency = Some(latencies[1] / 1.25);
+
+        let evaluate = QueueingEval::new(qm, &rt, &dm, &capacities, &services, FirstFit::new());
+        match evaluate.evaluate_ind(&routes) {
+            Constraint::Infeasible(violation) => {
+                assert!((violation.sla - 1.25).abs() < 1e-9);
+                assert_eq!(violation.total, violation.sla);
+            }
+            _ => panic!("Both services miss their SLA"),
+        }
+
+        services[1].sla = Sla::default();
+        assert_eq!(
+            sla_violations(&services, &latencies, &[0.0, 0.0]),
+            vec![1.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_parse_objective() {
+        assert_eq!("energy".parse(), Ok(Objective::Energy));
+        assert_eq!(
+            "latency_p99.5".parse(),
+            Ok(Objective::LatencyPercentile(99.5))
+        );
+        assert!("latency_p101".parse::<Objective>().is_err());
+        assert!("throughput".parse::<Objective>().is_err());
+        assert_eq!(
+            Objective::LatencyPercentile(95.0).to_string(),
+            "latency_p95"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use rand::prelude::*;
 
-use crate::{models::service::Service, operators::solution::Solution};
+use crate::{models::service::Service, operators::solution::Solution, utilities::rng::thread_rng};
 
 pub trait InitPop<X> {
     fn apply(&self, pop_size: usize) -> Vec<Solution<X>>;
@@ -24,10 +24,11 @@ impl<'a> InitPop<usize> for SAIntStringInitialisation<'a> {
     fn apply(&self, pop_size: usize) -> Vec<Solution<usize>> {
         let mut population = Vec::new();
 
+        // Sized by the first resource type
         let mut min_size = 0;
         for service in self.services {
             for vnf in &service.vnfs {
-                min_size = min_size + vnf.size;
+                min_size = min_size + vnf.size[0];
             }
         }
 
@@ -67,12 +68,13 @@ impl ServiceAwareInitialisation<'_> {
 impl<'a> InitPop<Vec<&'a Service>> for ServiceAwareInitialisation<'a> {
     fn apply(&self, pop_size: usize) -> Vec<Solution<Vec<&'a Service>>> {
         let mut population = Vec::new();
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
 
+        // Sized by the first resource type
         let mut min_size = 0;
         for service in self.services {
             for vnf in &service.vnfs {
-                min_size = min_size + vnf.size;
+                min_size = min_size + vnf.size[0];
             }
         }
 
@@ -2,10 +2,12 @@ use std::collections::{HashMap, VecDeque};
 
 use super::solution::Solution;
 use crate::models::datacentre::NodeID;
-use crate::models::{routing::RoutingTable, service::Service};
+use crate::models::{resources::Resources, routing::RoutingTable, service::Service};
 use crate::operators::distance_matrix::DistanceMatrix;
 use crate::operators::placement_strategies::NodeSelection;
-use rand::{thread_rng, Rng};
+use crate::utilities::rng::thread_rng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 pub trait Mapping<X> {
     fn apply(&self, ind: &Solution<X>) -> Vec<(usize, Vec<RouteNode>)>;
@@ -14,7 +16,7 @@ pub trait Mapping<X> {
 pub struct IntStringToRouteMapping<'a, X: NodeSelection + Clone> {
     node_selection: X,
     services: &'a Vec<Service>,
-    capacities: &'a Vec<usize>,
+    capacities: &'a Vec<Resources>,
     distance_matrix: &'a DistanceMatrix,
     routing_tables: &'a Vec<RoutingTable>,
 }
@@ -23,7 +25,7 @@ impl<'a, X: NodeSelection + Clone> IntStringToRouteMapping<'a, X> {
     pub fn new(
         node_selection: X,
         services: &'a Vec<Service>,
-        capacities: &'a Vec<usize>,
+        capacities: &'a Vec<Resources>,
         distance_matrix: &'a DistanceMatrix,
         routing_tables: &'a Vec<RoutingTable>,
     ) -> IntStringToRouteMapping<'a, X> {
@@ -65,7 +67,7 @@ impl<Z: NodeSelection + Clone> Mapping<usize> for IntStringToRouteMapping<'_, Z>
 
 pub struct ServiceToRouteMapping<'a, X: NodeSelection> {
     node_selection: X,
-    capacities: &'a Vec<usize>,
+    capacities: &'a Vec<Resources>,
     distance_matrix: &'a DistanceMatrix,
     routing_tables: &'a Vec<RoutingTable>,
 }
@@ -73,7 +75,7 @@ pub struct ServiceToRouteMapping<'a, X: NodeSelection> {
 impl<'a, X: NodeSelection> ServiceToRouteMapping<'a, X> {
     pub fn new(
         node_selection: X,
-        capacities: &'a Vec<usize>,
+        capacities: &'a Vec<Resources>,
         distance_matrix: &'a DistanceMatrix,
         routing_tables: &'a Vec<RoutingTable>,
     ) -> ServiceToRouteMapping<'a, X> {
@@ -104,7 +106,7 @@ impl<Z: NodeSelection> Mapping<Vec<&Service>> for ServiceToRouteMapping<'_, Z> {
                 for vnf in &service.vnfs {
                     // Apply node selection strategy
                     let row = &self.distance_matrix[pos];
-                    let space_at = self.node_selection.select(vnf.size, row, &capacities);
+                    let space_at = self.node_selection.select(&vnf.size, row, &capacities);
 
                     if space_at.is_none() {
                         // Insufficient space, free used capacity
@@ -138,13 +140,13 @@ impl<Z: NodeSelection> Mapping<Vec<&Service>> for ServiceToRouteMapping<'_, Z> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum NodeType {
     Component(NodeID),
     VNF(NodeID, usize), // (NodeID, Stage)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RouteNode {
     pub node_type: NodeType,
     pub route_count: u32,
@@ -2,6 +2,7 @@ pub mod distance_matrix;
 pub mod placement_strategies;
 pub mod solution;
 
+pub mod codec;
 pub mod crossover;
 pub mod evaluation;
 pub mod initialisation;
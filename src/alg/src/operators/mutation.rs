@@ -1,6 +1,7 @@
 use rand::prelude::*;
 
 use crate::operators::solution::Solution;
+use crate::utilities::rng::thread_rng;
 
 pub trait Mutation<X> {
     fn apply(&self, solution: &Solution<X>) -> Solution<X>;
@@ -22,7 +23,7 @@ impl<X: Clone> Mutation<X> for SwapMutation {
     fn apply(&self, solution: &Solution<X>) -> Solution<X> {
         let mut solution = solution.clone();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
         if rng.gen::<f64>() > self.pm {
             return solution;
         }
@@ -64,7 +65,7 @@ impl<X: Clone> Mutation<Vec<X>> for AddRemoveMutation<X> {
     fn apply(&self, solution: &Solution<Vec<X>>) -> Solution<Vec<X>> {
         let mut solution = solution.clone();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
 
         if rng.gen::<f64>() > self.pm {
             return solution;
@@ -137,7 +138,7 @@ impl Mutation<usize> for IncrDecrMutation {
     fn apply(&self, solution: &Solution<usize>) -> Solution<usize> {
         let mut solution = solution.clone();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
         if rng.gen::<f64>() > self.pm {
             return solution;
         }
@@ -173,7 +174,7 @@ impl<X> AddRemoveSwapMutation<X> {
 impl<X: Clone> Mutation<Vec<X>> for AddRemoveSwapMutation<X> {
     fn apply(&self, solution: &Solution<Vec<X>>) -> Solution<Vec<X>> {
         let mut solution = solution.clone();
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
 
         if self.pm >= rng.gen_range(0.0, 1.0) {
             return solution;
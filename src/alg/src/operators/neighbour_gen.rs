@@ -1,6 +1,7 @@
 use rand::prelude::*;
 
 use crate::operators::solution::Solution;
+use crate::utilities::rng::thread_rng;
 
 pub trait NeighbourGenerator<X> {
     fn apply(&self, solution: &Solution<X>) -> Solution<X>;
@@ -20,7 +21,7 @@ impl<X> AddSwapNeighbour<X> {
 impl<X: Clone> NeighbourGenerator<Vec<X>> for AddSwapNeighbour<X> {
     fn apply(&self, solution: &Solution<Vec<X>>) -> Solution<Vec<X>> {
         let mut solution = solution.clone();
-        let mut rng = rand::thread_rng();
+        let mut rng = thread_rng();
 
         let rn = rng.gen_range(0.0, 3.0);
 
@@ -1,12 +1,13 @@
 use crate::models::datacentre::NodeID;
+use crate::models::resources::Resources;
 use crate::operators::distance_matrix::DistanceCell;
 
 pub trait NodeSelection {
     fn select(
         &self,
-        req_capacity: usize,
+        req_capacity: &Resources,
         row: &Vec<DistanceCell>,
-        capacities: &Vec<usize>,
+        capacities: &Vec<Resources>,
     ) -> Option<NodeID>;
 }
 
@@ -21,15 +22,15 @@ impl FirstFit {
 impl NodeSelection for FirstFit {
     fn select(
         &self,
-        req_capacity: usize,
+        req_capacity: &Resources,
         row: &Vec<DistanceCell>,
-        capacities: &Vec<usize>,
+        capacities: &Vec<Resources>,
     ) -> Option<NodeID> {
         for i in 0..row.len() {
             let node = row[i].node_id;
 
             // Take the first VNF that fits
-            if capacities[node] >= req_capacity {
+            if capacities[node].fits(req_capacity) {
                 return Some(node);
             }
         }
@@ -49,12 +50,12 @@ impl BestFit {
 impl NodeSelection for BestFit {
     fn select(
         &self,
-        req_capacity: usize,
+        req_capacity: &Resources,
         row: &Vec<DistanceCell>,
-        capacities: &Vec<usize>,
+        capacities: &Vec<Resources>,
     ) -> Option<NodeID> {
         let mut best_cell: Option<&DistanceCell> = None;
-        let mut best_capacity = std::usize::MAX;
+        let mut best_fit = std::f64::INFINITY;
 
         for i in 0..row.len() {
             let cell = &row[i];
@@ -66,11 +67,12 @@ impl NodeSelection for BestFit {
                 }
             }
 
-            // Take the VNF with the least available space
-            let capacity = capacities[cell.node_id];
-            if capacity < best_capacity && capacity >= req_capacity {
+            // Take the VNF with the least available space in its most limiting resource
+            let capacity = &capacities[cell.node_id];
+            let fit = capacity.fit(req_capacity);
+            if fit < best_fit && capacity.fits(req_capacity) {
                 best_cell = Some(cell);
-                best_capacity = capacity;
+                best_fit = fit;
             }
         }
 
@@ -88,12 +90,12 @@ impl WorstFit {
 impl NodeSelection for WorstFit {
     fn select(
         &self,
-        req_capacity: usize,
+        req_capacity: &Resources,
         row: &Vec<DistanceCell>,
-        capacities: &Vec<usize>,
+        capacities: &Vec<Resources>,
     ) -> Option<NodeID> {
         let mut best_cell: Option<&DistanceCell> = None;
-        let mut best_capacity = req_capacity;
+        let mut best_fit = 0.0;
 
         for i in 0..row.len() {
             let cell = &row[i];
@@ -105,11 +107,12 @@ impl NodeSelection for WorstFit {
                 }
             }
 
-            // Take the VNF with the most available space
-            let capacity = capacities[cell.node_id];
-            if capacity >= best_capacity {
+            // Take the VNF with the most available space in its most limiting resource
+            let capacity = &capacities[cell.node_id];
+            let fit = capacity.fit(req_capacity);
+            if fit >= best_fit && capacity.fits(req_capacity) {
                 best_cell = Some(cell);
-                best_capacity = capacity;
+                best_fit = fit;
             }
         }
 
@@ -130,17 +133,17 @@ mod tests {
         let selector = FirstFit::new();
 
         // Finds nearest when possible
-        let first = selector.select(20, &row, &capacities);
+        let first = selector.select(&Resources::scalar(20), &row, &capacities);
         assert!(first.is_some());
         assert_eq!(0, first.unwrap());
 
         // Finds first with capacity when possible
-        let nearest = selector.select(40, &row, &capacities);
+        let nearest = selector.select(&Resources::scalar(40), &row, &capacities);
         assert!(nearest.is_some());
         assert_eq!(2, nearest.unwrap());
 
         // Finds last if needed
-        let last = selector.select(100, &row, &capacities);
+        let last = selector.select(&Resources::scalar(100), &row, &capacities);
         assert!(last.is_some());
         assert_eq!(6, last.unwrap());
     }
@@ -151,22 +154,22 @@ mod tests {
 
         let selector = BestFit::new();
         // Finds nearest when possible
-        let first = selector.select(20, &row, &capacities);
+        let first = selector.select(&Resources::scalar(20), &row, &capacities);
         assert!(first.is_some());
         assert_eq!(0, first.unwrap());
 
         // Finds nearest server with closest capacity #1
-        let nearest_one = selector.select(30, &row, &capacities);
+        let nearest_one = selector.select(&Resources::scalar(30), &row, &capacities);
         assert!(nearest_one.is_some());
         assert_eq!(1, nearest_one.unwrap());
 
         // Finds nearest server with closest capacity (boundary check)
-        let nearest_two = selector.select(25, &row, &capacities);
+        let nearest_two = selector.select(&Resources::scalar(25), &row, &capacities);
         assert!(nearest_two.is_some());
         assert_eq!(1, nearest_two.unwrap());
 
         // Finds last if needed
-        let last = selector.select(100, &row, &capacities);
+        let last = selector.select(&Resources::scalar(100), &row, &capacities);
         assert!(last.is_some());
         assert_eq!(6, last.unwrap());
     }
@@ -178,27 +181,70 @@ mod tests {
         let selector = WorstFit::new();
 
         // Finds nearest when possible
-        let first = selector.select(20, &row, &capacities);
+        let first = selector.select(&Resources::scalar(20), &row, &capacities);
         assert!(first.is_some());
         assert_eq!(0, first.unwrap());
 
         // Finds nearest server with worst capacity #1
-        let nearest_one = selector.select(30, &row, &capacities);
+        let nearest_one = selector.select(&Resources::scalar(30), &row, &capacities);
         assert!(nearest_one.is_some());
         assert_eq!(2, nearest_one.unwrap());
 
         // Finds nearest server with closest capacity (boundary check)
-        let nearest_two = selector.select(55, &row, &capacities);
+        let nearest_two = selector.select(&Resources::scalar(55), &row, &capacities);
         assert!(nearest_two.is_some());
         assert_eq!(5, nearest_two.unwrap());
 
         // Finds last if needed
-        let last = selector.select(100, &row, &capacities);
+        let last = selector.select(&Resources::scalar(100), &row, &capacities);
         assert!(last.is_some());
         assert_eq!(6, last.unwrap());
     }
 
-    fn make_row() -> (Vec<DistanceCell>, Vec<usize>) {
+    #[test]
+    fn test_resource_types() {
+        let (row, _) = make_row();
+
+        // CPU and memory, only the last dimension of servers 0 and 1 is too small
+        let capacities: Vec<Resources> = [
+            [20, 10],
+            [40, 30],
+            [30, 80],
+            [60, 45],
+            [25, 70],
+            [60, 60],
+            [100, 100],
+        ]
+        .iter()
+        .map(|amounts| Resources::new(amounts).unwrap())
+        .collect();
+        let vnf = Resources::new(&[20, 40]).unwrap();
+
+        assert_eq!(FirstFit::new().select(&vnf, &row, &capacities), Some(2));
+
+        // Server 3 fits 1.125 times by memory, server 2 fits 1.5 times by CPU
+        assert_eq!(BestFit::new().select(&vnf, &row, &capacities), Some(3));
+        assert_eq!(WorstFit::new().select(&vnf, &row, &capacities), Some(2));
+
+        let too_big = Resources::new(&[10, 101]).unwrap();
+        assert_eq!(FirstFit::new().select(&too_big, &row, &capacities), None);
+        assert_eq!(BestFit::new().select(&too_big, &row, &capacities), None);
+        assert_eq!(WorstFit::new().select(&too_big, &row, &capacities), None);
+    }
+
+    #[test]
+    fn test_zero_size() {
+        let (mut row, capacities) = make_row();
+        row.remove(0);
+
+        // Every server fits a VNF that needs nothing, the fullest or emptiest nearest one is taken
+        let nothing = Resources::scalar(0);
+        assert_eq!(FirstFit::new().select(&nothing, &row, &capacities), Some(1));
+        assert_eq!(BestFit::new().select(&nothing, &row, &capacities), Some(1));
+        assert_eq!(WorstFit::new().select(&nothing, &row, &capacities), Some(2));
+    }
+
+    fn make_row() -> (Vec<DistanceCell>, Vec<Resources>) {
         let row = vec![
             DistanceCell {
                 node_id: 0,
@@ -230,7 +276,10 @@ mod tests {
             },
         ];
 
-        let capacities = vec![20, 30, 50, 40, 25, 60, 100];
+        let capacities = [20, 30, 50, 40, 25, 60, 100]
+            .iter()
+            .map(|&capacity| Resources::scalar(capacity))
+            .collect();
 
         (row, capacities)
     }
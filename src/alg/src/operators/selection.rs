@@ -1,4 +1,6 @@
+use crate::utilities::rng::thread_rng;
 use rand::prelude::*;
+
 pub struct TournamentSelection<T>
 where
     T: Fn(usize, usize) -> bool,
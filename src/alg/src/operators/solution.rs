@@ -1,12 +1,16 @@
 use std::{
+    cmp::Ordering,
     fmt::{Debug, Display},
     ops::{Index, IndexMut},
+    str::FromStr,
 };
 
-#[derive(Clone, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Solution<X> {
     pub point: Vec<X>,
-    pub objectives: Constraint<Vec<f64>, usize>,
+    pub objectives: Constraint<Vec<f64>, Violation>,
 }
 
 impl<X> Solution<X> {
@@ -34,9 +38,9 @@ impl<X> Solution<X> {
             (Constraint::Infeasible(_), Constraint::Feasible(_)) => {
                 return false;
             }
-            // Infeasible solutions with a lower rating are better
+            // Infeasible solutions with a lower violation are better
             (Constraint::Infeasible(x), Constraint::Infeasible(y)) => {
-                return x < y;
+                return x.is_better(y);
             }
             // Otherwise check dominance
             _ => {}
@@ -77,7 +81,7 @@ impl<X> IndexMut<usize> for Solution<X> {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum Constraint<X, Y> {
     Feasible(X),
     Infeasible(Y),
@@ -116,15 +120,176 @@ where
             _ => panic!("Attempted to unwrap infeasible or undefined value."),
         }
     }
+}
 
-    pub fn unwrap_infeasible(&self) -> Y {
-        match self {
-            Constraint::Infeasible(value) => value.clone(),
-            _ => panic!("Attempted to unwrap feasible or undefined value."),
+/// Number of kinds of constraint a `Violation` measures and weighs into its total
+pub const NUM_MEASURES: usize = 3;
+
+/**
+ * How far an infeasible solution is from being feasible, by each kind of constraint it
+ * breaks. Solutions are compared by the `total`, the measures aggregated by the `Aggregation`
+ * of the evaluation, so a solution that misses its constraints by less is better.
+ *
+ * There is no measure of overloaded server capacity, as the mapping only places a VNF on a
+ * server it fits in and leaves the services it cannot fit unplaced.
+ **/
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Violation {
+    /// Services with no instance placed
+    pub unplaced: f64,
+
+    /**
+     * 1 when the model could not evaluate the solution, e.g. if it did not converge. Its
+     * other measures are then unknown, so it is worse than any evaluated solution rather
+     * than being weighed into the total.
+     **/
+    pub failed: f64,
+
+    /// How far the services miss their SLA, relative to the bounds
+    pub sla: f64,
+
+    /// Utilisation over 1 summed over the overloaded links
+    pub links: f64,
+
+    pub total: f64,
+}
+
+impl Violation {
+    /// Infeasible only because `num_unplaced` services could not be placed
+    #[cfg(test)]
+    pub fn unplaced(num_unplaced: usize) -> Violation {
+        Violation {
+            unplaced: num_unplaced as f64,
+            total: num_unplaced as f64,
+            ..Violation::default()
+        }
+    }
+
+    /// The measures weighed into the total
+    pub fn measures(&self) -> [f64; NUM_MEASURES] {
+        [self.unplaced, self.sla, self.links]
+    }
+
+    pub fn is_violated(&self) -> bool {
+        self.failed > 0.0 || self.measures().iter().any(|&measure| measure > 0.0)
+    }
+
+    /// Failed evaluations come last whatever the aggregation, the others by their total
+    pub fn compare(&self, other: &Violation) -> Ordering {
+        let failed = |violation: &Violation| violation.failed > 0.0;
+
+        failed(self).cmp(&failed(other)).then_with(|| {
+            self.total
+                .partial_cmp(&other.total)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// Whether this is closer to being feasible than `other`
+    pub fn is_better(&self, other: &Violation) -> bool {
+        self.compare(other) == Ordering::Less
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.total)
+    }
+}
+
+/**
+ * How the measures of a violation are combined into its total, a weighted `sum` or the
+ * largest weighted measure, `max`. Written as `sum` or `max`, optionally followed by the
+ * weights of the unplaced, SLA and link measures, e.g. `sum:10:1:1`. The weights are 1 if
+ * not given. Failed evaluations are not weighed, they always rank last.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    Sum([f64; NUM_MEASURES]),
+    Max([f64; NUM_MEASURES]),
+}
+
+impl Default for Aggregation {
+    fn default() -> Self {
+        Aggregation::Sum([1.0; NUM_MEASURES])
+    }
+}
+
+impl Aggregation {
+    /// Sets the total of `violation`
+    pub fn aggregate(&self, mut violation: Violation) -> Violation {
+        let measures = violation.measures();
+        let weighted = |weights: &[f64; NUM_MEASURES]| -> Vec<f64> {
+            measures.iter().zip(weights).map(|(m, w)| m * w).collect()
+        };
+
+        violation.total = match self {
+            Aggregation::Sum(weights) => weighted(weights).iter().sum(),
+            Aggregation::Max(weights) => weighted(weights).into_iter().fold(0.0, f64::max),
+        };
+
+        violation
+    }
+}
+
+impl Display for Aggregation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, weights) = match self {
+            Aggregation::Sum(weights) => ("sum", weights),
+            Aggregation::Max(weights) => ("max", weights),
+        };
+
+        write!(f, "{}", name)?;
+        if weights.iter().any(|&weight| weight != 1.0) {
+            for weight in weights {
+                write!(f, ":{}", weight)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Aggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid aggregation '{}', expected sum or max, optionally followed by {} weights, e.g. sum:10:1:1",
+                s, NUM_MEASURES
+            )
+        };
+
+        let lower = s.to_lowercase();
+        let mut parts = lower.split(':').map(|part| part.trim());
+        let kind = parts.next().unwrap();
+
+        let mut weights = [1.0; NUM_MEASURES];
+        let values: Vec<&str> = parts.collect();
+        if !values.is_empty() {
+            if values.len() != NUM_MEASURES {
+                return Err(invalid());
+            }
+
+            for (weight, value) in weights.iter_mut().zip(values) {
+                match value.parse::<f64>() {
+                    Ok(value) if value >= 0.0 => *weight = value,
+                    _ => return Err(invalid()),
+                }
+            }
+        }
+
+        match kind {
+            "sum" => Ok(Aggregation::Sum(weights)),
+            "max" => Ok(Aggregation::Max(weights)),
+            _ => Err(invalid()),
         }
     }
 }
 
+serialize_display!(Aggregation);
+
 impl<X: Display, Y: Display> Display for Constraint<X, Y> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -136,3 +301,69 @@ impl<X: Display, Y: Display> Display for Constraint<X, Y> {
         Ok(())
     }
 }
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregation() {
+        let violation = Violation {
+            unplaced: 1.0,
+            sla: 0.5,
+            links: 0.25,
+            ..Violation::default()
+        };
+
+        assert_eq!(Aggregation::default().aggregate(violation).total, 1.75);
+
+        let aggregation: Aggregation = "max:1:4:1".parse().unwrap();
+        assert_eq!(aggregation.aggregate(violation).total, 2.0);
+        assert_eq!(aggregation.to_string(), "max:1:4:1");
+        assert_eq!("sum".parse(), Ok(Aggregation::default()));
+        assert!("sum:1:2".parse::<Aggregation>().is_err());
+        assert!("sum:1:1:1:1".parse::<Aggregation>().is_err());
+        assert!("min".parse::<Aggregation>().is_err());
+
+        assert!(violation.is_violated());
+        assert!(!Violation::default().is_violated());
+    }
+
+    #[test]
+    fn test_dominates() {
+        let mut solutions = vec![Solution::new(vec![0]); 3];
+        solutions[0].objectives = Constraint::Feasible(vec![2.0, 2.0]);
+        solutions[1].objectives = Constraint::Infeasible(Violation::unplaced(1));
+        solutions[2].objectives =
+            Constraint::Infeasible(Aggregation::default().aggregate(Violation {
+                sla: 0.5,
+                ..Violation::default()
+            }));
+
+        assert!(solutions[0].dominates(&solutions[1]));
+        assert!(solutions[2].dominates(&solutions[1]));
+        assert!(!solutions[1].dominates(&solutions[2]));
+    }
+
+    #[test]
+    fn test_failed() {
+        let aggregation: Aggregation = "max:1:1:1".parse().unwrap();
+        let failed = aggregation.aggregate(Violation {
+            failed: 1.0,
+            ..Violation::default()
+        });
+        let unplaced = aggregation.aggregate(Violation {
+            unplaced: 20.0,
+            sla: 3.0,
+            ..Violation::default()
+        });
+
+        // Worse even though it adds up to less
+        assert!(failed.is_violated());
+        assert!(failed.total < unplaced.total);
+        assert!(unplaced.is_better(&failed));
+        assert!(!failed.is_better(&unplaced));
+        assert_eq!(failed.compare(&failed), Ordering::Equal);
+    }
+}
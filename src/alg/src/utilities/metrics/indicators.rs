@@ -0,0 +1,447 @@
+use rand::Rng;
+
+use super::mean;
+use crate::{operators::solution::Solution, utilities::rng};
+
+// Quality indicators of an approximation of the Pareto front, with every objective minimised.
+// The indicators work on objective vectors, which `objectives` takes from the feasible
+// solutions of a population or `NonDominatedSet`. Fronts from different runs are only
+// comparable once they are normalised against the same ideal and nadir points, e.g. the
+// `bounds` of every front being compared. The hypervolume of normalised fronts is usually
+// taken against a reference point just beyond the nadir, such as 1.000001 in every objective.
+
+/// Samples `hypervolume` takes once there are too many objectives to compute it exactly
+pub const MONTE_CARLO_SAMPLES: usize = 1_000_000;
+
+/// The objective vectors of the feasible solutions
+pub fn objectives<X>(solutions: &[Solution<X>]) -> Vec<Vec<f64>> {
+    solutions
+        .iter()
+        .filter(|solution| solution.objectives.is_feasible())
+        .map(|solution| solution.objectives.unwrap())
+        .collect()
+}
+
+/// The ideal and nadir points, the best and worst value of each objective over the points
+pub fn bounds(points: &[Vec<f64>]) -> (Vec<f64>, Vec<f64>) {
+    let num_obj = points.first().map_or(0, |point| point.len());
+
+    let mut ideal = vec![std::f64::INFINITY; num_obj];
+    let mut nadir = vec![std::f64::NEG_INFINITY; num_obj];
+
+    for point in points {
+        for i in 0..num_obj {
+            ideal[i] = ideal[i].min(point[i]);
+            nadir[i] = nadir[i].max(point[i]);
+        }
+    }
+
+    (ideal, nadir)
+}
+
+/// Scales the points so the ideal point is at 0 and the nadir at 1, objectives where the two
+/// are equal are mapped to 0
+pub fn normalise(points: &[Vec<f64>], ideal: &[f64], nadir: &[f64]) -> Vec<Vec<f64>> {
+    points
+        .iter()
+        .map(|point| {
+            point
+                .iter()
+                .zip(ideal.iter().zip(nadir))
+                .map(|(x, (min, max))| {
+                    if max > min {
+                        (x - min) / (max - min)
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/**
+ * The volume of objective space dominated by the points and bounded by the reference point.
+ * It is exact for up to three objectives and estimated from `MONTE_CARLO_SAMPLES` samples of
+ * the current thread's generator for more.
+ **/
+pub fn hypervolume(points: &[Vec<f64>], reference: &[f64]) -> f64 {
+    if reference.len() <= 3 {
+        hypervolume_exact(points, reference)
+    } else {
+        hypervolume_monte_carlo(
+            points,
+            reference,
+            MONTE_CARLO_SAMPLES,
+            &mut rng::thread_rng(),
+        )
+    }
+}
+
+/**
+ * The hypervolume computed with the WFG algorithm (While et al., A Fast Way of Calculating
+ * Exact Hypervolumes, 2012). The volume is the sum of the volume each point dominates
+ * exclusively, which is found by removing the volume of the points after it, limited to the
+ * box of the point. Its time grows exponentially with the number of objectives.
+ **/
+pub fn hypervolume_exact(points: &[Vec<f64>], reference: &[f64]) -> f64 {
+    wfg(bounded_front(points, reference), reference)
+}
+
+/// Estimates the hypervolume from the proportion of the samples in the box between the best
+/// values of the points and the reference point that are dominated by a point
+pub fn hypervolume_monte_carlo<R: Rng>(
+    points: &[Vec<f64>],
+    reference: &[f64],
+    samples: usize,
+    rng: &mut R,
+) -> f64 {
+    let front = bounded_front(points, reference);
+    if front.is_empty() || samples == 0 {
+        return 0.0;
+    }
+
+    let (lower, _) = bounds(&front);
+    let volume: f64 = lower.iter().zip(reference).map(|(l, r)| r - l).product();
+
+    let mut sample = vec![0.0; reference.len()];
+    let mut num_dominated = 0;
+
+    for _ in 0..samples {
+        for (i, x) in sample.iter_mut().enumerate() {
+            *x = rng.gen_range(lower[i], reference[i]);
+        }
+
+        if front.iter().any(|point| weakly_dominates(point, &sample)) {
+            num_dominated = num_dominated + 1;
+        }
+    }
+
+    volume * num_dominated as f64 / samples as f64
+}
+
+/// Inverted generational distance, the mean distance from each point of the reference front
+/// to the closest of the points
+pub fn igd(points: &[Vec<f64>], reference_front: &[Vec<f64>]) -> f64 {
+    mean_min_distance(reference_front, points, |r, p| euclidean(p, r))
+}
+
+/// IGD+ (Ishibuchi et al., 2015), IGD where only the objectives a point is worse in than the
+/// reference point count towards their distance, which makes it weakly Pareto compliant
+pub fn igd_plus(points: &[Vec<f64>], reference_front: &[Vec<f64>]) -> f64 {
+    mean_min_distance(reference_front, points, |r, p| {
+        p.iter()
+            .zip(r)
+            .map(|(p, r)| (p - r).max(0.0).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    })
+}
+
+/// Additive epsilon, the smallest amount the points must be moved by in every objective so
+/// that they weakly dominate the whole reference front
+pub fn additive_epsilon(points: &[Vec<f64>], reference_front: &[Vec<f64>]) -> f64 {
+    if points.is_empty() {
+        return std::f64::INFINITY;
+    }
+
+    reference_front
+        .iter()
+        .map(|r| {
+            points
+                .iter()
+                .map(|p| {
+                    p.iter()
+                        .zip(r)
+                        .map(|(p, r)| p - r)
+                        .fold(std::f64::NEG_INFINITY, f64::max)
+                })
+                .fold(std::f64::INFINITY, f64::min)
+        })
+        .fold(std::f64::NEG_INFINITY, f64::max)
+}
+
+/**
+ * Generalised spread (Zhou et al., 2006), how far the points are from the extremes of the
+ * reference front, those with the largest value of each objective, and how evenly they are
+ * spaced. 0 is a perfect spread, fewer than two points have a spread of 1.
+ **/
+pub fn spread(points: &[Vec<f64>], reference_front: &[Vec<f64>]) -> f64 {
+    if points.len() < 2 {
+        return 1.0;
+    }
+
+    let num_obj = points[0].len();
+    let extremes = (0..num_obj).filter_map(|i| {
+        reference_front
+            .iter()
+            .max_by(|a, b| a[i].partial_cmp(&b[i]).unwrap())
+    });
+
+    let extreme_distance: f64 = extremes
+        .map(|extreme| {
+            points
+                .iter()
+                .map(|p| euclidean(p, extreme))
+                .fold(std::f64::INFINITY, f64::min)
+        })
+        .sum();
+
+    let distances = nearest_distances(points, euclidean);
+    let mean_distance = mean(&distances);
+    let deviation: f64 = distances.iter().map(|d| (d - mean_distance).abs()).sum();
+
+    let denominator = extreme_distance + points.len() as f64 * mean_distance;
+    if denominator > 0.0 {
+        (extreme_distance + deviation) / denominator
+    } else {
+        1.0
+    }
+}
+
+/// Spacing (Schott, 1995), the standard deviation of the Manhattan distance from each point to
+/// its nearest neighbour, 0 when the points are evenly spaced
+pub fn spacing(points: &[Vec<f64>]) -> f64 {
+    if points.len() < 2 {
+        return 0.0;
+    }
+
+    let distances = nearest_distances(points, |a, b| {
+        a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum()
+    });
+    let mean_distance = mean(&distances);
+
+    let sum_squares: f64 = distances.iter().map(|d| (d - mean_distance).powi(2)).sum();
+
+    (sum_squares / (points.len() - 1) as f64).sqrt()
+}
+
+// The non-dominated points that dominate the reference point, the only ones adding volume
+fn bounded_front(points: &[Vec<f64>], reference: &[f64]) -> Vec<Vec<f64>> {
+    let bounded = points
+        .iter()
+        .filter(|point| point.iter().zip(reference).all(|(x, r)| x < r))
+        .cloned()
+        .collect();
+
+    nondominated(bounded)
+}
+
+fn wfg(mut front: Vec<Vec<f64>>, reference: &[f64]) -> f64 {
+    if front.is_empty() {
+        return 0.0;
+    }
+
+    if reference.len() == 2 {
+        return hypervolume_2d(front, reference);
+    }
+
+    // Sorted, the points after each one are mostly worse in the first objective and so
+    // limiting them to its box leaves a small front
+    front.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap());
+
+    (0..front.len())
+        .map(|k| {
+            let limited = front[k + 1..]
+                .iter()
+                .map(|q| q.iter().zip(&front[k]).map(|(q, p)| q.max(*p)).collect())
+                .collect();
+
+            let inclusive: f64 = front[k].iter().zip(reference).map(|(x, r)| r - x).product();
+
+            inclusive - wfg(nondominated(limited), reference)
+        })
+        .sum()
+}
+
+// Sweeps the non-dominated points in order of the first objective, the second then decreases
+fn hypervolume_2d(mut front: Vec<Vec<f64>>, reference: &[f64]) -> f64 {
+    front.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap());
+
+    let mut volume = 0.0;
+    for (i, point) in front.iter().enumerate() {
+        let next_x = front.get(i + 1).map_or(reference[0], |next| next[0]);
+        volume = volume + (next_x - point[0]) * (reference[1] - point[1]);
+    }
+
+    volume
+}
+
+/// Removes the points that are weakly dominated by another, keeping one of any duplicates
+pub fn nondominated(points: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let mut front: Vec<Vec<f64>> = Vec::with_capacity(points.len());
+
+    for point in points {
+        if front.iter().any(|other| weakly_dominates(other, &point)) {
+            continue;
+        }
+
+        front.retain(|other| !weakly_dominates(&point, other));
+        front.push(point);
+    }
+
+    front
+}
+
+fn weakly_dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a <= b)
+}
+
+fn euclidean(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn mean_min_distance(
+    from: &[Vec<f64>],
+    to: &[Vec<f64>],
+    distance: impl Fn(&[f64], &[f64]) -> f64,
+) -> f64 {
+    if to.is_empty() {
+        return std::f64::INFINITY;
+    }
+    if from.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = from
+        .iter()
+        .map(|a| {
+            to.iter()
+                .map(|b| distance(a, b))
+                .fold(std::f64::INFINITY, f64::min)
+        })
+        .sum();
+
+    total / from.len() as f64
+}
+
+// The distance from each point to the closest other point
+fn nearest_distances(points: &[Vec<f64>], distance: impl Fn(&[f64], &[f64]) -> f64) -> Vec<f64> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, b)| distance(a, b))
+                .fold(std::f64::INFINITY, f64::min)
+        })
+        .collect()
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::solution::{Constraint, Violation};
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    fn approx_eq(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_objectives() {
+        let mut solutions = vec![Solution::new(vec![0]); 3];
+        solutions[0].objectives = Constraint::Feasible(vec![1.0, 2.0]);
+        solutions[1].objectives = Constraint::Infeasible(Violation::unplaced(2));
+        solutions[2].objectives = Constraint::Feasible(vec![3.0, 0.0]);
+
+        let points = objectives(&solutions);
+        assert_eq!(points, vec![vec![1.0, 2.0], vec![3.0, 0.0]]);
+
+        let (ideal, nadir) = bounds(&points);
+        assert_eq!(ideal, vec![1.0, 0.0]);
+        assert_eq!(nadir, vec![3.0, 2.0]);
+
+        let normalised = normalise(&points, &ideal, &vec![3.0, 0.0]);
+        assert_eq!(normalised, vec![vec![0.0, 0.0], vec![1.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_hypervolume() {
+        // Dominated points and those beyond the reference point add nothing
+        let points = vec![
+            vec![1.0, 3.0],
+            vec![2.0, 2.0],
+            vec![3.0, 1.0],
+            vec![3.0, 3.0],
+            vec![5.0, 0.0],
+        ];
+        assert!(approx_eq(hypervolume(&points, &[4.0, 4.0]), 6.0));
+
+        assert!(approx_eq(
+            hypervolume(&[vec![1.0, 1.0, 1.0]], &[2.0, 3.0, 4.0]),
+            6.0
+        ));
+
+        let points = vec![vec![1.0, 2.0, 2.0], vec![2.0, 1.0, 1.0]];
+        assert!(approx_eq(hypervolume(&points, &[3.0, 3.0, 3.0]), 5.0));
+
+        let points = vec![
+            vec![1.0, 1.0, 2.0],
+            vec![1.0, 2.0, 2.0],
+            vec![2.0, 1.0, 1.0],
+        ];
+        assert!(approx_eq(hypervolume(&points, &[3.0, 3.0, 3.0]), 6.0));
+
+        assert_eq!(hypervolume(&[], &[1.0, 1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_hypervolume_monte_carlo() {
+        let mut rng = Pcg64::seed_from_u64(0);
+        let reference = vec![1.0; 4];
+
+        let points = vec![vec![0.0; 4]];
+        let estimate = hypervolume_monte_carlo(&points, &reference, 1000, &mut rng);
+        assert!(approx_eq(estimate, 1.0));
+
+        let points = vec![
+            vec![0.2, 0.5, 0.4, 0.7],
+            vec![0.6, 0.1, 0.3, 0.5],
+            vec![0.4, 0.4, 0.8, 0.1],
+        ];
+        let exact = hypervolume_exact(&points, &reference);
+        let estimate = hypervolume_monte_carlo(&points, &reference, 100_000, &mut rng);
+        assert!((estimate - exact).abs() < 0.01 * exact);
+    }
+
+    #[test]
+    fn test_distances() {
+        let reference_front = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let points = vec![vec![0.0, 1.0]];
+
+        assert!(approx_eq(
+            igd(&points, &reference_front),
+            0.5 * 2.0f64.sqrt()
+        ));
+        assert!(approx_eq(igd_plus(&points, &reference_front), 0.5));
+        assert_eq!(igd(&reference_front, &reference_front), 0.0);
+
+        let points = vec![vec![0.5, 1.5]];
+        assert!(approx_eq(additive_epsilon(&points, &reference_front), 1.5));
+        assert_eq!(additive_epsilon(&reference_front, &reference_front), 0.0);
+    }
+
+    #[test]
+    fn test_distribution() {
+        let even = vec![vec![0.0, 2.0], vec![1.0, 1.0], vec![2.0, 0.0]];
+        assert!(approx_eq(spacing(&even), 0.0));
+        assert!(approx_eq(spread(&even, &even), 0.0));
+
+        let uneven = vec![vec![0.0, 4.0], vec![1.0, 3.0], vec![4.0, 0.0]];
+        assert!(approx_eq(spacing(&uneven), (48.0f64 / 9.0).sqrt()));
+
+        // Nearest distances of 1, 1 and 3 times 0.5 sqrt(2)
+        let uneven = vec![vec![0.0, 2.0], vec![0.5, 1.5], vec![2.0, 0.0]];
+        assert!(approx_eq(spread(&uneven, &even), 8.0 / 15.0));
+    }
+}
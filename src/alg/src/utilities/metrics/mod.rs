@@ -1,3 +1,6 @@
+pub mod indicators;
+pub mod statistics;
+
 pub fn mean(x: &Vec<f64>) -> f64 {
     x.into_iter().sum::<f64>() / x.len() as f64
 }
@@ -11,6 +14,35 @@ pub fn variance(x: &Vec<f64>) -> f64 {
     x.iter().map(|x| (x - mean_x).powf(2.0)).sum()
 }
 
+// Finds the sample standard deviation
+pub fn std_dev(x: &Vec<f64>) -> f64 {
+    if x.len() < 2 {
+        return 0.0;
+    }
+
+    (variance(x) / (x.len() - 1) as f64).sqrt()
+}
+
+// Finds the p-th percentile the same way as Matlab's prctile, which places the
+// i-th smallest of n values at the 100 * (i - 0.5) / n percentile and interpolates
+pub fn percentile(x: &Vec<f64>, p: f64) -> f64 {
+    let mut sorted = x.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let position = p / 100.0 * sorted.len() as f64 - 0.5;
+    if position <= 0.0 {
+        return sorted[0];
+    }
+    if position >= (sorted.len() - 1) as f64 {
+        return sorted[sorted.len() - 1];
+    }
+
+    let lower = position.floor() as usize;
+    let fraction = position - lower as f64;
+
+    sorted[lower] + fraction * (sorted[lower + 1] - sorted[lower])
+}
+
 // Finds the sample covariance between two series
 // Assumes that the indexes of each vector align
 pub fn cov(x: &Vec<f64>, y: &Vec<f64>) -> f64 {
@@ -67,4 +99,18 @@ mod tests {
         assert!(opposite > -1.01 && opposite < -0.99);
         assert_eq!(scaled, 1.0);
     }
+
+    #[test]
+    fn test_percentile() {
+        let x = vec![4.0, 1.0, 3.0, 2.0];
+
+        assert_eq!(percentile(&x, 0.0), 1.0);
+        assert_eq!(percentile(&x, 25.0), 1.5);
+        assert_eq!(percentile(&x, 50.0), 2.5);
+        assert_eq!(percentile(&x, 75.0), 3.5);
+        assert_eq!(percentile(&x, 100.0), 4.0);
+
+        assert!((std_dev(&x) - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(std_dev(&vec![2.0]), 0.0);
+    }
 }
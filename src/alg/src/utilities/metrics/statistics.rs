@@ -0,0 +1,308 @@
+use std::cmp::Ordering;
+
+// Non-parametric tests for comparing the results of algorithms. The p-values are two-sided
+// and, like Matlab's ranksum and signrank, exact for small samples without ties and from the
+// normal approximation, with tie and continuity corrections, otherwise.
+
+/// Ranks of the values from 1, tied values share the mean of their ranks
+pub fn ranks(x: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..x.len()).collect();
+    order.sort_by(|&a, &b| x[a].partial_cmp(&x[b]).unwrap_or(Ordering::Equal));
+
+    let mut ranks = vec![0.0; x.len()];
+    let mut start = 0;
+
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && x[order[end]] == x[order[start]] {
+            end = end + 1;
+        }
+
+        // Positions start..end hold ranks start + 1 to end
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+
+        start = end;
+    }
+
+    ranks
+}
+
+/// Wilcoxon rank-sum (Mann-Whitney U) test of whether two independent samples come from the
+/// same distribution
+pub fn rank_sum(x: &[f64], y: &[f64]) -> f64 {
+    let (nx, ny) = (x.len(), y.len());
+    if nx == 0 || ny == 0 {
+        return 1.0;
+    }
+
+    let combined: Vec<f64> = x.iter().chain(y).cloned().collect();
+    let ranks = ranks(&combined);
+    let rank_sum_x: f64 = ranks[..nx].iter().sum();
+    let u = rank_sum_x - (nx * (nx + 1)) as f64 / 2.0;
+
+    let ties = tie_correction(&combined);
+    if ties == 0.0 && nx.min(ny) < 10 && nx + ny < 20 {
+        return exact_p_value(&rank_sum_distribution(nx, ny), u);
+    }
+
+    let n = (nx + ny) as f64;
+    let mean = (nx * ny) as f64 / 2.0;
+    let variance = (nx * ny) as f64 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+
+    normal_p_value(u, mean, variance)
+}
+
+/// Wilcoxon signed-rank test of whether the differences of paired samples are centred on 0,
+/// pairs with no difference are left out
+pub fn signed_rank(x: &[f64], y: &[f64]) -> f64 {
+    assert_eq!(
+        x.len(),
+        y.len(),
+        "The samples of a paired test must be the same size"
+    );
+
+    let differences: Vec<f64> = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| x - y)
+        .filter(|d| *d != 0.0)
+        .collect();
+    let n = differences.len();
+    if n == 0 {
+        return 1.0;
+    }
+
+    let magnitudes: Vec<f64> = differences.iter().map(|d| d.abs()).collect();
+    let ranks = ranks(&magnitudes);
+    let w: f64 = ranks
+        .iter()
+        .zip(&differences)
+        .filter(|(_, d)| **d > 0.0)
+        .map(|(rank, _)| rank)
+        .sum();
+
+    let ties = tie_correction(&magnitudes);
+    if ties == 0.0 && n <= 15 {
+        return exact_p_value(&signed_rank_distribution(n), w);
+    }
+
+    let n = n as f64;
+    let mean = n * (n + 1.0) / 4.0;
+    let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - ties / 48.0;
+
+    normal_p_value(w, mean, variance)
+}
+
+/// Vargha-Delaney A12 effect size, the probability that a value from `x` is larger than one
+/// from `y`, counting ties as half. 0.5 is no effect, 0.71 or 0.29 are large effects.
+pub fn a12(x: &[f64], y: &[f64]) -> f64 {
+    if x.is_empty() || y.is_empty() {
+        return 0.5;
+    }
+
+    let mut wins = 0.0;
+    for a in x {
+        for b in y {
+            if a > b {
+                wins = wins + 1.0;
+            } else if a == b {
+                wins = wins + 0.5;
+            }
+        }
+    }
+
+    wins / (x.len() * y.len()) as f64
+}
+
+/// Holm-Bonferroni adjusted p-values of a family of tests, in the order they are given
+pub fn holm(p_values: &[f64]) -> Vec<f64> {
+    let m = p_values.len();
+
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|&a, &b| {
+        p_values[a]
+            .partial_cmp(&p_values[b])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let mut adjusted = vec![0.0; m];
+    let mut max_adjusted: f64 = 0.0;
+
+    for (i, &j) in order.iter().enumerate() {
+        max_adjusted = max_adjusted.max(((m - i) as f64 * p_values[j]).min(1.0));
+        adjusted[j] = max_adjusted;
+    }
+
+    adjusted
+}
+
+// Sum of t^3 - t over the groups of t tied values
+fn tie_correction(x: &[f64]) -> f64 {
+    let mut sorted = x.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let mut correction = 0.0;
+    let mut start = 0;
+
+    while start < sorted.len() {
+        let mut end = start + 1;
+        while end < sorted.len() && sorted[end] == sorted[start] {
+            end = end + 1;
+        }
+
+        let t = (end - start) as f64;
+        correction = correction + t.powi(3) - t;
+        start = end;
+    }
+
+    correction
+}
+
+// Number of orderings of nx and ny values giving each U statistic, the number of (x, y)
+// pairs with x above y
+fn rank_sum_distribution(nx: usize, ny: usize) -> Vec<f64> {
+    let max_u = nx * ny;
+
+    // counts[m][u] for m x values and n y values, starting from n = 0
+    let mut counts = vec![vec![0.0; max_u + 1]; nx + 1];
+    for row in counts.iter_mut() {
+        row[0] = 1.0;
+    }
+
+    for n in 1..=ny {
+        // The largest value is either a y, leaving U as it was with one y fewer, or an x,
+        // which is above all n y values
+        for m in 1..=nx {
+            for u in n..=max_u {
+                counts[m][u] = counts[m][u] + counts[m - 1][u - n];
+            }
+        }
+    }
+
+    counts.swap_remove(nx)
+}
+
+// Number of subsets of the ranks 1..=n with each sum
+fn signed_rank_distribution(n: usize) -> Vec<f64> {
+    let max_w = n * (n + 1) / 2;
+
+    let mut counts = vec![0.0; max_w + 1];
+    counts[0] = 1.0;
+
+    for rank in 1..=n {
+        for w in (rank..=max_w).rev() {
+            counts[w] = counts[w] + counts[w - rank];
+        }
+    }
+
+    counts
+}
+
+fn exact_p_value(counts: &[f64], statistic: f64) -> f64 {
+    let total: f64 = counts.iter().sum();
+    let statistic = statistic.round() as usize;
+
+    let lower: f64 = counts[..=statistic].iter().sum();
+    let upper: f64 = counts[statistic..].iter().sum();
+
+    (2.0 * lower.min(upper) / total).min(1.0)
+}
+
+fn normal_p_value(statistic: f64, mean: f64, variance: f64) -> f64 {
+    if variance <= 0.0 {
+        return 1.0;
+    }
+
+    let difference = statistic - mean;
+    let corrected = difference - 0.5 * difference.signum();
+    let z = if difference.abs() < 0.5 {
+        0.0
+    } else {
+        corrected / variance.sqrt()
+    };
+
+    erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0)
+}
+
+// Complementary error function, accurate to 1.2e-7 (Numerical Recipes, erfcc)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+
+    let poly = -1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let result = t * (-z * z + poly).exp();
+
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranks() {
+        assert_eq!(ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+        assert_eq!(tie_correction(&[3.0, 1.0, 3.0, 3.0]), 24.0);
+    }
+
+    #[test]
+    fn test_rank_sum() {
+        // Example of R's wilcox.test, W = 35 with a two-sided p-value of 0.2544
+        let x = vec![0.80, 0.83, 1.89, 1.04, 1.45, 1.38, 1.91, 1.64, 0.73, 1.46];
+        let y = vec![1.15, 0.88, 0.90, 0.74, 1.21];
+        assert!((rank_sum(&x, &y) - 0.2544).abs() < 1e-4);
+        assert!((rank_sum(&y, &x) - 0.2544).abs() < 1e-4);
+
+        assert_eq!(rank_sum(&x, &x), 1.0);
+
+        let x: Vec<f64> = (0..30).map(|i| i as f64).collect();
+        let y: Vec<f64> = (0..30).map(|i| (i + 25) as f64).collect();
+        assert!(rank_sum(&x, &y) < 1e-6);
+    }
+
+    #[test]
+    fn test_signed_rank() {
+        // Example of R's wilcox.test, V = 40 with a two-sided p-value of 0.0390625
+        let x = vec![1.83, 0.50, 1.62, 2.48, 1.68, 1.88, 1.55, 3.06, 1.30];
+        let y = vec![0.878, 0.647, 0.598, 2.05, 1.06, 1.29, 1.06, 3.14, 1.29];
+        assert!((signed_rank(&x, &y) - 0.0390625).abs() < 1e-9);
+
+        assert_eq!(signed_rank(&x, &x), 1.0);
+
+        let x: Vec<f64> = (0..30).map(|i| i as f64).collect();
+        let y: Vec<f64> = (0..30).map(|i| i as f64 - 1.0 - (i % 3) as f64).collect();
+        assert!(signed_rank(&x, &y) < 1e-5);
+    }
+
+    #[test]
+    fn test_a12() {
+        assert_eq!(a12(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]), 0.5);
+        assert_eq!(a12(&[4.0, 5.0], &[1.0, 2.0, 3.0]), 1.0);
+        assert_eq!(a12(&[1.0, 2.0], &[2.0]), 0.25);
+    }
+
+    #[test]
+    fn test_holm() {
+        let adjusted = holm(&[0.01, 0.04, 0.03]);
+        let expected = [0.03, 0.06, 0.06];
+
+        for (a, b) in adjusted.iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+}
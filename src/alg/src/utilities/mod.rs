@@ -1,4 +1,21 @@
+/// Serializes settings through `Display`, so a dump of the parameters can be parsed back
+macro_rules! serialize_display {
+    ($($setting:ty),*) => {
+        $(
+            impl serde::Serialize for $setting {
+                fn serialize<S: serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+        )*
+    };
+}
+
 pub mod math;
 pub mod metrics;
 pub mod nds;
+pub mod rng;
 pub mod stopwatch;
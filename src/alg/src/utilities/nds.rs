@@ -13,6 +13,14 @@ impl<X> NonDominatedSet<X> {
         }
     }
 
+    /// Rebuilds a set from the solutions of `get_raw`, keeping their order
+    pub fn from_raw(accept_duplicates: bool, archive: Vec<Solution<X>>) -> NonDominatedSet<X> {
+        NonDominatedSet {
+            accept_duplicates,
+            archive,
+        }
+    }
+
     pub fn try_push(&mut self, solution: Solution<X>) -> bool {
         let mut is_dominated = false;
         let mut is_duplicate = false;
@@ -78,7 +86,7 @@ impl<X> NonDominatedSet<X> {
 
 #[cfg(test)]
 mod tests {
-    use crate::operators::solution::Constraint;
+    use crate::operators::solution::{Constraint, Violation};
 
     use super::*;
 
@@ -104,11 +112,11 @@ mod tests {
         // All infeasible
         let mut i_set = NonDominatedSet::new(true);
 
-        solutions[0].objectives = Constraint::Infeasible(3);
-        solutions[1].objectives = Constraint::Infeasible(4);
-        solutions[2].objectives = Constraint::Infeasible(2);
-        solutions[3].objectives = Constraint::Infeasible(1);
-        solutions[4].objectives = Constraint::Infeasible(5);
+        solutions[0].objectives = Constraint::Infeasible(Violation::unplaced(3));
+        solutions[1].objectives = Constraint::Infeasible(Violation::unplaced(4));
+        solutions[2].objectives = Constraint::Infeasible(Violation::unplaced(2));
+        solutions[3].objectives = Constraint::Infeasible(Violation::unplaced(1));
+        solutions[4].objectives = Constraint::Infeasible(Violation::unplaced(5));
 
         let i_a = i_set.try_push(solutions[0].clone());
         let i_b = i_set.try_push(solutions[1].clone());
@@ -119,10 +127,10 @@ mod tests {
         // Mixed
         let mut m_set = NonDominatedSet::new(true);
 
-        solutions[0].objectives = Constraint::Infeasible(3);
-        solutions[1].objectives = Constraint::Infeasible(2);
+        solutions[0].objectives = Constraint::Infeasible(Violation::unplaced(3));
+        solutions[1].objectives = Constraint::Infeasible(Violation::unplaced(2));
         solutions[2].objectives = Constraint::Feasible(vec![3.0, 4.0]);
-        solutions[3].objectives = Constraint::Infeasible(1);
+        solutions[3].objectives = Constraint::Infeasible(Violation::unplaced(1));
         solutions[4].objectives = Constraint::Feasible(vec![4.0, 3.0]);
 
         let m_a = m_set.try_push(solutions[0].clone());
@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+
+use rand::{Error, Rng, RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+
+thread_local! {
+    static THREAD_RNG: RefCell<Pcg64> = RefCell::new(Pcg64::from_entropy());
+}
+
+/**
+ * Drop-in replacement for `rand::ThreadRng` whose state can be seeded. Every thread owns a
+ * generator that starts from entropy until `seed` is called on that thread.
+ **/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadRng;
+
+pub fn thread_rng() -> ThreadRng {
+    ThreadRng
+}
+
+impl RngCore for ThreadRng {
+    fn next_u32(&mut self) -> u32 {
+        THREAD_RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        THREAD_RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        THREAD_RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        THREAD_RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+/// Resets the generator of the current thread
+pub fn seed(seed: u64) {
+    THREAD_RNG.with(|rng| *rng.borrow_mut() = Pcg64::seed_from_u64(seed));
+}
+
+/// Copy of the current thread's generator, to save in a checkpoint
+pub fn state() -> Pcg64 {
+    THREAD_RNG.with(|rng| rng.borrow().clone())
+}
+
+/// Continues the current thread's generator from a saved state
+pub fn restore(state: Pcg64) {
+    THREAD_RNG.with(|rng| *rng.borrow_mut() = state);
+}
+
+/// A seed taken from entropy, for runs that were not given one
+pub fn random_seed() -> u64 {
+    to_seed(rand::thread_rng().gen())
+}
+
+/// A seed drawn from the current thread's generator
+pub fn next_seed() -> u64 {
+    to_seed(thread_rng().gen())
+}
+
+/// Seeds are written to config files, which store integers as i64
+pub fn to_seed(value: u64) -> u64 {
+    value >> 1
+}
+
+/**
+ * Seeds the current thread's generator for a parallel task and restores the previous state
+ * when the returned guard is dropped. Rayon decides which thread runs a task, and that can be
+ * the thread that started the parallel loop, so each task should hold one of these with its
+ * own seed from `task_seeds` for as long as it draws random numbers.
+ **/
+pub fn seed_task(seed: u64) -> TaskRng {
+    restore_task(Pcg64::seed_from_u64(seed))
+}
+
+/// Like `seed_task`, but continues a task from a saved state
+pub fn restore_task(state: Pcg64) -> TaskRng {
+    let previous = THREAD_RNG.with(|rng| rng.replace(state));
+    TaskRng { previous }
+}
+
+pub struct TaskRng {
+    previous: Pcg64,
+}
+
+impl Drop for TaskRng {
+    fn drop(&mut self) {
+        let previous = self.previous.clone();
+        THREAD_RNG.with(|rng| rng.replace(previous));
+    }
+}
+
+/// Draws a seed for each of `num_tasks` parallel tasks from the current thread's generator
+pub fn task_seeds(num_tasks: usize) -> Vec<u64> {
+    (0..num_tasks).map(|_| next_seed()).collect()
+}
+
+// ----- Unit tests ---- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    #[test]
+    fn test_seed() {
+        seed(42);
+        let a: Vec<u64> = (0..10).map(|_| thread_rng().gen()).collect();
+
+        seed(42);
+        let b: Vec<u64> = (0..10).map(|_| thread_rng().gen()).collect();
+
+        seed(43);
+        let c: Vec<u64> = (0..10).map(|_| thread_rng().gen()).collect();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        // A restored state continues the same stream
+        seed(42);
+        thread_rng().gen::<u64>();
+        let saved = state();
+        let d: Vec<u64> = (0..9).map(|_| thread_rng().gen()).collect();
+
+        restore(saved);
+        let e: Vec<u64> = (0..9).map(|_| thread_rng().gen()).collect();
+
+        assert_eq!(&a[1..], &d[..]);
+        assert_eq!(d, e);
+    }
+
+    #[test]
+    fn test_task_seeds() {
+        let run = || {
+            seed(7);
+
+            let values = task_seeds(64)
+                .into_par_iter()
+                .map(|task_seed| {
+                    let _rng = seed_task(task_seed);
+                    thread_rng().gen_range(0, 1000)
+                })
+                .collect::<Vec<usize>>();
+
+            // The tasks must not disturb the caller's stream
+            (values, next_seed())
+        };
+
+        assert_eq!(run(), run());
+    }
+}